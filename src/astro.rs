@@ -0,0 +1,164 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+
+/// Approximate length of the golden hour after sunrise and before sunset
+const GOLDEN_HOUR: Duration = Duration::minutes(60);
+
+/// Mean length of the synodic month (new moon to new moon) in days
+const SYNODIC_MONTH_DAYS: f64 = 29.530588853;
+
+/// Computes the morning and evening golden-hour windows for a day.
+///
+/// Each window is roughly the hour after sunrise or before sunset, shortened on
+/// days with less than two hours of daylight so the windows never overlap.
+pub fn golden_hours(
+    sunrise: NaiveDateTime,
+    sunset: NaiveDateTime,
+) -> [(NaiveDateTime, NaiveDateTime); 2] {
+    let half_day = (sunset - sunrise) / 2;
+    let length = GOLDEN_HOUR.min(half_day).max(Duration::zero());
+
+    [(sunrise, sunrise + length), (sunset - length, sunset)]
+}
+
+/// Approximates the illuminated fraction of the moon (0.0 new to 1.0 full) at a UTC time.
+///
+/// Uses the mean synodic month from a reference new moon, which is accurate to within
+/// a few percent; that is plenty for judging how much moonlight washes out the sky.
+pub fn moon_illumination(at_utc: NaiveDateTime) -> f64 {
+    let reference_new_moon = NaiveDate::from_ymd_opt(2000, 1, 6)
+        .and_then(|date| date.and_hms_opt(18, 14, 0))
+        .expect("valid reference date");
+    let days = (at_utc - reference_new_moon).num_seconds() as f64 / 86_400.0;
+    let phase = days.rem_euclid(SYNODIC_MONTH_DAYS) / SYNODIC_MONTH_DAYS;
+    (1.0 - (2.0 * std::f64::consts::PI * phase).cos()) / 2.0
+}
+
+/// Sun position events for one date, in UTC
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunTimes {
+    pub solar_noon: NaiveDateTime,
+    /// Sunrise and sunset; `None` during polar day or night
+    pub rise_and_set: Option<(NaiveDateTime, NaiveDateTime)>,
+    pub day_length: Duration,
+}
+
+/// Computes sunrise, sunset, and solar noon for a date with the NOAA sunrise equation.
+///
+/// Longitude is positive east. Times are accurate to about a minute away from the
+/// poles; sunrise and sunset use the standard -0.833 degree altitude, which accounts
+/// for refraction and the sun's radius.
+pub fn sun_times(latitude: f64, longitude: f64, date: NaiveDate) -> SunTimes {
+    const J2000: f64 = 2_451_545.0;
+    const UNIX_EPOCH_JULIAN: f64 = 2_440_587.5;
+
+    let j2000_date = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid reference date");
+    let day = (date - j2000_date).num_days() as f64 + 0.0008;
+    let mean_noon = day - longitude / 360.0;
+
+    let anomaly = (357.5291 + 0.985_600_28 * mean_noon).rem_euclid(360.0).to_radians();
+    let center = 1.9148 * anomaly.sin()
+        + 0.02 * (2.0 * anomaly).sin()
+        + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic_longitude =
+        (anomaly.to_degrees() + center + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+    let transit =
+        J2000 + mean_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+
+    let to_datetime = |julian: f64| {
+        let seconds = ((julian - UNIX_EPOCH_JULIAN) * 86_400.0).round() as i64;
+        DateTime::from_timestamp(seconds, 0)
+            .map(|t| t.naive_utc())
+            .unwrap_or_default()
+    };
+
+    let (rise_and_set, day_length) = if cos_hour_angle < -1.0 {
+        (None, Duration::hours(24))
+    } else if cos_hour_angle > 1.0 {
+        (None, Duration::zero())
+    } else {
+        let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+        let rise = to_datetime(transit - half_day);
+        let set = to_datetime(transit + half_day);
+        (Some((rise, set)), set - rise)
+    };
+
+    SunTimes {
+        solar_noon: to_datetime(transit),
+        rise_and_set,
+        day_length,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn golden_hours_span_an_hour_after_sunrise_and_before_sunset() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let [morning, evening] = golden_hours(at(date, 5, 30), at(date, 21, 10));
+        assert_eq!(morning, (at(date, 5, 30), at(date, 6, 30)));
+        assert_eq!(evening, (at(date, 20, 10), at(date, 21, 10)));
+    }
+
+    #[test]
+    fn golden_hours_never_overlap_on_short_days() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+        let [morning, evening] = golden_hours(at(date, 11, 0), at(date, 12, 30));
+        assert_eq!(morning, (at(date, 11, 0), at(date, 11, 45)));
+        assert_eq!(evening, (at(date, 11, 45), at(date, 12, 30)));
+    }
+
+    #[test]
+    fn moon_illumination_follows_the_lunar_cycle() {
+        // New moon on 2024-01-11 11:57 UTC, full moon on 2024-01-25 17:54 UTC
+        let new_moon = at(NaiveDate::from_ymd_opt(2024, 1, 11).unwrap(), 11, 57);
+        let full_moon = at(NaiveDate::from_ymd_opt(2024, 1, 25).unwrap(), 17, 54);
+        assert!(moon_illumination(new_moon) < 0.02);
+        assert!(moon_illumination(full_moon) > 0.98);
+    }
+
+    /// Asserts a computed time is within three minutes of an almanac time
+    fn assert_near(actual: NaiveDateTime, expected: NaiveDateTime) {
+        let difference = (actual - expected).num_seconds().abs();
+        assert!(difference <= 180, "{} is not within 3 minutes of {}", actual, expected);
+    }
+
+    #[test]
+    fn sun_times_match_almanac_values() {
+        // London at the June solstice: sunrise 04:43 BST, sunset 21:21 BST
+        let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+        let london = sun_times(51.5074, -0.1278, date);
+        let (rise, set) = london.rise_and_set.unwrap();
+        assert_near(rise, at(date, 3, 43));
+        assert_near(set, at(date, 20, 21));
+        assert_near(london.solar_noon, at(date, 12, 2));
+
+        // New York at the December solstice: sunrise 07:16 EST, sunset 16:32 EST
+        let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+        let new_york = sun_times(40.7128, -74.006, date);
+        let (rise, set) = new_york.rise_and_set.unwrap();
+        assert_near(rise, at(date, 12, 16));
+        assert_near(set, at(date, 21, 32));
+    }
+
+    #[test]
+    fn sun_times_report_polar_day_and_night() {
+        let summer = sun_times(69.65, 18.96, NaiveDate::from_ymd_opt(2024, 6, 21).unwrap());
+        assert_eq!(summer.rise_and_set, None);
+        assert_eq!(summer.day_length, Duration::hours(24));
+
+        let winter = sun_times(69.65, 18.96, NaiveDate::from_ymd_opt(2024, 12, 21).unwrap());
+        assert_eq!(winter.rise_and_set, None);
+        assert_eq!(winter.day_length, Duration::zero());
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Consecutive failures after which an upstream is short-circuited
+const FAILURE_THRESHOLD: u32 = 5;
+
+/// How long requests to a tripped upstream fail fast before one is let through again
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Tracks consecutive failures for one upstream host.
+///
+/// Once tripped, the breaker rejects requests until the cooldown passes. The failure
+/// count is only reset by a success, so a failed trial request re-trips it at once.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    failures: AtomicU32,
+    open_until_ms: AtomicU64,
+}
+
+impl CircuitBreaker {
+    /// Whether requests should currently be short-circuited
+    pub fn is_open(&self) -> bool {
+        now_ms() < self.open_until_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= FAILURE_THRESHOLD {
+            let open_until = now_ms() + COOLDOWN.as_millis() as u64;
+            self.open_until_ms.store(open_until, Ordering::Relaxed);
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trips_after_consecutive_failures() {
+        let breaker = CircuitBreaker::default();
+        for _ in 1..FAILURE_THRESHOLD {
+            breaker.record_failure();
+        }
+        assert!(!breaker.is_open());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = CircuitBreaker::default();
+        for _ in 1..FAILURE_THRESHOLD {
+            breaker.record_failure();
+        }
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,311 @@
+/// Chart size in pixels
+const WIDTH: usize = 240;
+const HEIGHT: usize = 80;
+const PADDING: usize = 6;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+
+/// Line colors for successive series: red for highs, blue for lows, then grey
+const SERIES_COLORS: [[u8; 3]; 3] = [[214, 69, 65], [52, 101, 164], [110, 110, 110]];
+
+/// Block characters used for text sparklines, lowest to highest
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Renders values as a one-line text sparkline
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+    values
+        .iter()
+        .map(|value| {
+            let level = if range > 0.0 {
+                ((value - min) / range * (SPARK_LEVELS.len() - 1) as f64).round() as usize
+            } else {
+                SPARK_LEVELS.len() / 2
+            };
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+/// Renders one or more series as a small PNG line chart sharing one vertical scale.
+///
+/// Returns `None` when there is nothing to plot: no series with at least two points,
+/// or values that aren't finite.
+pub fn line_chart_png(series: &[&[f64]]) -> Option<Vec<u8>> {
+    let values = || series.iter().flat_map(|s| s.iter().copied());
+    if values().any(|v| !v.is_finite()) || series.iter().all(|s| s.len() < 2) {
+        return None;
+    }
+    let min = values().fold(f64::INFINITY, f64::min);
+    let max = values().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+
+    let mut pixels = vec![BACKGROUND; WIDTH * HEIGHT];
+    let plot_width = (WIDTH - 2 * PADDING - 1) as f64;
+    let plot_height = (HEIGHT - 2 * PADDING - 1) as f64;
+    for (values, color) in series.iter().zip(SERIES_COLORS.iter().cycle()) {
+        let step = plot_width / (values.len().max(2) - 1) as f64;
+        let points: Vec<(i64, i64)> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let x = PADDING as f64 + i as f64 * step;
+                let y = PADDING as f64 + plot_height * (1.0 - (value - min) / range);
+                (x.round() as i64, y.round() as i64)
+            })
+            .collect();
+        for pair in points.windows(2) {
+            draw_line(&mut pixels, pair[0], pair[1], *color);
+        }
+    }
+
+    Some(encode_png(&pixels, WIDTH))
+}
+
+/// Draws a two-pixel-thick line with Bresenham's algorithm
+fn draw_line(pixels: &mut [[u8; 3]], from: (i64, i64), to: (i64, i64), color: [u8; 3]) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let sx = if x < to.0 { 1 } else { -1 };
+    let sy = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        for (px, py) in [(x, y), (x, y + 1)] {
+            if (0..WIDTH as i64).contains(&px) && (0..HEIGHT as i64).contains(&py) {
+                pixels[py as usize * WIDTH + px as usize] = color;
+            }
+        }
+        if (x, y) == to {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+}
+
+/// Encodes rows of `width` RGB pixels as a PNG, using uncompressed deflate blocks.
+///
+/// Charts are a few dozen kilobytes at most, so skipping compression keeps this
+/// free of extra dependencies at little cost.
+fn encode_png(pixels: &[[u8; 3]], width: usize) -> Vec<u8> {
+    let height = pixels.len() / width;
+    let mut raw = Vec::with_capacity(height * (1 + width * 3));
+    for row in pixels.chunks(width) {
+        raw.push(0); // filter type: none
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]); // 8-bit RGB, default compression/filter, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(data);
+        let crc = crc32(&png[start..]);
+        png.extend(crc.to_be_bytes());
+    }
+    png
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+/// Encodes bytes as standard padded base64, as MCP image content expects
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | ((byte as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes a PNG written by `encode_png`, checking every chunk CRC and the zlib
+    /// stream, and returns the image size and RGB pixels
+    fn decode_png(png: &[u8]) -> (usize, usize, Vec<[u8; 3]>) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (body, crc) = rest[4..].split_at(4 + len);
+            assert_eq!(crc32(body), u32::from_be_bytes(crc[..4].try_into().unwrap()));
+            chunks.push((body[..4].to_vec(), body[4..].to_vec()));
+            rest = &crc[4..];
+        }
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| kind.as_slice()).collect();
+        assert_eq!(kinds, [b"IHDR".as_slice(), b"IDAT", b"IEND"]);
+
+        let header = &chunks[0].1;
+        let width = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        assert_eq!(&header[8..], [8, 2, 0, 0, 0]);
+
+        // zlib: a valid header, stored deflate blocks, then the Adler-32 of the data
+        let zlib = &chunks[1].1;
+        assert_eq!(u16::from_be_bytes([zlib[0], zlib[1]]) % 31, 0);
+        let mut raw = Vec::new();
+        let mut position = 2;
+        loop {
+            let last = zlib[position] & 1 == 1;
+            assert_eq!(zlib[position] >> 1, 0, "only stored blocks are expected");
+            let len = u16::from_le_bytes([zlib[position + 1], zlib[position + 2]]);
+            let inverse = u16::from_le_bytes([zlib[position + 3], zlib[position + 4]]);
+            assert_eq!(len, !inverse);
+            position += 5;
+            raw.extend(&zlib[position..position + len as usize]);
+            position += len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(&zlib[position..], adler32(&raw).to_be_bytes());
+
+        let pixels = raw
+            .chunks(1 + width * 3)
+            .flat_map(|row| {
+                assert_eq!(row[0], 0, "filter type");
+                row[1..].chunks(3).map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            })
+            .collect();
+        (width, height, pixels)
+    }
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn adler32_matches_known_values() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn base64_matches_rfc_4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(base64_encode(input.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn chart_decodes_to_the_drawn_pixels() {
+        let png = line_chart_png(&[&[1.0, 3.0, 2.0], &[0.0, 1.0, 0.5]]).unwrap();
+        let (width, height, pixels) = decode_png(&png);
+        assert_eq!((width, height), (WIDTH, HEIGHT));
+        assert_eq!(pixels.len(), WIDTH * HEIGHT);
+        assert_eq!(pixels[0], BACKGROUND);
+        // Both series share the 0-3 scale: the first starts at 1.0, two thirds of
+        // the way down the plot, and the second at 0.0 along its bottom edge
+        let pixel = |x: usize, y: usize| pixels[y * WIDTH + x];
+        let plot_bottom = HEIGHT - PADDING - 1;
+        let two_thirds_down = (PADDING as f64 + (plot_bottom - PADDING) as f64 * 2.0 / 3.0).round();
+        assert_eq!(pixel(PADDING, two_thirds_down as usize), SERIES_COLORS[0]);
+        assert_eq!(pixel(PADDING, plot_bottom), SERIES_COLORS[1]);
+        assert_eq!(pixel(PADDING - 1, plot_bottom), BACKGROUND);
+    }
+
+    #[test]
+    fn chart_needs_two_finite_points() {
+        assert_eq!(line_chart_png(&[&[1.0]]), None);
+        assert_eq!(line_chart_png(&[&[1.0, f64::NAN]]), None);
+    }
+
+    #[test]
+    fn sparkline_spans_the_levels() {
+        assert_eq!(sparkline(&[0.0, 7.0, 3.5]), "▁█▅");
+        assert_eq!(sparkline(&[2.0, 2.0]), "▅▅");
+    }
+
+    #[test]
+    fn single_pixel_png_matches_a_reference_encoding() {
+        // Built with Python's zlib.compress(level=0) and zlib.crc32, which write the
+        // same stored-block stream
+        let expected: &[u8] = b"\x89PNG\r\n\x1a\n\
+            \x00\x00\x00\x0dIHDR\x00\x00\x00\x01\x00\x00\x00\x01\x08\x02\x00\x00\x00\x90wS\xde\
+            \x00\x00\x00\x0fIDATx\x01\x01\x04\x00\xfb\xff\x00\xd6EA\x03Q\x01]\x94Xp\x12\
+            \x00\x00\x00\x00IEND\xaeB`\x82";
+        assert_eq!(encode_png(&[SERIES_COLORS[0]], 1), expected);
+    }
+
+    #[test]
+    fn large_images_span_several_stored_blocks() {
+        // 25,000 pixels make a 75,001-byte row, more than one stored block holds
+        let pixels: Vec<[u8; 3]> = (0..25_000u32).map(|i| [i as u8, (i >> 8) as u8, 7]).collect();
+        let png = encode_png(&pixels, pixels.len());
+        let (width, height, decoded) = decode_png(&png);
+        assert_eq!((width, height), (25_000, 1));
+        assert_eq!(decoded, pixels);
+    }
+}
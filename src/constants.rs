@@ -4,5 +4,151 @@ pub const USER_AGENT: &str = "mcp-rust-weather-server/0.1.0";
 /// National Weather Service API base URL
 pub const NWS_API_BASE: &str = "https://api.weather.gov";
 
+/// Attribution footer for NWS-sourced output
+pub const NWS_ATTRIBUTION: &str = "Data: National Weather Service (weather.gov)";
+
 /// Open-Meteo API base URL
 pub const OPEN_METEO_API_BASE: &str = "https://api.open-meteo.com/v1";
+
+/// Attribution footer for Open-Meteo-sourced output, as required by its CC BY 4.0 license
+pub const OPEN_METEO_ATTRIBUTION: &str = "Data: Open-Meteo.com (CC BY 4.0)";
+
+/// Open-Meteo historical weather API base URL
+pub const OPEN_METEO_ARCHIVE_API_BASE: &str = "https://archive-api.open-meteo.com/v1";
+
+/// Open-Meteo geocoding API base URL
+pub const OPEN_METEO_GEOCODING_API_BASE: &str = "https://geocoding-api.open-meteo.com/v1";
+
+/// Nominatim (OpenStreetMap) search API base URL, the fallback geocoder
+pub const NOMINATIM_API_BASE: &str = "https://nominatim.openstreetmap.org";
+
+/// Open-Meteo ensemble API base URL
+pub const OPEN_METEO_ENSEMBLE_API_BASE: &str = "https://ensemble-api.open-meteo.com/v1";
+
+/// Open-Meteo air quality API base URL
+pub const OPEN_METEO_AIR_QUALITY_API_BASE: &str = "https://air-quality-api.open-meteo.com/v1";
+
+/// Environment variable overriding the NWS API base URL
+pub const NWS_BASE_ENV_VAR: &str = "MCP_WEATHER_NWS_BASE";
+
+/// Environment variable overriding the Open-Meteo API base URL
+pub const OPEN_METEO_BASE_ENV_VAR: &str = "MCP_WEATHER_OPEN_METEO_BASE";
+
+/// Environment variable overriding the Open-Meteo historical weather API base URL
+pub const OPEN_METEO_ARCHIVE_BASE_ENV_VAR: &str = "MCP_WEATHER_ARCHIVE_BASE";
+
+/// Environment variable overriding the Open-Meteo geocoding API base URL
+pub const OPEN_METEO_GEOCODING_BASE_ENV_VAR: &str = "MCP_WEATHER_GEOCODING_BASE";
+
+/// Environment variable overriding the Open-Meteo ensemble API base URL
+pub const OPEN_METEO_ENSEMBLE_BASE_ENV_VAR: &str = "MCP_WEATHER_ENSEMBLE_BASE";
+
+/// Environment variable overriding the Open-Meteo air quality API base URL
+pub const OPEN_METEO_AIR_QUALITY_BASE_ENV_VAR: &str = "MCP_WEATHER_AIR_QUALITY_BASE";
+
+/// Environment variable overriding the Nominatim fallback geocoder base URL
+pub const NOMINATIM_BASE_ENV_VAR: &str = "MCP_WEATHER_NOMINATIM_BASE";
+
+/// Environment variable setting the units used when a request omits `units`
+pub const DEFAULT_UNITS_ENV_VAR: &str = "MCP_WEATHER_DEFAULT_UNITS";
+
+/// Environment variable capping how many days ahead forecast tools may look
+pub const MAX_FORECAST_DAYS_ENV_VAR: &str = "MCP_WEATHER_MAX_FORECAST_DAYS";
+
+/// Environment variable capping how many days of history archive tools may request
+pub const MAX_ARCHIVE_DAYS_ENV_VAR: &str = "MCP_WEATHER_MAX_ARCHIVE_DAYS";
+
+/// Open-Meteo's forecast horizon, the default forecast cap
+pub const OPEN_METEO_MAX_FORECAST_DAYS: u32 = 16;
+
+/// Most locations any tool accepts in a single request
+pub const MAX_BATCH_LOCATIONS: usize = 10;
+
+/// Environment variable setting the overall per-request HTTP timeout, in seconds
+pub const HTTP_TIMEOUT_ENV_VAR: &str = "MCP_WEATHER_HTTP_TIMEOUT_SECS";
+
+/// Environment variable capping idle pooled connections kept per upstream host
+pub const POOL_MAX_IDLE_ENV_VAR: &str = "MCP_WEATHER_POOL_MAX_IDLE";
+
+/// Environment variable setting how long idle pooled connections are kept, in seconds
+pub const POOL_IDLE_TIMEOUT_ENV_VAR: &str = "MCP_WEATHER_POOL_IDLE_TIMEOUT_SECS";
+
+/// Environment variable naming a directory to cache forecast responses in across
+/// restarts; no disk cache when unset
+pub const CACHE_DIR_ENV_VAR: &str = "MCP_WEATHER_CACHE_DIR";
+
+/// Environment variable listing the tools to expose, comma-separated; all when unset
+pub const ENABLED_TOOLS_ENV_VAR: &str = "MCP_WEATHER_ENABLED_TOOLS";
+
+/// Environment variable that enables the debug_fetch tool
+pub const DEBUG_ENV_VAR: &str = "MCP_WEATHER_ENABLE_DEBUG";
+
+/// Environment variable overriding the WMO weather codes treated as adverse weather,
+/// as a comma-separated list
+pub const BAD_CODES_ENV_VAR: &str = "MCP_WEATHER_BAD_CODES";
+
+/// WMO weather codes treated as adverse by default: drizzle, rain, snow, showers,
+/// and thunderstorms
+pub const DEFAULT_ADVERSE_WEATHER_CODES: &[i32] = &[
+    51, 53, 55, 56, 57, 61, 63, 65, 66, 67, 71, 73, 75, 77, 80, 81, 82, 85, 86, 95, 96, 99,
+];
+
+/// US state and territory codes accepted by the NWS alerts `area` parameter
+pub const US_STATES: &[(&str, &str)] = &[
+    ("AL", "Alabama"),
+    ("AK", "Alaska"),
+    ("AZ", "Arizona"),
+    ("AR", "Arkansas"),
+    ("CA", "California"),
+    ("CO", "Colorado"),
+    ("CT", "Connecticut"),
+    ("DE", "Delaware"),
+    ("DC", "District of Columbia"),
+    ("FL", "Florida"),
+    ("GA", "Georgia"),
+    ("HI", "Hawaii"),
+    ("ID", "Idaho"),
+    ("IL", "Illinois"),
+    ("IN", "Indiana"),
+    ("IA", "Iowa"),
+    ("KS", "Kansas"),
+    ("KY", "Kentucky"),
+    ("LA", "Louisiana"),
+    ("ME", "Maine"),
+    ("MD", "Maryland"),
+    ("MA", "Massachusetts"),
+    ("MI", "Michigan"),
+    ("MN", "Minnesota"),
+    ("MS", "Mississippi"),
+    ("MO", "Missouri"),
+    ("MT", "Montana"),
+    ("NE", "Nebraska"),
+    ("NV", "Nevada"),
+    ("NH", "New Hampshire"),
+    ("NJ", "New Jersey"),
+    ("NM", "New Mexico"),
+    ("NY", "New York"),
+    ("NC", "North Carolina"),
+    ("ND", "North Dakota"),
+    ("OH", "Ohio"),
+    ("OK", "Oklahoma"),
+    ("OR", "Oregon"),
+    ("PA", "Pennsylvania"),
+    ("RI", "Rhode Island"),
+    ("SC", "South Carolina"),
+    ("SD", "South Dakota"),
+    ("TN", "Tennessee"),
+    ("TX", "Texas"),
+    ("UT", "Utah"),
+    ("VT", "Vermont"),
+    ("VA", "Virginia"),
+    ("WA", "Washington"),
+    ("WV", "West Virginia"),
+    ("WI", "Wisconsin"),
+    ("WY", "Wyoming"),
+    ("AS", "American Samoa"),
+    ("GU", "Guam"),
+    ("MP", "Northern Mariana Islands"),
+    ("PR", "Puerto Rico"),
+    ("VI", "U.S. Virgin Islands"),
+];
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How long a cached response is served before it is fetched again
+const TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Upstream responses stored as one JSON file per key, so they survive restarts.
+///
+/// Keys are request URLs with their coordinates rounded, so requests for the same
+/// point and parameters share an entry. Entries expire by file modification time.
+/// Disk errors are logged and treated as misses; the cache never fails a request.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the cached body for a key if it was stored within the TTL
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.path(key);
+        let age = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age > TTL {
+            return None;
+        }
+        std::fs::read(&path).ok()
+    }
+
+    /// Stores a body under a key, replacing any earlier entry
+    pub fn put(&self, key: &str, body: &[u8]) {
+        let path = self.path(key);
+        // Write then rename so a concurrent reader never sees a partial file
+        let partial = path.with_extension("json.tmp");
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&partial, body))
+            .and_then(|_| std::fs::rename(&partial, &path));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write cache file {}: {}", path.display(), e);
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+}
+
+/// 64-bit FNV-1a hash; unlike `DefaultHasher` it is stable across Rust releases,
+/// which keeps file names valid between builds
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
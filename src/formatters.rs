@@ -1,4 +1,260 @@
-use crate::models::{AlertResponse, ForecastResponse, OpenMeteoResponse};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use std::sync::OnceLock;
+
+use crate::astro::{golden_hours, SunTimes};
+use crate::constants::{BAD_CODES_ENV_VAR, DEFAULT_ADVERSE_WEATHER_CODES};
+use crate::models::{
+    ActivityResponse, AlertProperties, AlertResponse, CurrentWeather, DailyUnits, ForecastArrays,
+    ForecastPeriod, ForecastResponse, GeocodingResult, GetAreaForecastRequest, HourlyData,
+    ObservationProperties, OpenMeteoCurrentResponse, OpenMeteoHourlyResponse, OpenMeteoResponse,
+    PollenResponse, SnowfallResponse, StationProperties,
+};
+use crate::stats::{
+    activity_score, compare_highs, gust_factor, linear_slope, snow_impact, wind_verdict, Activity,
+    ActivityWeather, DailyExtremes, DegreeDays, EventScore, HighChange, PrecipitationBlock,
+    Quartiles, SnowImpact, StargazingRating, TravelImpact, WindSport, WindVerdict,
+    EVENT_SCORE_WEIGHTS,
+};
+use crate::units::{
+    celsius_to_fahrenheit, celsius_to_kelvin, fahrenheit_to_celsius, heat_index_f, mph_to_kmh,
+    to_beaufort, wind_chill_f, SpeedUnit, Units, WindScale,
+};
+
+/// Display style for Open-Meteo forecast dates
+#[derive(Debug, Clone, Copy, Default)]
+pub enum DateFormat {
+    /// "2024-06-15", as returned by the API
+    #[default]
+    Iso,
+    /// "Sat Jun 15"
+    Short,
+    /// "Saturday"
+    Weekday,
+}
+
+impl DateFormat {
+    /// Parses a user-supplied format name, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "iso" => Some(Self::Iso),
+            "short" => Some(Self::Short),
+            "weekday" => Some(Self::Weekday),
+            _ => None,
+        }
+    }
+}
+
+/// Clock style for times of day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// "13:05"
+    #[default]
+    TwentyFourHour,
+    /// "1:05 PM"
+    TwelveHour,
+}
+
+impl TimeFormat {
+    /// Parses a user-supplied time format name, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "24h" => Some(Self::TwentyFourHour),
+            "12h" => Some(Self::TwelveHour),
+            _ => None,
+        }
+    }
+
+    /// Formats the time of day; midnight is "12:00 AM" and noon "12:00 PM" in 12h style
+    pub fn format(self, time: NaiveDateTime) -> String {
+        match self {
+            Self::TwentyFourHour => time.format("%H:%M").to_string(),
+            Self::TwelveHour => time.format("%-I:%M %p").to_string(),
+        }
+    }
+}
+
+/// Overall shape of the forecast output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Structured per-day text blocks
+    #[default]
+    Text,
+    /// Flowing prose suited to being read aloud
+    Narrative,
+    /// JSON parallel arrays of the Open-Meteo daily data, for charting
+    Arrays,
+    /// A GeoJSON Feature of the Open-Meteo daily data, for mapping tools
+    GeoJson,
+}
+
+impl OutputFormat {
+    /// Parses a user-supplied format name, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" => Some(Self::Text),
+            "narrative" => Some(Self::Narrative),
+            "arrays" => Some(Self::Arrays),
+            "geojson" => Some(Self::GeoJson),
+            _ => None,
+        }
+    }
+}
+
+/// Presentation options for the Open-Meteo forecast formatter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FormatOptions {
+    pub format: OutputFormat,
+    pub date_format: DateFormat,
+    pub units: Units,
+    pub wind_scale: WindScale,
+    pub time_format: TimeFormat,
+    /// Mark the day with the most severe weather code
+    pub highlight_worst: bool,
+    /// Show golden-hour windows derived from sunrise and sunset
+    pub show_golden_hour: bool,
+    /// Note when an hour of the day was more severe than the daily weather code
+    pub include_hourly_worst: bool,
+    /// Print "0.0 mm" for dry days instead of "none"
+    pub show_zero_precipitation: bool,
+    /// Add an arrow for the wind direction
+    pub wind_arrows: bool,
+}
+
+/// Arrows for compass bearings from north, clockwise in 45° steps
+const COMPASS_ARROWS: [char; 8] = [
+    '\u{2191}', '\u{2197}', '\u{2192}', '\u{2198}', '\u{2193}', '\u{2199}', '\u{2190}', '\u{2196}',
+];
+
+/// Points of the 16-wind compass rose, clockwise from north
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// Maps a compass bearing in degrees to the nearest of eight arrows, e.g. 0° to ↑
+pub fn degrees_to_arrow(degrees: f64) -> char {
+    let step = (degrees.rem_euclid(360.0) / 45.0).round() as usize % 8;
+    COMPASS_ARROWS[step]
+}
+
+/// Maps a compass point such as "N" or "SE" to its arrow, or `None` if unrecognized.
+/// Half-way points like "NNE" round to the nearest arrow.
+pub fn cardinal_to_arrow(direction: &str) -> Option<char> {
+    let direction = direction.trim().to_ascii_uppercase();
+    let index = COMPASS_POINTS.iter().position(|point| *point == direction)?;
+    Some(degrees_to_arrow(index as f64 * 22.5))
+}
+
+/// Maps a compass bearing in degrees to the nearest of the 16 compass points
+pub fn degrees_to_cardinal(degrees: f64) -> &'static str {
+    let step = (degrees.rem_euclid(360.0) / 22.5).round() as usize % 16;
+    COMPASS_POINTS[step]
+}
+
+/// Appends the direction's arrow when arrows are on and the direction is recognized
+fn with_arrow(direction: &str, arrows: bool) -> String {
+    match cardinal_to_arrow(direction).filter(|_| arrows) {
+        Some(arrow) => format!("{} {}", direction, arrow),
+        None => direction.to_string(),
+    }
+}
+
+/// Formats coordinates for display with 4 decimal places, e.g. "52.5200, 13.4100"
+pub fn format_coords(latitude: f64, longitude: f64) -> String {
+    format!("{:.4}, {:.4}", latitude, longitude)
+}
+
+/// Describes a geocoded place as "Name, Region, Country" with its coordinates
+pub fn format_place(place: &GeocodingResult) -> String {
+    let mut parts = vec![place.name.as_str()];
+    parts.extend(place.admin1.as_deref().filter(|admin1| *admin1 != place.name));
+    parts.extend(place.country.as_deref());
+    format!(
+        "{} ({})",
+        parts.join(", "),
+        format_coords(place.latitude, place.longitude)
+    )
+}
+
+/// Formats state codes and names as a two-column list
+pub fn format_states(states: &[(&str, &str)]) -> String {
+    let mut output = String::from("Supported US States and Territories:\n\n");
+    for (code, name) in states {
+        output.push_str(&format!("  {}  {}\n", code, name));
+    }
+    output
+}
+
+/// NWS alert parameters worth surfacing, with their display labels
+const ALERT_PARAMETERS: &[(&str, &str)] = &[
+    ("maxWindGust", "Max Wind Gust"),
+    ("maxHailSize", "Max Hail Size"),
+    ("tornadoDetection", "Tornado"),
+    ("thunderstormDamageThreat", "Damage Threat"),
+    ("flashFloodDetection", "Flash Flood"),
+];
+
+/// Named HTML entities that turn up in NWS alert text
+const HTML_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", ' '),
+];
+
+/// Decodes common HTML entities and collapses runs of blank lines in alert text.
+///
+/// Numeric entities such as `&#39;` and `&#x27;` are decoded too; anything else that
+/// looks like an entity is left as written.
+fn clean_alert_text(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|name| match name.strip_prefix('#') {
+            Some(number) => match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => number.parse().ok(),
+            }
+            .and_then(char::from_u32),
+            None => HTML_ENTITIES
+                .iter()
+                .find(|(entity, _)| *entity == name)
+                .map(|(_, character)| *character),
+        });
+        match (entity, character) {
+            (Some(name), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[name.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    // Keep paragraph breaks but drop the extra blank lines between them
+    let mut output = String::with_capacity(decoded.len());
+    let mut blank_lines = 0;
+    for line in decoded.replace("\r\n", "\n").lines() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !output.is_empty() {
+            output.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        output.push_str(line.trim_end());
+        blank_lines = 0;
+    }
+    output
+}
 
 /// Formats weather alerts into a human-readable string
 pub fn format_alerts(alerts: AlertResponse) -> String {
@@ -6,67 +262,2392 @@ pub fn format_alerts(alerts: AlertResponse) -> String {
         return "No active weather alerts.".to_string();
     }
 
-    let mut output = String::from("Active Weather Alerts:\n\n");
-    for (i, feature) in alerts.features.iter().enumerate() {
-        let props = &feature.properties;
+    let mut output = String::from("Active Weather Alerts:\n\n");
+    for (i, feature) in alerts.features.iter().enumerate() {
+        let props = &feature.properties;
+        let marker = props
+            .message_marker()
+            .map(|marker| format!(" ({})", marker))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "Alert {}{}:\n  ID: {}\n  Event: {}\n  Severity: {}\n  Area: {}\n",
+            i + 1,
+            marker,
+            props.id,
+            props.event,
+            props.severity,
+            clean_alert_text(&props.area_desc)
+        ));
+        if let Some(headline) = &props.headline {
+            output.push_str(&format!("  Headline: {}\n", clean_alert_text(headline)));
+        }
+        if let Some(parameters) = &props.parameters {
+            for (key, label) in ALERT_PARAMETERS {
+                if let Some(values) = parameters.get(*key).filter(|v| !v.is_empty()) {
+                    output.push_str(&format!("  {}: {}\n", label, values.join(", ")));
+                }
+            }
+        }
+        if let Some(description) = &props.description {
+            output.push_str(&format!("  Description: {}\n", clean_alert_text(description)));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Formats weather alerts as one terse line each
+pub fn format_alert_headlines(alerts: AlertResponse) -> String {
+    if alerts.features.is_empty() {
+        return "No active weather alerts.".to_string();
+    }
+
+    let mut output = format!("Active Weather Alerts ({}):\n", alerts.features.len());
+    for feature in &alerts.features {
+        let props = &feature.properties;
+        let marker = props
+            .message_marker()
+            .map(|marker| format!(" [{}]", marker))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "- {}{} ({}): {}\n",
+            props.event, marker, props.severity, props.area_desc
+        ));
+    }
+    output
+}
+
+/// Formats the alerts that are new since the last poll, followed by expired alert IDs
+pub fn format_alert_changes(new_alerts: AlertResponse, expired_ids: &[String]) -> String {
+    let mut output = if new_alerts.features.is_empty() {
+        String::from("No new weather alerts.\n\n")
+    } else {
+        format_alerts(new_alerts).replacen("Active Weather Alerts:", "New Weather Alerts:", 1)
+    };
+
+    if expired_ids.is_empty() {
+        output.push_str("No previously seen alerts have expired.\n");
+    } else {
+        output.push_str("Expired Alerts:\n");
+        for id in expired_ids {
+            output.push_str(&format!("  {}\n", id));
+        }
+    }
+    output
+}
+
+/// Formats the updates collected by an alert watch, oldest first
+pub fn format_alert_watch(state: &str, minutes: u64, polls: usize, updates: &[String]) -> String {
+    let mut output = format!(
+        "Alert watch for {}: {} min, {} polls, {} updates\n",
+        state,
+        minutes,
+        polls,
+        updates.len()
+    );
+    for update in updates {
+        output.push('\n');
+        output.push_str(update);
+    }
+    output
+}
+
+/// Appended when a forecast is cut short to fit a character limit
+const TRUNCATION_NOTE: &str = "\u{2026} (truncated, request fewer days for full detail)\n";
+
+/// A formatted forecast: a header followed by one entry per period or day
+#[derive(Debug, Clone)]
+pub struct FormattedForecast {
+    pub header: String,
+    pub entries: Vec<String>,
+    /// Trailing text such as a data source attribution
+    pub footer: String,
+}
+
+impl FormattedForecast {
+    /// Joins the header and all entries into a single string
+    pub fn into_text(self) -> String {
+        let mut output = self.header;
+        output.extend(self.entries);
+        output.push_str(&self.footer);
+        output
+    }
+
+    /// Drops trailing entries so the text fits within `max_chars`, noting the truncation.
+    ///
+    /// Entries are never split; the header and footer are always kept. Lengths are
+    /// counted in characters rather than bytes, so accented place names and the degree
+    /// sign count once each.
+    pub fn truncate(&mut self, max_chars: usize) {
+        let total: usize = std::iter::once(&self.header)
+            .chain(&self.entries)
+            .chain(std::iter::once(&self.footer))
+            .map(|part| part.chars().count())
+            .sum();
+        if total <= max_chars {
+            return;
+        }
+
+        let budget = max_chars.saturating_sub(TRUNCATION_NOTE.chars().count());
+        let mut used = self.header.chars().count() + self.footer.chars().count();
+        let keep = self
+            .entries
+            .iter()
+            .take_while(|entry| {
+                used += entry.chars().count();
+                used <= budget
+            })
+            .count();
+        self.entries.truncate(keep);
+
+        match self.entries.last_mut() {
+            Some(last) => last.push_str(TRUNCATION_NOTE),
+            None => self.header.push_str(TRUNCATION_NOTE),
+        }
+    }
+
+    /// Returns one string per entry, with the header attached to the first and the
+    /// footer to the last
+    pub fn into_blocks(self) -> Vec<String> {
+        let mut entries = self.entries.into_iter();
+        let first = match entries.next() {
+            Some(entry) => format!("{}{}", self.header, entry),
+            None => self.header,
+        };
+        let mut blocks: Vec<String> = std::iter::once(first)
+            .chain(entries)
+            .map(|block| block.trim_end().to_string())
+            .collect();
+
+        if let Some(last) = blocks.last_mut().filter(|_| !self.footer.is_empty()) {
+            last.push_str("\n\n");
+            last.push_str(self.footer.trim_end());
+        }
+        blocks
+    }
+}
+
+/// Formats NWS forecast into a human-readable string
+pub fn format_forecast(
+    forecast: ForecastResponse,
+    latitude: f64,
+    longitude: f64,
+    time_format: TimeFormat,
+    wind_arrows: bool,
+) -> FormattedForecast {
+    let entries = forecast
+        .properties
+        .periods
+        .into_iter()
+        .map(|period| {
+            let feels_like = match feels_like_f(&period) {
+                Some(value) => format!("  Feels Like: {:.0}\u{00b0}F\n", value),
+                None => String::new(),
+            };
+            let span = match period_span(&period, time_format) {
+                Some(span) => format!("  Period: {}\n", span),
+                None => String::new(),
+            };
+            let trend = match period.temperature_trend.as_deref().map(str::trim) {
+                Some(trend) if !trend.is_empty() => format!(" and {}", trend.to_lowercase()),
+                _ => String::new(),
+            };
+            format!(
+                "{}:\n{}  Temperature: {}\u{00b0}{}{}\n{}  Wind: {} {}\n  Conditions: {}\n  Details: {}\n\n",
+                period.name,
+                span,
+                period.temperature,
+                period.temperature_unit(),
+                trend,
+                feels_like,
+                period.wind_speed,
+                with_arrow(&period.wind_direction, wind_arrows),
+                period.short_forecast,
+                period.detailed_forecast
+            )
+        })
+        .collect();
+
+    FormattedForecast {
+        header: nws_header(latitude, longitude),
+        entries,
+        footer: String::new(),
+    }
+}
+
+/// Formats NWS forecast with each day/night pair merged into a single entry
+pub fn format_forecast_merged(
+    forecast: ForecastResponse,
+    latitude: f64,
+    longitude: f64,
+) -> FormattedForecast {
+    let mut entries = Vec::new();
+    let mut periods = forecast.properties.periods.into_iter().peekable();
+
+    while let Some(period) = periods.next() {
+        // A leading night period (e.g. "Tonight") has no daytime half to pair with
+        if !period.is_daytime {
+            entries.push(format!(
+                "{}:\n  Low: {}\u{00b0}{}\n  Wind: {} {}\n  Conditions: {}\n\n",
+                period.name,
+                period.temperature,
+                period.temperature_unit(),
+                period.wind_speed,
+                period.wind_direction,
+                period.short_forecast
+            ));
+            continue;
+        }
+
+        // Only pair with the night that begins on the same calendar date
+        let day_date = period.start().map(|start| start.date_naive());
+        let same_day_night = |next: &ForecastPeriod| {
+            !next.is_daytime && next.start().map(|start| start.date_naive()) == day_date
+        };
+
+        entries.push(match periods.next_if(same_day_night) {
+            Some(night) => format!(
+                "{}:\n  High: {}\u{00b0}{}, Low: {}\u{00b0}{}\n  Wind: {} {}\n  Conditions: {}, then {}\n\n",
+                period.name,
+                period.temperature,
+                period.temperature_unit(),
+                night.temperature,
+                night.temperature_unit(),
+                period.wind_speed,
+                period.wind_direction,
+                period.short_forecast,
+                night.short_forecast
+            ),
+            None => format!(
+                "{}:\n  High: {}\u{00b0}{}\n  Wind: {} {}\n  Conditions: {}\n\n",
+                period.name,
+                period.temperature,
+                period.temperature_unit(),
+                period.wind_speed,
+                period.wind_direction,
+                period.short_forecast
+            ),
+        });
+    }
+
+    FormattedForecast {
+        header: nws_header(latitude, longitude),
+        entries,
+        footer: String::new(),
+    }
+}
+
+/// Describes when an NWS period starts and ends in its local time
+fn period_span(period: &ForecastPeriod, time_format: TimeFormat) -> Option<String> {
+    let (start, end) = (period.start()?, period.end()?);
+    let (start, end) = (start.naive_local(), end.naive_local());
+    if start.date() == end.date() {
+        Some(format!(
+            "{}, {}-{}",
+            start.format("%a %b %-d"),
+            time_format.format(start),
+            time_format.format(end)
+        ))
+    } else {
+        Some(format!(
+            "{} {} - {} {}",
+            start.format("%a %b %-d"),
+            time_format.format(start),
+            end.format("%a %b %-d"),
+            time_format.format(end)
+        ))
+    }
+}
+
+/// Computes wind chill or heat index for an NWS period when it differs noticeably
+/// from the air temperature
+fn feels_like_f(period: &ForecastPeriod) -> Option<f64> {
+    if period.temperature_unit() != "F" {
+        return None;
+    }
+
+    let temperature = f64::from(period.temperature);
+    let humidity = period.relative_humidity.as_ref().and_then(|rh| rh.value);
+
+    let apparent = parse_wind_speed_mph(&period.wind_speed)
+        .and_then(|wind| wind_chill_f(temperature, wind))
+        .or_else(|| humidity.and_then(|rh| heat_index_f(temperature, rh)))?;
+
+    ((apparent - temperature).abs() >= 1.0).then_some(apparent)
+}
+
+/// Extracts the highest speed from NWS wind strings like "10 mph" or "5 to 15 mph"
+fn parse_wind_speed_mph(wind_speed: &str) -> Option<f64> {
+    wind_speed
+        .split_whitespace()
+        .rev()
+        .find_map(|part| part.parse::<f64>().ok())
+}
+
+/// Sustained wind, in mph, flagged as a hazard in a safety briefing
+const HIGH_WIND_MPH: f64 = 30.0;
+
+/// Words in an NWS short forecast that make a period hazardous, with the hazard
+/// named in a safety briefing; the first match wins
+const FORECAST_HAZARDS: &[(&str, &str)] = &[
+    ("blizzard", "Blizzard"),
+    ("thunderstorm", "Thunderstorms"),
+    ("heavy rain", "Heavy rain"),
+    ("freezing", "Freezing rain or drizzle"),
+    ("sleet", "Sleet"),
+    ("heavy snow", "Heavy snow"),
+];
+
+/// Formats a safety briefing: active alerts, most severe first with their
+/// instructions, then hazards in the forecast periods given.
+///
+/// Cancelled alerts are left out. Forecast hazards are sustained wind of at least
+/// `HIGH_WIND_MPH` and the conditions in `FORECAST_HAZARDS`.
+pub fn format_safety_briefing(
+    latitude: f64,
+    longitude: f64,
+    alerts: AlertResponse,
+    periods: &[ForecastPeriod],
+) -> String {
+    let mut output = format!(
+        "Safety Briefing (NWS)\nLocation: {}\n\n",
+        format_coords(latitude, longitude)
+    );
+
+    let mut alerts: Vec<AlertProperties> = alerts
+        .features
+        .into_iter()
+        .map(|feature| feature.properties)
+        .filter(|props| !props.is_cancel())
+        .collect();
+    // Stable, so alerts of equal severity keep the order NWS lists them in
+    alerts.sort_by_key(|props| std::cmp::Reverse(props.severity_rank()));
+
+    let hazards: Vec<String> = periods
+        .iter()
+        .filter_map(|period| {
+            let short_forecast = period.short_forecast.to_ascii_lowercase();
+            let mut found: Vec<String> = FORECAST_HAZARDS
+                .iter()
+                .find(|(keyword, _)| short_forecast.contains(keyword))
+                .map(|(_, hazard)| format!("{} ({})", hazard, period.short_forecast))
+                .into_iter()
+                .collect();
+            if let Some(wind) =
+                parse_wind_speed_mph(&period.wind_speed).filter(|&wind| wind >= HIGH_WIND_MPH)
+            {
+                found.push(format!("High wind, up to {:.0} mph", wind));
+            }
+            (!found.is_empty()).then(|| format!("- {}: {}\n", period.name, found.join("; ")))
+        })
+        .collect();
+
+    if alerts.is_empty() && hazards.is_empty() {
+        output.push_str("No active hazards: no weather alerts in effect and none in the forecast.\n");
+        return output;
+    }
+
+    if alerts.is_empty() {
+        output.push_str("No weather alerts in effect.\n\n");
+    } else {
+        output.push_str("Active alerts, most severe first:\n");
+        for (i, props) in alerts.iter().enumerate() {
+            output.push_str(&format!("{}. {} ({})\n", i + 1, props.event, props.severity));
+            if let Some(headline) = &props.headline {
+                output.push_str(&format!("   {}\n", clean_alert_text(headline)));
+            }
+            if let Some(instruction) = &props.instruction {
+                output.push_str(&format!("   What to do: {}\n", clean_alert_text(instruction)));
+            }
+        }
+        output.push('\n');
+    }
+
+    if hazards.is_empty() {
+        output.push_str("No hazards in the near-term forecast.\n");
+    } else {
+        output.push_str("Forecast hazards:\n");
+        for hazard in hazards {
+            output.push_str(&hazard);
+        }
+    }
+    output
+}
+
+/// Builds the header shared by the NWS forecast formatters
+fn nws_header(latitude: f64, longitude: f64) -> String {
+    format!(
+        "Weather Forecast (NWS)\nLocation: {}\n\n",
+        format_coords(latitude, longitude)
+    )
+}
+
+/// Formats Open-Meteo forecast into a human-readable string
+pub fn format_open_meteo_forecast(
+    forecast: OpenMeteoResponse,
+    options: FormatOptions,
+) -> FormattedForecast {
+    let daily_units = forecast.units_or_default(options.units);
+    let temperature = |value: f64| {
+        format_temperature(value, &daily_units.temperature_max, options.units, 1)
+    };
+
+    let current = match &forecast.current_weather {
+        Some(current) => format_current_weather(current, &daily_units, options),
+        None => String::new(),
+    };
+    let timezone = match &forecast.timezone_abbreviation {
+        Some(abbreviation) if *abbreviation != forecast.timezone => {
+            format!("{} ({})", forecast.timezone, abbreviation)
+        }
+        _ => forecast.timezone.clone(),
+    };
+    let local_time = forecast
+        .utc_offset_seconds
+        .map(|offset| format_local_time(offset, Utc::now(), options.time_format))
+        .unwrap_or_default();
+    let header = format!(
+        "{}Weather Forecast (Open-Meteo)\nLocation: {}\nTimezone: {}\n{}{}\n",
+        open_water_note(&forecast),
+        format_coords(forecast.latitude, forecast.longitude),
+        timezone,
+        local_time,
+        current
+    );
+
+    let days = forecast.daily.time.len().min(7);
+    let worst_day = if options.highlight_worst {
+        // Earliest day wins on ties; a week without adverse weather has nothing to highlight
+        (0..days)
+            .filter(|&i| is_adverse_weather(forecast.daily.weather_code[i]))
+            .max_by_key(|&i| {
+                (
+                    weather_code_severity(forecast.daily.weather_code[i]),
+                    std::cmp::Reverse(i),
+                )
+            })
+    } else {
+        None
+    };
+
+    let daily = &forecast.daily;
+    let entries = (0..days)
+        .map(|i| {
+            let marker = if worst_day == Some(i) {
+                " (most severe weather this week)"
+            } else {
+                ""
+            };
+
+            let mut wind = match options.wind_scale {
+                WindScale::Exact => {
+                    format!("{:.1} {}", daily.wind_speed_max[i], daily_units.wind_speed_max)
+                }
+                WindScale::Beaufort => {
+                    let speed_kmh = match options.units {
+                        Units::Imperial => mph_to_kmh(daily.wind_speed_max[i]),
+                        Units::Metric | Units::Kelvin => daily.wind_speed_max[i],
+                    };
+                    let (force, description) = to_beaufort(speed_kmh);
+                    format!("Force {} \u{2014} {}", force, description)
+                }
+            };
+            let direction = daily.wind_direction_dominant.get(i).copied().flatten();
+            if let Some(degrees) = direction.filter(|_| options.wind_arrows) {
+                wind.push_str(&format!(" {}", degrees_to_arrow(degrees)));
+            }
+
+            let mut conditions = weather_code_to_description(daily.weather_code[i]).to_string();
+            let hourly_worst = forecast.hourly.as_ref().filter(|_| options.include_hourly_worst);
+            if let Some((code, start, end)) = hourly_worst
+                .and_then(|hourly| hourly_worst_code(hourly, &daily.time[i], daily.weather_code[i]))
+            {
+                conditions.push_str(&format!(
+                    ", but {} possible {}-{}",
+                    weather_code_to_description(code).to_lowercase(),
+                    options.time_format.format(start),
+                    options.time_format.format(end)
+                ));
+            }
+
+            // Amounts that would print as 0.0 are just noise on a dry day
+            let precipitation = daily.precipitation_sum[i];
+            let precipitation = if precipitation < 0.05 && !options.show_zero_precipitation {
+                "none".to_string()
+            } else {
+                format!("{:.1} {}", precipitation, daily_units.precipitation_sum)
+            };
+
+            let mut entry = format!(
+                "{}{}:\n  Temperature: {} - {}\n  Conditions: {}\n  Wind Speed: {}\n  Precipitation: {}\n",
+                format_date(&daily.time[i], options.date_format),
+                marker,
+                temperature(daily.temperature_min[i]),
+                temperature(daily.temperature_max[i]),
+                conditions,
+                wind,
+                precipitation
+            );
+
+            let local_time = |times: &[Option<String>]| {
+                times.get(i).and_then(|raw| raw.as_deref()).and_then(parse_local_time)
+            };
+            let sunrise = local_time(&daily.sunrise);
+            let sunset = local_time(&daily.sunset);
+            let daylight = daily.daylight_duration.get(i).copied().flatten();
+            if let Some(label) = daylight.and_then(polar_daylight) {
+                entry.push_str(&format!("  Sun: {}\n", label));
+            } else if let (Some(sunrise), Some(sunset)) = (sunrise, sunset) {
+                entry.push_str(&format!(
+                    "  Sunrise: {}, Sunset: {}\n",
+                    options.time_format.format(sunrise),
+                    options.time_format.format(sunset)
+                ));
+
+                if options.show_golden_hour {
+                    let [morning, evening] = golden_hours(sunrise, sunset);
+                    entry.push_str(&format!(
+                        "  Golden Hour: {}-{}, {}-{}\n",
+                        options.time_format.format(morning.0),
+                        options.time_format.format(morning.1),
+                        options.time_format.format(evening.0),
+                        options.time_format.format(evening.1)
+                    ));
+                }
+            }
+
+            entry.push('\n');
+            entry
+        })
+        .collect();
+
+    FormattedForecast {
+        header,
+        entries,
+        footer: String::new(),
+    }
+}
+
+/// Finds the most severe hourly weather code on a date, if it is more severe than
+/// the daily code, with the span of its first run of hours
+fn hourly_worst_code(
+    hourly: &HourlyData,
+    date: &str,
+    daily_code: i32,
+) -> Option<(i32, NaiveDateTime, NaiveDateTime)> {
+    let hours: Vec<(NaiveDateTime, i32)> = hourly
+        .time
+        .iter()
+        .zip(&hourly.weather_code)
+        .filter(|(raw, _)| raw.starts_with(date))
+        .filter_map(|(raw, code)| Some((parse_local_time(raw)?, (*code)?)))
+        .collect();
+
+    let worst = hours
+        .iter()
+        .map(|&(_, code)| code)
+        .max_by_key(|&code| weather_code_severity(code))?;
+    let severity = weather_code_severity(worst);
+    if severity <= weather_code_severity(daily_code) {
+        return None;
+    }
+
+    let run: Vec<NaiveDateTime> = hours
+        .iter()
+        .skip_while(|&&(_, code)| weather_code_severity(code) != severity)
+        .take_while(|&&(_, code)| weather_code_severity(code) == severity)
+        .map(|&(time, _)| time)
+        .collect();
+    Some((worst, *run.first()?, *run.last()? + Duration::hours(1)))
+}
+
+/// A leading note for forecasts that appear to be over open water, where the
+/// land-based models are less reliable
+fn open_water_note(forecast: &OpenMeteoResponse) -> &'static str {
+    if forecast.is_open_water() {
+        "Note: these coordinates appear to be over open water; forecast reliability is limited.\n\n"
+    } else {
+        ""
+    }
+}
+
+/// Describes a polar day or night from the daylight duration in seconds
+fn polar_daylight(seconds: f64) -> Option<&'static str> {
+    if seconds <= 0.0 {
+        Some("Polar night (no sunrise)")
+    } else if seconds >= 86_400.0 {
+        Some("Polar day (no sunset)")
+    } else {
+        None
+    }
+}
+
+/// The "Local time" header line for a place `offset_seconds` ahead of UTC at `now`
+fn format_local_time(offset_seconds: i64, now: DateTime<Utc>, time_format: TimeFormat) -> String {
+    let local = now.naive_utc() + Duration::seconds(offset_seconds);
+    format!("Local time: {}\n", time_format.format(local))
+}
+
+/// Formats Open-Meteo's legacy `current_weather` block as a header line
+fn format_current_weather(
+    current: &CurrentWeather,
+    daily_units: &DailyUnits,
+    options: FormatOptions,
+) -> String {
+    let daylight = match current.is_day {
+        Some(0) => " (night)",
+        Some(_) => " (day)",
+        None => "",
+    };
+    format!(
+        "Current: {}, {}{}, wind {:.1} {} from {:.0}\u{00b0}\n",
+        format_temperature(current.temperature, &daily_units.temperature_max, options.units, 1),
+        weather_code_to_description(current.weathercode),
+        daylight,
+        current.windspeed,
+        daily_units.wind_speed_max,
+        current.winddirection
+    )
+}
+
+/// Formats the first forecast day's hourly series, if the response includes one
+pub fn format_today_hourly(forecast: &OpenMeteoResponse, options: FormatOptions) -> Option<String> {
+    let hourly = forecast.hourly.as_ref()?;
+    let today = forecast.daily.time.first()?;
+    let daily_units = forecast.units_or_default(options.units);
+
+    let mut output = format!("Hourly for {}:\n", format_date(today, options.date_format));
+    for (i, raw) in hourly.time.iter().enumerate() {
+        if !raw.starts_with(today.as_str()) {
+            continue;
+        }
+        let Some(time) = parse_local_time(raw) else {
+            continue;
+        };
+
+        let temperature = |values: &[Option<f64>]| {
+            let value = values.get(i).copied().flatten()?;
+            Some(format_temperature(value, &daily_units.temperature_max, options.units, 1))
+        };
+        let mut temperature_label =
+            temperature(&hourly.temperature).unwrap_or_else(|| "?".to_string());
+        if let Some(apparent) = temperature(&hourly.apparent_temperature) {
+            temperature_label.push_str(&format!(" (feels {})", apparent));
+        }
+        let conditions = hourly
+            .weather_code
+            .get(i)
+            .copied()
+            .flatten()
+            .map(weather_code_to_description)
+            .unwrap_or("Unknown");
+        output.push_str(&format!(
+            "  {}  {}  {}",
+            options.time_format.format(time),
+            temperature_label,
+            conditions
+        ));
+        if let Some(amount) = hourly.precipitation.get(i).copied().flatten().filter(|a| *a > 0.0) {
+            output.push_str(&format!(", {:.1} {}", amount, daily_units.precipitation_sum));
+        }
+        output.push('\n');
+    }
+
+    output.push('\n');
+    Some(output)
+}
+
+/// Returns the Open-Meteo daily data as chart-ready parallel arrays of equal length
+pub fn open_meteo_arrays(forecast: &OpenMeteoResponse, units: Units) -> serde_json::Value {
+    let daily = &forecast.daily;
+    let days = [
+        daily.time.len(),
+        daily.temperature_max.len(),
+        daily.temperature_min.len(),
+        daily.precipitation_sum.len(),
+    ]
+    .into_iter()
+    .min()
+    .unwrap_or(0);
+
+    let daily_units = forecast.units_or_default(units);
+    let (temperature_unit, convert): (&str, fn(f64) -> f64) = match units {
+        Units::Kelvin => ("K", celsius_to_kelvin),
+        Units::Metric | Units::Imperial => (&daily_units.temperature_max, |value| value),
+    };
+
+    serde_json::json!({
+        "latitude": forecast.latitude,
+        "longitude": forecast.longitude,
+        "timezone": forecast.timezone,
+        "units": {
+            "temperature": temperature_unit,
+            "precipitation": daily_units.precipitation_sum,
+        },
+        "dates": &daily.time[..days],
+        "highs": daily.temperature_max[..days].iter().map(|&v| convert(v)).collect::<Vec<_>>(),
+        "lows": daily.temperature_min[..days].iter().map(|&v| convert(v)).collect::<Vec<_>>(),
+        "precipitation": &daily.precipitation_sum[..days],
+    })
+}
+
+/// How one day's forecast moved between two snapshots; values are `(before, after)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayChange {
+    pub date: String,
+    pub high: (f64, f64),
+    pub low: (f64, f64),
+    pub precipitation: (f64, f64),
+}
+
+/// Pairs up the days two forecast snapshots share, in the current snapshot's order.
+///
+/// Days only in the earlier snapshot have passed and days only in the current one
+/// weren't forecast before, so neither has anything to compare.
+pub fn diff_forecast_arrays(previous: &ForecastArrays, current: &ForecastArrays) -> Vec<DayChange> {
+    let day = |arrays: &ForecastArrays, i: usize| {
+        Some((
+            *arrays.highs.get(i)?,
+            *arrays.lows.get(i)?,
+            *arrays.precipitation.get(i)?,
+        ))
+    };
+    current
+        .dates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, date)| {
+            let before = previous.dates.iter().position(|d| d == date)?;
+            let (old_high, old_low, old_precipitation) = day(previous, before)?;
+            let (high, low, precipitation) = day(current, i)?;
+            Some(DayChange {
+                date: date.clone(),
+                high: (old_high, high),
+                low: (old_low, low),
+                precipitation: (old_precipitation, precipitation),
+            })
+        })
+        .collect()
+}
+
+/// Formats how each shared day's forecast changed, e.g. "high 18.0°C → 21.0°C
+/// (revised up 3.0)"
+pub fn format_forecast_diff(
+    previous: &ForecastArrays,
+    current: &ForecastArrays,
+    changes: &[DayChange],
+    date_format: DateFormat,
+) -> String {
+    let mut output = format!(
+        "Forecast Changes (Open-Meteo)\nLocation: {}\n\n",
+        format_coords(current.latitude, current.longitude)
+    );
+    if changes.is_empty() {
+        output.push_str("The earlier forecast has no days in common with the current one.\n");
+        return output;
+    }
+
+    let describe = |label: &str, (before, after): (f64, f64), unit: &str, threshold: f64| {
+        let delta = after - before;
+        if delta.abs() < threshold {
+            return None;
+        }
+        let direction = if delta > 0.0 { "up" } else { "down" };
+        Some(format!(
+            "{} {:.1}{} \u{2192} {:.1}{} (revised {} {:.1})",
+            label,
+            before,
+            unit,
+            after,
+            unit,
+            direction,
+            delta.abs()
+        ))
+    };
+    let temperature_unit = &current.units.temperature;
+    let temperature_unit = if temperature_unit == "K" { " K" } else { temperature_unit };
+    let precipitation_unit = format!(" {}", current.units.precipitation);
+    let (temperature_threshold, precipitation_threshold) = match current.units.units() {
+        Units::Imperial => (1.0, 0.01),
+        Units::Metric | Units::Kelvin => (0.5, 0.1),
+    };
+
+    for change in changes {
+        let revisions: Vec<String> = [
+            describe("high", change.high, temperature_unit, temperature_threshold),
+            describe("low", change.low, temperature_unit, temperature_threshold),
+            describe(
+                "precipitation",
+                change.precipitation,
+                &precipitation_unit,
+                precipitation_threshold,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let summary = if revisions.is_empty() {
+            "unchanged".to_string()
+        } else {
+            revisions.join("; ")
+        };
+        output.push_str(&format!("{}: {}\n", format_date(&change.date, date_format), summary));
+    }
+
+    let new_days = current.dates.iter().filter(|d| !previous.dates.contains(d)).count();
+    if new_days > 0 {
+        output.push_str(&format!("\n{} day(s) added since the earlier forecast.\n", new_days));
+    }
+    output
+}
+
+/// Returns the Open-Meteo daily data as a GeoJSON Feature: a Point at the forecast
+/// coordinates, with one object per day under `properties.daily`
+pub fn open_meteo_geojson(forecast: &OpenMeteoResponse, units: Units) -> serde_json::Value {
+    let daily = &forecast.daily;
+    let daily_units = forecast.units_or_default(units);
+    let (temperature_unit, convert): (&str, fn(f64) -> f64) = match units {
+        Units::Kelvin => ("K", celsius_to_kelvin),
+        Units::Metric | Units::Imperial => (&daily_units.temperature_max, |value| value),
+    };
+
+    let days: Vec<serde_json::Value> = daily
+        .time
+        .iter()
+        .zip(&daily.temperature_max)
+        .zip(&daily.temperature_min)
+        .zip(&daily.precipitation_sum)
+        .enumerate()
+        .map(|(i, (((date, &high), &low), &precipitation))| {
+            let code = daily.weather_code.get(i).copied();
+            serde_json::json!({
+                "date": date,
+                "high": convert(high),
+                "low": convert(low),
+                "precipitation": precipitation,
+                "weather_code": code,
+                "conditions": code.map(weather_code_to_description),
+            })
+        })
+        .collect();
+
+    // GeoJSON positions are longitude first
+    serde_json::json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": [forecast.longitude, forecast.latitude],
+        },
+        "properties": {
+            "source": "Open-Meteo",
+            "timezone": forecast.timezone,
+            "units": {
+                "temperature": temperature_unit,
+                "precipitation": daily_units.precipitation_sum,
+            },
+            "daily": days,
+        },
+    })
+}
+
+/// Formats the warmest and coldest hour of each day, e.g.
+/// "Warmest ~15:00 (24°C), coldest ~05:00 (14°C)"
+pub fn format_temperature_profile(
+    forecast: &OpenMeteoHourlyResponse,
+    extremes: &[DailyExtremes],
+    options: FormatOptions,
+) -> String {
+    let hourly = &forecast.hourly;
+    let mut output = format!(
+        "Daily Temperature Profile (Open-Meteo)\nLocation: {}\nTimezone: {}\n\n",
+        format_coords(forecast.latitude, forecast.longitude),
+        forecast.timezone
+    );
+
+    if extremes.is_empty() {
+        output.push_str("No hourly temperatures are available.\n");
+        return output;
+    }
+
+    let unit = forecast
+        .hourly_units
+        .get("temperature_2m")
+        .map(String::as_str)
+        .unwrap_or("\u{00b0}C");
+    let hour = |i: usize| {
+        let raw = &hourly.time[i];
+        let time = parse_local_time(raw)
+            .map(|t| options.time_format.format(t))
+            .unwrap_or_else(|| raw.clone());
+        let temperature = hourly.temperature[i]
+            .map(|value| format_temperature(value, unit, options.units, 0))
+            .unwrap_or_else(|| "?".to_string());
+        (time, temperature)
+    };
+
+    for day in extremes {
+        let date = hourly.time[day.max].get(..10).unwrap_or_default();
+        let (warmest, high) = hour(day.max);
+        let (coldest, low) = hour(day.min);
+        output.push_str(&format!(
+            "{}: Warmest ~{} ({}), coldest ~{} ({})\n",
+            format_date(date, options.date_format),
+            warmest,
+            high,
+            coldest,
+            low
+        ));
+    }
+    output
+}
+
+/// Formats a temperature with the given precision in the requested unit system.
+///
+/// Open-Meteo units already include the degree sign, e.g. "°C".
+pub fn format_temperature(value: f64, unit: &str, units: Units, precision: usize) -> String {
+    match units {
+        Units::Kelvin => {
+            // 273.15 is stored just below itself, so round before formatting or 0°C
+            // would print as 273.1 K
+            let scale = 10f64.powi(precision as i32);
+            let kelvin = (celsius_to_kelvin(value) * scale).round() / scale;
+            format!("{:.*} K", precision, kelvin)
+        }
+        Units::Metric | Units::Imperial => format!("{:.*}{}", precision, value, unit),
+    }
+}
+
+/// Describes an Open-Meteo forecast as a short flowing paragraph
+pub fn format_open_meteo_narrative(
+    forecast: OpenMeteoResponse,
+    options: FormatOptions,
+) -> FormattedForecast {
+    let daily = &forecast.daily;
+    let days = daily.time.len().min(7);
+    let header = format!(
+        "{}Weather Outlook (Open-Meteo)\nLocation: {}\n\n",
+        open_water_note(&forecast),
+        format_coords(forecast.latitude, forecast.longitude)
+    );
+    if days == 0 {
+        return FormattedForecast {
+            header,
+            entries: vec!["No forecast data is available.\n".to_string()],
+            footer: String::new(),
+        };
+    }
+
+    let daily_units = forecast.units_or_default(options.units);
+    let unit = &daily_units.temperature_max;
+    let temperature = |value: f64| format_temperature(value, unit, options.units, 0);
+    let day_name = |i: usize| match i {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        _ => format_date(&daily.time[i], DateFormat::Weekday),
+    };
+    let conditions = |i: usize| weather_code_to_description(daily.weather_code[i]).to_lowercase();
+
+    let mut sentences = vec![format!(
+        "Expect {} today with a high near {} and a low around {}.",
+        conditions(0),
+        temperature(daily.temperature_max[0]),
+        temperature(daily.temperature_min[0])
+    )];
+
+    if let Some(change) = (1..days).find(|&i| conditions(i) != conditions(0)) {
+        sentences.push(format!(
+            "Conditions turn to {} by {}.",
+            conditions(change),
+            day_name(change)
+        ));
+    }
+
+    let last = days - 1;
+    let high_change = daily.temperature_max[last] - daily.temperature_max[0];
+    if last > 0 && high_change.abs() >= 3.0 {
+        sentences.push(format!(
+            "Highs {} to around {} by {}.",
+            if high_change > 0.0 { "climb" } else { "fall" },
+            temperature(daily.temperature_max[last]),
+            day_name(last)
+        ));
+    } else if last > 0 {
+        sentences.push("Temperatures stay fairly steady through the period.".to_string());
+    }
+
+    let wettest = (0..days).max_by(|&a, &b| {
+        daily.precipitation_sum[a].total_cmp(&daily.precipitation_sum[b])
+    });
+    match wettest {
+        Some(i) if daily.precipitation_sum[i] >= 1.0 => sentences.push(format!(
+            "The wettest day looks to be {}, with about {:.0} {} of precipitation.",
+            day_name(i),
+            daily.precipitation_sum[i],
+            daily_units.precipitation_sum
+        )),
+        _ => sentences.push("Little or no precipitation is expected.".to_string()),
+    }
+
+    FormattedForecast {
+        header,
+        entries: vec![format!("{}\n", sentences.join(" "))],
+        footer: String::new(),
+    }
+}
+
+/// Describes an NWS forecast as paragraphs built from each period's detailed forecast
+pub fn format_forecast_narrative(
+    forecast: ForecastResponse,
+    latitude: f64,
+    longitude: f64,
+) -> FormattedForecast {
+    let entries = forecast
+        .properties
+        .periods
+        .into_iter()
+        .map(|period| format!("{}: {}\n\n", period.name, period.detailed_forecast))
+        .collect();
+
+    FormattedForecast {
+        header: nws_header(latitude, longitude),
+        entries,
+        footer: String::new(),
+    }
+}
+
+/// Formats the coming week's snowfall: daily amounts, the total, and whether the
+/// heaviest day calls for shoveling
+pub fn format_snow_outlook(forecast: &SnowfallResponse, options: FormatOptions) -> String {
+    let daily = &forecast.daily;
+    let unit = forecast
+        .daily_units
+        .get("snowfall_sum")
+        .map(String::as_str)
+        .unwrap_or("cm");
+    let mut output = format!(
+        "Snow Outlook (Open-Meteo)\nLocation: {}\n\n",
+        format_coords(forecast.latitude, forecast.longitude)
+    );
+
+    let to_cm = |amount: f64| match options.units {
+        Units::Imperial => amount * 2.54,
+        Units::Metric | Units::Kelvin => amount,
+    };
+    let snowy: Vec<(&String, f64)> = daily
+        .time
+        .iter()
+        .zip(&daily.snowfall_sum)
+        .filter_map(|(date, amount)| Some((date, (*amount)?)))
+        .filter(|&(_, amount)| snow_impact(to_cm(amount)) > SnowImpact::None)
+        .collect();
+    let Some(&(heaviest_date, heaviest)) = snowy.iter().max_by(|a, b| a.1.total_cmp(&b.1)) else {
+        output.push_str("No snow expected.\n");
+        return output;
+    };
+
+    for (date, amount) in &snowy {
+        output.push_str(&format!(
+            "{}: {:.1} {}\n",
+            format_date(date, options.date_format),
+            amount,
+            unit
+        ));
+    }
+    let total: f64 = daily.snowfall_sum.iter().flatten().sum();
+    output.push_str(&format!("\nTotal: {:.1} {}\n", total, unit));
+
+    let day = format_date(heaviest_date, options.date_format);
+    let assessment = match snow_impact(to_cm(heaviest)) {
+        SnowImpact::None | SnowImpact::Light => {
+            format!("Light snow at most, heaviest {} \u{2014} a quick sweep should do", day)
+        }
+        SnowImpact::Moderate => format!("Snow {} \u{2014} expect to shovel", day),
+        SnowImpact::Significant => {
+            format!("Significant snow {} \u{2014} plan to shovel", day)
+        }
+    };
+    output.push_str(&format!("Assessment: {}\n", assessment));
+    output
+}
+
+/// Formats today's forecast high against yesterday's, e.g. "2.0°C warmer than
+/// yesterday."
+///
+/// `highs` are yesterday's and today's highs in `unit`. Yesterday's comes from
+/// recent model data rather than the archive, which lags a few days, so it is
+/// labeled provisional; when it is missing there is nothing to compare.
+pub fn format_yesterday_comparison(
+    latitude: f64,
+    longitude: f64,
+    highs: (Option<f64>, Option<f64>),
+    unit: &str,
+    units: Units,
+) -> String {
+    let mut output = format!(
+        "Today vs Yesterday (Open-Meteo)\nLocation: {}\n\n",
+        format_coords(latitude, longitude)
+    );
+    let (yesterday, today) = highs;
+    let Some(today) = today else {
+        output.push_str("Today's forecast high isn't available.\n");
+        return output;
+    };
+    output.push_str(&format!(
+        "Today: high {} (forecast)\n",
+        format_temperature(today, unit, units, 1)
+    ));
+    let Some(yesterday) = yesterday else {
+        output.push_str("Yesterday: not available yet, so there's nothing to compare.\n");
+        return output;
+    };
+    output.push_str(&format!(
+        "Yesterday: high {} (provisional)\n\n",
+        format_temperature(yesterday, unit, units, 1)
+    ));
+
+    // Differences are the same size in Kelvin and Celsius, so only the label changes
+    let (threshold, delta_unit) = match units {
+        Units::Imperial => (1.0, unit),
+        Units::Metric => (0.5, unit),
+        Units::Kelvin => (0.5, "K"),
+    };
+    output.push_str(&match compare_highs(today, yesterday, threshold) {
+        HighChange::Warmer(by) => format!("{:.1}{} warmer than yesterday.\n", by, delta_unit),
+        HighChange::Cooler(by) => format!("{:.1}{} cooler than yesterday.\n", by, delta_unit),
+        HighChange::Similar => "About the same as yesterday.\n".to_string(),
+    });
+    output
+}
+
+/// Describes how the daily highs and precipitation change across a forecast, from
+/// the slope of each over the days shown
+pub fn format_weather_trend(forecast: &OpenMeteoResponse, options: FormatOptions) -> String {
+    let daily = &forecast.daily;
+    let daily_units = forecast.units_or_default(options.units);
+    let days = daily.time.len().min(daily.temperature_max.len());
+    let mut output = format!(
+        "Weather Trend (Open-Meteo)\nLocation: {}\n",
+        format_coords(forecast.latitude, forecast.longitude)
+    );
+    if days < 2 {
+        output.push_str("\nNot enough forecast days to show a trend.\n");
+        return output;
+    }
+    output.push_str(&format!(
+        "Window: {} to {}\n\n",
+        format_date(&daily.time[0], options.date_format),
+        format_date(&daily.time[days - 1], options.date_format)
+    ));
+
+    // Changes under these per-day rates read as steady; Kelvin steps match Celsius
+    let (temperature_step, precipitation_step) = match options.units {
+        Units::Imperial => (1.0, 0.02),
+        Units::Metric | Units::Kelvin => (0.5, 0.5),
+    };
+    let temperature_unit = if options.units == Units::Kelvin {
+        "K"
+    } else {
+        daily_units.temperature_max.as_str()
+    };
+
+    if let Some(slope) = linear_slope(&daily.temperature_max[..days]) {
+        let direction = if slope.abs() < temperature_step {
+            "steady".to_string()
+        } else {
+            format!(
+                "{} ~{:.1}{}/day",
+                if slope > 0.0 { "warming" } else { "cooling" },
+                slope.abs(),
+                temperature_unit
+            )
+        };
+        let unit = &daily_units.temperature_max;
+        let high = |i: usize| format_temperature(daily.temperature_max[i], unit, options.units, 0);
+        output.push_str(&format!(
+            "Temperature: {} (highs {} to {})\n",
+            direction,
+            high(0),
+            high(days - 1)
+        ));
+    }
+
+    let precipitation = &daily.precipitation_sum[..days.min(daily.precipitation_sum.len())];
+    if let Some(slope) = linear_slope(precipitation) {
+        let unit = &daily_units.precipitation_sum;
+        let direction = if slope.abs() < precipitation_step {
+            "steady".to_string()
+        } else {
+            format!(
+                "{} ~{:.1} {}/day",
+                if slope > 0.0 { "wetter" } else { "drier" },
+                slope.abs(),
+                unit
+            )
+        };
+        output.push_str(&format!(
+            "Precipitation: {} ({:.1} {} total)\n",
+            direction,
+            precipitation.iter().sum::<f64>(),
+            unit
+        ));
+    }
+    output
+}
+
+/// Describes a 0-100 weather score in a word
+fn score_verdict(total: f64) -> &'static str {
+    match total {
+        t if t >= 80.0 => "great",
+        t if t >= 60.0 => "good",
+        t if t >= 40.0 => "fair",
+        _ => "poor",
+    }
+}
+
+/// Formats a week of daily scores for an activity, with the best day.
+///
+/// Each day lists the conditions that matter for the activity and any factor that
+/// earned less than three quarters of its points.
+pub fn format_activity_forecast(
+    forecast: &ActivityResponse,
+    activity: Activity,
+    options: FormatOptions,
+) -> String {
+    let daily = &forecast.daily;
+    let unit = |field: &str, default: &'static str| {
+        forecast.daily_units.get(field).map(String::as_str).unwrap_or(default).to_string()
+    };
+    let temperature_unit = unit("temperature_2m_max", "°C");
+    let precipitation_unit = unit("precipitation_sum", "mm");
+    let wind_unit = unit("wind_speed_10m_max", "km/h");
+    let snowfall_unit = unit("snowfall_sum", "cm");
+
+    // Capitalize by character so a name starting with a multi-byte letter can't panic
+    let name = activity.name();
+    let mut letters = name.chars();
+    let title: String = letters
+        .next()
+        .map(|first| first.to_uppercase().chain(letters).collect())
+        .unwrap_or_default();
+    let mut output = format!(
+        "{} Forecast (Open-Meteo)\nLocation: {}\n\n",
+        title,
+        format_coords(forecast.latitude, forecast.longitude)
+    );
+
+    let mut best: Option<(&str, f64)> = None;
+    for (i, date) in daily.time.iter().enumerate() {
+        let value = |values: &[Option<f64>]| values.get(i).copied().flatten();
+        let (Some(high), Some(precipitation), Some(wind)) = (
+            value(&daily.temperature_max),
+            value(&daily.precipitation_sum),
+            value(&daily.wind_speed_max),
+        ) else {
+            continue;
+        };
+        let snowfall = value(&daily.snowfall_sum).unwrap_or(0.0);
+        let sunshine_hours = value(&daily.sunshine_duration).unwrap_or(0.0) / 3600.0;
+        let weather = match options.units {
+            Units::Imperial => ActivityWeather {
+                high_c: fahrenheit_to_celsius(high),
+                precipitation_mm: precipitation * 25.4,
+                wind_kmh: mph_to_kmh(wind),
+                snowfall_cm: snowfall * 2.54,
+                sunshine_hours,
+            },
+            Units::Metric | Units::Kelvin => ActivityWeather {
+                high_c: high,
+                precipitation_mm: precipitation,
+                wind_kmh: wind,
+                snowfall_cm: snowfall,
+                sunshine_hours,
+            },
+        };
+        let factors = activity_score(activity, &weather);
+        let total: f64 = factors.iter().map(|factor| factor.points).sum();
+
+        let mut conditions = vec![
+            format!("high {}", format_temperature(high, &temperature_unit, options.units, 1)),
+            format!("{:.1} {} precipitation", precipitation, precipitation_unit),
+            format!("wind up to {:.1} {}", wind, wind_unit),
+        ];
+        match activity {
+            Activity::Beach => conditions.push(format!("{:.1} h sun", sunshine_hours)),
+            Activity::Skiing => {
+                conditions.push(format!("{:.1} {} new snow", snowfall, snowfall_unit))
+            }
+            Activity::Running | Activity::Cycling | Activity::Gardening => {}
+        }
+        output.push_str(&format!(
+            "{}: {:.0}/100 ({}) \u{2014} {}\n",
+            format_date(date, options.date_format),
+            total,
+            score_verdict(total),
+            conditions.join(", ")
+        ));
+
+        let weak: Vec<String> = factors
+            .iter()
+            .filter(|factor| factor.points < factor.weight * 0.75)
+            .map(|factor| format!("{} {:.0}/{:.0}", factor.name, factor.points, factor.weight))
+            .collect();
+        if !weak.is_empty() {
+            output.push_str(&format!("  Held back by: {}\n", weak.join(", ")));
+        }
+
+        if best.is_none_or(|(_, best_total)| total > best_total) {
+            best = Some((date, total));
+        }
+    }
+
+    match best {
+        Some((date, total)) => output.push_str(&format!(
+            "\nBest day for {}: {} ({:.0}/100)\n",
+            name,
+            format_date(date, options.date_format),
+            total
+        )),
+        None => output.push_str("No forecast data available.\n"),
+    }
+    output
+}
+
+/// The weather behind an event score, in the response's units
+pub struct EventConditions {
+    pub high: f64,
+    pub precipitation: f64,
+    pub wind_speed: f64,
+}
+
+/// Formats an event-day score with its breakdown.
+///
+/// `source` says whether the score comes from the forecast or from past years; for
+/// climatology the score and conditions are averages over `days` historical days.
+#[allow(clippy::too_many_arguments)]
+pub fn format_event_score(
+    latitude: f64,
+    longitude: f64,
+    date: &str,
+    source: &str,
+    score: &EventScore,
+    conditions: &EventConditions,
+    unit_labels: (&str, &str, &str),
+    units: Units,
+) -> String {
+    let (temperature_unit, precipitation_unit, wind_unit) = unit_labels;
+    let (temperature_weight, precipitation_weight, wind_weight) = EVENT_SCORE_WEIGHTS;
+    let total = score.total();
+    let verdict = score_verdict(total);
+    format!(
+        "Event Weather Score\nLocation: {}\nDate: {}\nSource: {}\n\nScore: {:.0}/100 ({})\n  Temperature: {:.0}/{:.0} (high {})\n  Precipitation: {:.0}/{:.0} ({:.1} {})\n  Wind: {:.0}/{:.0} (up to {:.1} {})\n",
+        format_coords(latitude, longitude),
+        date,
+        source,
+        total,
+        verdict,
+        score.temperature,
+        temperature_weight,
+        format_temperature(conditions.high, temperature_unit, units, 1),
+        score.precipitation,
+        precipitation_weight,
+        conditions.precipitation,
+        precipitation_unit,
+        score.wind,
+        wind_weight,
+        conditions.wind_speed,
+        wind_unit
+    )
+}
+
+/// Formats per-day and running heating and cooling degree days.
+///
+/// `days` pairs each date with its degree days, or `None` where the temperatures
+/// are missing. `base` and the degree days are in the data's unit, `unit`.
+pub fn format_degree_days(
+    latitude: f64,
+    longitude: f64,
+    base: f64,
+    unit: &str,
+    units: Units,
+    days: &[(String, Option<DegreeDays>)],
+) -> String {
+    // Differences are the same size in Kelvin and Celsius, so only the label changes
+    let delta_unit = if units == Units::Kelvin { "K" } else { unit };
+    let mut output = format!(
+        "Degree Days (Open-Meteo)\nLocation: {}\nBase: {} (HDD and CDD in {}-days)\n\n",
+        format_coords(latitude, longitude),
+        format_temperature(base, unit, units, 1),
+        delta_unit
+    );
+
+    let mut total = DegreeDays {
+        heating: 0.0,
+        cooling: 0.0,
+    };
+    let mut missing = 0;
+    for (date, day) in days {
+        let Some(day) = day else {
+            missing += 1;
+            output.push_str(&format!("{}: no data\n", date));
+            continue;
+        };
+        total.heating += day.heating;
+        total.cooling += day.cooling;
+        output.push_str(&format!(
+            "{}: HDD {:.1}, CDD {:.1} (cumulative HDD {:.1}, CDD {:.1})\n",
+            date, day.heating, day.cooling, total.heating, total.cooling
+        ));
+    }
+
+    output.push_str(&format!(
+        "\nTotal over {} days: HDD {:.1}, CDD {:.1}\n",
+        days.len() - missing,
+        total.heating,
+        total.cooling
+    ));
+    if missing > 0 {
+        output.push_str(&format!(
+            "{} of {} days had no data and are not counted.\n",
+            missing,
+            days.len()
+        ));
+    }
+    output
+}
+
+/// Ensemble spread of one day's high and low
+pub struct DayQuartiles {
+    pub date: String,
+    pub high: Quartiles,
+    pub low: Quartiles,
+    /// Ensemble members with data for the day
+    pub members: usize,
+}
+
+/// Formats a temperature range with the unit once, e.g. "15–21°C"
+fn format_temperature_range(low: f64, high: f64, unit: &str, units: Units) -> String {
+    let low = if units == Units::Kelvin { celsius_to_kelvin(low) } else { low };
+    format!("{:.0}\u{2013}{}", low, format_temperature(high, unit, units, 0))
+}
+
+/// Formats each day's median high and low with the range the middle half of the
+/// ensemble members fall in, e.g. "High 18°C, likely 15–21°C"
+pub fn format_forecast_quartiles(
+    latitude: f64,
+    longitude: f64,
+    model: &str,
+    unit: &str,
+    days: &[DayQuartiles],
+    options: FormatOptions,
+) -> String {
+    let mut output = format!(
+        "Forecast Ranges (Open-Meteo {} ensemble)\nLocation: {}\n\n",
+        model,
+        format_coords(latitude, longitude)
+    );
+    if days.is_empty() {
+        output.push_str("No ensemble data is available.\n");
+        return output;
+    }
+
+    for day in days {
+        output.push_str(&format!(
+            "{}: High {}, likely {}; Low {}, likely {} ({} members)\n",
+            format_date(&day.date, options.date_format),
+            format_temperature(day.high.median, unit, options.units, 0),
+            format_temperature_range(day.high.p25, day.high.p75, unit, options.units),
+            format_temperature(day.low.median, unit, options.units, 0),
+            format_temperature_range(day.low.p25, day.low.p75, unit, options.units),
+            day.members
+        ));
+    }
+    output.push_str("\n\"Likely\" is the range the middle half of the ensemble members fall in.\n");
+    output
+}
+
+/// A temperature compared against its historical normal
+#[derive(Debug)]
+pub struct TemperatureAnomaly {
+    pub label: &'static str,
+    pub value: f64,
+    pub normal: f64,
+    pub anomaly: f64,
+}
+
+/// Formats today's temperatures against the same date's historical averages
+pub fn format_temperature_anomaly(
+    latitude: f64,
+    longitude: f64,
+    date: &str,
+    years: usize,
+    unit: &str,
+    units: Units,
+    anomalies: &[TemperatureAnomaly],
+) -> String {
+    let mut output = format!(
+        "Temperature Anomaly (Open-Meteo)\nLocation: {}\nDate: {}\nNormal: average of {} previous years\n\n",
+        format_coords(latitude, longitude),
+        date,
+        years
+    );
+
+    // Differences are the same size in Kelvin and Celsius, so only the label changes
+    let delta_unit = if units == Units::Kelvin { " K" } else { unit };
+    for item in anomalies {
+        let direction = if item.anomaly.abs() < 0.5 {
+            "near normal"
+        } else if item.anomaly > 0.0 {
+            "above normal"
+        } else {
+            "below normal"
+        };
+        output.push_str(&format!(
+            "{}: {} vs normal {} ({:+.1}{} {})\n",
+            item.label,
+            format_temperature(item.value, unit, units, 1),
+            format_temperature(item.normal, unit, units, 1),
+            item.anomaly,
+            delta_unit,
+            direction
+        ));
+    }
+    output
+}
+
+/// One weather model's outlook for the compared day
+pub struct ModelOutlook {
+    pub name: &'static str,
+    pub high: Option<f64>,
+    pub precipitation: Option<f64>,
+}
+
+/// Formats a side-by-side comparison of model outlooks with their agreement
+pub fn format_model_agreement(
+    latitude: f64,
+    longitude: f64,
+    date: &str,
+    outlooks: &[ModelOutlook],
+    high_spread: Option<f64>,
+    precipitation_spread: Option<f64>,
+    score: Option<u8>,
+) -> String {
+    let mut output = format!(
+        "Model Agreement (Open-Meteo)\nLocation: {}\nDate: {}\n\n",
+        format_coords(latitude, longitude),
+        date
+    );
+
+    let value = |value: Option<f64>, unit: &str| match value {
+        Some(value) => format!("{:.1}{}", value, unit),
+        None => "n/a".to_string(),
+    };
+    for outlook in outlooks {
+        output.push_str(&format!(
+            "{}: high {}, precipitation {}\n",
+            outlook.name,
+            value(outlook.high, "\u{00b0}C"),
+            value(outlook.precipitation, " mm")
+        ));
+    }
+
+    output.push('\n');
+    if let Some(spread) = high_spread {
+        output.push_str(&format!("High temperature spread: {:.1}\u{00b0}C\n", spread));
+    }
+    if let Some(spread) = precipitation_spread {
+        output.push_str(&format!("Precipitation spread: {:.1} mm\n", spread));
+    }
+    match score {
+        Some(score) => {
+            let verdict = match score {
+                80.. => "models agree",
+                50..=79 => "models partly agree",
+                _ => "models diverge",
+            };
+            output.push_str(&format!("Agreement: {}/100 ({})\n", score, verdict));
+        }
+        None => output.push_str("Agreement: not enough model data to compare\n"),
+    }
+    output
+}
+
+/// Formats tonight's cloud cover, clearest window, and moonlight for stargazing.
+///
+/// `night` and `clear_window` are inclusive indices into the hourly series.
+pub fn format_stargazing(
+    forecast: &OpenMeteoHourlyResponse,
+    night: (usize, usize),
+    clear_window: Option<(usize, usize)>,
+    moon_illumination: f64,
+    rating: StargazingRating,
+    time_format: TimeFormat,
+) -> String {
+    let hourly = &forecast.hourly;
+    let time = |i: usize| {
+        hourly
+            .time
+            .get(i)
+            .and_then(|raw| parse_local_time(raw))
+            .map(|t| time_format.format(t))
+            .unwrap_or_else(|| "?".to_string())
+    };
+    let moon = format!("{:.0}% moon", moon_illumination * 100.0);
+
+    let label = match rating {
+        StargazingRating::Good => "Good",
+        StargazingRating::Fair => "Fair",
+        StargazingRating::Poor => "Poor",
+    };
+    let summary = match clear_window {
+        Some((start, end)) => format!(
+            "{} \u{2014} mostly clear {}-{}, {}",
+            label,
+            time(start),
+            time(end + 1),
+            moon
+        ),
+        None => format!("{} \u{2014} cloudy all night, {}", label, moon),
+    };
+
+    let mut output = format!(
+        "Stargazing Conditions (Open-Meteo)\nLocation: {}\nTimezone: {}\n\n{}\n\nCloud cover tonight:\n",
+        format_coords(forecast.latitude, forecast.longitude),
+        forecast.timezone,
+        summary
+    );
+    for i in night.0..=night.1 {
+        let cover = match hourly.cloud_cover.get(i).copied().flatten() {
+            Some(cover) => format!("{:.0}%", cover),
+            None => "?".to_string(),
+        };
+        output.push_str(&format!("  {}  {}\n", time(i), cover));
+    }
+    output
+}
+
+/// Formats computed sun times, shifted by `utc_offset` hours for display
+pub fn format_sun_times(
+    latitude: f64,
+    longitude: f64,
+    date: NaiveDate,
+    utc_offset: f64,
+    times: &SunTimes,
+    time_format: TimeFormat,
+) -> String {
+    let offset = Duration::seconds((utc_offset * 3600.0).round() as i64);
+    let zone = if utc_offset == 0.0 {
+        "UTC".to_string()
+    } else {
+        let minutes = offset.num_minutes();
+        format!(
+            "UTC{}{:02}:{:02}",
+            if minutes < 0 { '-' } else { '+' },
+            minutes.abs() / 60,
+            minutes.abs() % 60
+        )
+    };
+    let local = |t: NaiveDateTime| time_format.format(t + offset);
+
+    let mut output = format!(
+        "Sun Times (calculated)\nLocation: {}\nDate: {}\nTime zone: {}\n\n",
+        format_coords(latitude, longitude),
+        date.format("%Y-%m-%d"),
+        zone
+    );
+    match times.rise_and_set {
+        Some((sunrise, sunset)) => output.push_str(&format!(
+            "Sunrise: {}\nSunset: {}\n",
+            local(sunrise),
+            local(sunset)
+        )),
+        None if times.day_length > Duration::zero() => {
+            output.push_str("Polar day (no sunset)\n")
+        }
+        None => output.push_str("Polar night (no sunrise)\n"),
+    }
+    output.push_str(&format!(
+        "Solar noon: {}\nDay length: {}h {:02}m\n",
+        local(times.solar_noon),
+        times.day_length.num_hours(),
+        times.day_length.num_minutes() % 60
+    ));
+    output
+}
+
+/// Min, mean, and max of one variable across an area, already formatted with units
+pub struct AreaSummary {
+    pub label: &'static str,
+    pub min: String,
+    pub mean: String,
+    pub max: String,
+}
+
+/// Formats today's per-variable ranges across an area grid
+pub fn format_area_forecast(
+    request: &GetAreaForecastRequest,
+    date: Option<&str>,
+    sampled: usize,
+    requested: usize,
+    summaries: &[AreaSummary],
+) -> String {
+    let mut output = format!(
+        "Area Forecast (Open-Meteo)\nArea: {} to {}\nDate: {}\nGrid points: {} of {}\n\n",
+        format_coords(request.min_latitude, request.min_longitude),
+        format_coords(request.max_latitude, request.max_longitude),
+        date.unwrap_or("today"),
+        sampled,
+        requested
+    );
+    for summary in summaries {
         output.push_str(&format!(
-            "Alert {}:\n  Event: {}\n  Severity: {}\n  Area: {}\n",
-            i + 1,
-            props.event,
-            props.severity,
-            props.area_desc
+            "{}: min {}, mean {}, max {}\n",
+            summary.label, summary.min, summary.mean, summary.max
         ));
-        if let Some(headline) = &props.headline {
-            output.push_str(&format!("  Headline: {}\n", headline));
+    }
+    output
+}
+
+/// A location's mean daily high over the ranking window
+pub struct RankedLocation {
+    pub name: String,
+    pub mean_high: f64,
+    pub days: usize,
+}
+
+/// Formats locations ranked by mean high as a table, followed by any that failed
+pub fn format_location_ranking(
+    window: &str,
+    ranked: &[RankedLocation],
+    unavailable: &[(String, String)],
+    unit: &str,
+    units: Units,
+) -> String {
+    let mut output = format!(
+        "Locations by Mean High Temperature (Open-Meteo)\nDates: {}\n\n",
+        window
+    );
+
+    if !ranked.is_empty() {
+        output.push_str("Rank  Mean High  Days  Location\n");
+        for (rank, location) in ranked.iter().enumerate() {
+            // Padding counts characters, and the name goes last so wide or accented
+            // names can't misalign the columns
+            output.push_str(&format!(
+                "{:>4}  {:>9}  {:>4}  {}\n",
+                rank + 1,
+                format_temperature(location.mean_high, unit, units, 1),
+                location.days,
+                location.name
+            ));
         }
-        if let Some(description) = &props.description {
-            output.push_str(&format!("  Description: {}\n", description));
+    }
+
+    if !unavailable.is_empty() {
+        output.push_str("\nUnavailable:\n");
+        for (name, reason) in unavailable {
+            output.push_str(&format!("- {} ({})\n", name, reason));
         }
-        output.push('\n');
     }
     output
 }
 
-/// Formats NWS forecast into a human-readable string
-pub fn format_forecast(forecast: ForecastResponse) -> String {
-    let mut output = String::from("Weather Forecast:\n\n");
-    for period in forecast.properties.periods {
+/// Weather impact at one end of a journey
+pub struct TravelLeg {
+    pub label: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub time: NaiveDateTime,
+    pub impact: TravelImpact,
+    /// Worst weather code in the sampled hours
+    pub weather_code: Option<i32>,
+}
+
+fn travel_impact_label(impact: TravelImpact) -> &'static str {
+    match impact {
+        TravelImpact::Clear => "clear",
+        TravelImpact::RainSlowed => "rain-slowed",
+        TravelImpact::SnowHazard => "snow hazard",
+    }
+}
+
+/// Formats the travel impact assessment for both ends of a journey
+pub fn format_travel_impact(legs: &[TravelLeg], duration_hours: f64, estimated: bool) -> String {
+    let overall = legs
+        .iter()
+        .map(|leg| leg.impact)
+        .max()
+        .unwrap_or(TravelImpact::Clear);
+    let mut output = format!(
+        "Travel Weather Impact (Open-Meteo)\nOverall: {}\nDuration: {:.1} hours{}\n\n",
+        travel_impact_label(overall),
+        duration_hours,
+        if estimated { " (estimated from distance)" } else { "" }
+    );
+    for leg in legs {
+        let conditions = leg
+            .weather_code
+            .map(weather_code_to_description)
+            .unwrap_or("Unknown");
         output.push_str(&format!(
-            "{}:\n  Temperature: {}\u{00b0}{}\n  Wind: {} {}\n  Conditions: {}\n  Details: {}\n\n",
-            period.name,
-            period.temperature,
-            period.temperature_unit,
-            period.wind_speed,
-            period.wind_direction,
-            period.short_forecast,
-            period.detailed_forecast
+            "{}: {} at {} UTC\n  Conditions: {}\n  Impact: {}\n",
+            leg.label,
+            format_coords(leg.latitude, leg.longitude),
+            leg.time.format("%Y-%m-%d %H:%M"),
+            conditions,
+            travel_impact_label(leg.impact)
         ));
     }
     output
 }
 
-/// Formats Open-Meteo forecast into a human-readable string
-pub fn format_open_meteo_forecast(forecast: OpenMeteoResponse) -> String {
+/// Formats precipitation blocks from an hourly forecast as a compact timeline
+pub fn format_rain_timeline(
+    forecast: &OpenMeteoHourlyResponse,
+    blocks: &[PrecipitationBlock],
+    time_format: TimeFormat,
+) -> String {
+    let hourly = &forecast.hourly;
+    let mut output = format!(
+        "Rain Timeline (next {} hours)\nLocation: {}\nTimezone: {}\n\n",
+        hourly.time.len(),
+        format_coords(forecast.latitude, forecast.longitude),
+        forecast.timezone
+    );
+
+    if blocks.is_empty() {
+        output.push_str("No meaningful precipitation expected.\n");
+        return output;
+    }
+
+    let unit = forecast
+        .hourly_units
+        .get("precipitation")
+        .map(String::as_str)
+        .unwrap_or("mm");
+    let time = |i: usize, offset_hours: i64| {
+        hourly
+            .time
+            .get(i)
+            .and_then(|raw| parse_local_time(raw))
+            .map(|t| t + Duration::hours(offset_hours))
+            .map(|t| format!("{} {}", t.format("%a"), time_format.format(t)))
+            .unwrap_or_else(|| "?".to_string())
+    };
+
+    for block in blocks {
+        let probability = (block.start..=block.end)
+            .filter_map(|i| hourly.precipitation_probability.get(i).copied().flatten())
+            .max_by(f64::total_cmp);
+        let likelihood = match probability {
+            Some(p) if p >= 60.0 => format!("likely ({:.0}%)", p),
+            Some(p) => format!("possible ({:.0}%)", p),
+            None => "expected".to_string(),
+        };
+        let total: f64 = (block.start..=block.end)
+            .filter_map(|i| hourly.precipitation.get(i).copied().flatten())
+            .sum();
+
+        output.push_str(&format!(
+            "- {} to {}: precipitation {}, {:.1} {} total",
+            time(block.start, 0),
+            time(block.end, 1),
+            likelihood,
+            total,
+            unit
+        ));
+        if block.end > block.start && total > 0.0 {
+            output.push_str(&format!(", heaviest around {}", time(block.peak, 0)));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Formats the pollen forecast with current and peak levels per pollen type
+pub fn format_pollen(pollen: PollenResponse, time_format: TimeFormat) -> String {
+    let header = format!(
+        "Pollen Forecast (Open-Meteo, Europe only)\nLocation: {}\n\n",
+        format_coords(pollen.latitude, pollen.longitude)
+    );
+
+    let current = pollen.current.as_ref();
+    let hourly = &pollen.hourly;
+    let types = [
+        ("Alder", current.and_then(|c| c.alder_pollen), &hourly.alder_pollen, TREE_POLLEN),
+        ("Birch", current.and_then(|c| c.birch_pollen), &hourly.birch_pollen, TREE_POLLEN),
+        ("Grass", current.and_then(|c| c.grass_pollen), &hourly.grass_pollen, GRASS_POLLEN),
+        ("Ragweed", current.and_then(|c| c.ragweed_pollen), &hourly.ragweed_pollen, RAGWEED_POLLEN),
+    ];
+
+    let mut output = header;
+    let mut has_data = false;
+    for (name, now, series, thresholds) in types {
+        let peak = series
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| value.map(|v| (i, v)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if now.is_none() && peak.is_none() {
+            continue;
+        }
+        has_data = true;
+
+        output.push_str(&format!("{}:\n", name));
+        if let Some(value) = now {
+            output.push_str(&format!(
+                "  Current: {:.0} grains/m\u{00b3} ({})\n",
+                value,
+                pollen_level(value, thresholds)
+            ));
+        }
+        if let Some((i, value)) = peak {
+            let time = hourly
+                .time
+                .get(i)
+                .and_then(|raw| parse_local_time(raw))
+                .map(|t| format!("{} {}", t.format("%a"), time_format.format(t)))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "  Peak: {:.0} grains/m\u{00b3} ({}) {}\n",
+                value,
+                pollen_level(value, thresholds),
+                time
+            ));
+        }
+        output.push('\n');
+    }
+
+    if !has_data {
+        output.push_str(
+            "No pollen data is available for this location. Pollen forecasts are only \
+            provided for Europe.\n",
+        );
+    }
+    output
+}
+
+/// Moderate and high thresholds in grains/m³ for a pollen type
+type PollenThresholds = (f64, f64);
+
+const TREE_POLLEN: PollenThresholds = (15.0, 90.0);
+const GRASS_POLLEN: PollenThresholds = (20.0, 50.0);
+const RAGWEED_POLLEN: PollenThresholds = (10.0, 50.0);
+
+/// Categorizes a pollen concentration as Low, Moderate, or High
+fn pollen_level(value: f64, (moderate, high): PollenThresholds) -> &'static str {
+    if value >= high {
+        "High"
+    } else if value >= moderate {
+        "Moderate"
+    } else {
+        "Low"
+    }
+}
+
+/// Formats an ISO date string, falling back to the raw value if it can't be parsed
+fn format_date(raw: &str, date_format: DateFormat) -> String {
+    let pattern = match date_format {
+        DateFormat::Iso => return raw.to_string(),
+        DateFormat::Short => "%a %b %-d",
+        DateFormat::Weekday => "%A",
+    };
+
+    match NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        Ok(date) => date.format(pattern).to_string(),
+        Err(_) => raw.to_string(),
+    }
+}
+
+/// Parses an Open-Meteo local timestamp such as "2024-06-15T05:12"
+fn parse_local_time(raw: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M").ok()
+}
+
+/// Answers "is it raining?" from current conditions.
+///
+/// Rain, drizzle, showers, and thunderstorm codes count as rain even when the last
+/// hour's total is still zero; measurable precipitation counts too unless the code
+/// says it is falling as snow.
+pub fn format_is_it_raining(current: &OpenMeteoCurrentResponse) -> String {
+    let conditions = &current.current;
+    let precipitation = conditions.precipitation.unwrap_or(0.0);
+    let code = conditions.weather_code;
+    let is_snow = matches!(code, Some(71..=77 | 85 | 86));
+    let is_rain_code = matches!(code, Some(51..=67 | 80..=82 | 95..=99));
+    let raining = is_rain_code || (precipitation > 0.0 && !is_snow);
+
+    let description = code
+        .map(weather_code_to_description)
+        .unwrap_or("Unknown")
+        .to_lowercase();
+    let mut answer = if raining {
+        format!("Yes, {}", description)
+    } else {
+        format!("No, it's {}", description)
+    };
+    if precipitation > 0.0 {
+        let unit = current
+            .current_units
+            .get("precipitation")
+            .map(String::as_str)
+            .unwrap_or("mm");
+        answer.push_str(&format!(" ({:.1} {} in the last hour)", precipitation, unit));
+    }
+    match conditions.is_day {
+        Some(0) => answer.push_str(", currently nighttime"),
+        Some(_) => answer.push_str(", currently daytime"),
+        None => {}
+    }
+
+    format!(
+        "{}.\nLocation: {}\nAs of: {}\n",
+        answer,
+        format_coords(current.latitude, current.longitude),
+        conditions.time
+    )
+}
+
+/// Formats current wind for a wind sport: sustained speed and direction, gusts,
+/// the gust factor, and whether it suits the sport. Speeds are in `unit`.
+pub fn format_wind_conditions(
+    current: &OpenMeteoCurrentResponse,
+    sport: WindSport,
+    unit: SpeedUnit,
+) -> String {
+    let mut output = format!(
+        "Wind Conditions (Open-Meteo)\nLocation: {}\n\n",
+        format_coords(current.latitude, current.longitude)
+    );
+    let data = &current.current;
+    let (Some(sustained), Some(gust)) = (data.wind_speed, data.wind_gusts) else {
+        output.push_str("Current wind data is not available.\n");
+        return output;
+    };
+
+    let label = unit.label();
+    let direction = data
+        .wind_direction
+        .map(|degrees| format!(" from {} ({:.0}°)", degrees_to_cardinal(degrees), degrees))
+        .unwrap_or_default();
+    output.push_str(&format!("Sustained: {:.1} {}{}\n", sustained, label, direction));
+    output.push_str(&format!("Gusts: {:.1} {}\n", gust, label));
+    match gust_factor(sustained, gust) {
+        Some(factor) => output.push_str(&format!("Gust factor: {:.2}\n", factor)),
+        None => output.push_str("Gust factor: n/a (calm)\n"),
+    }
+
+    let (min, max) = sport.range_knots();
+    let note = match wind_verdict(sport, unit.to_knots(sustained), unit.to_knots(gust)) {
+        WindVerdict::TooLight => "Too light \u{2014} not enough wind.",
+        WindVerdict::Good => "Good \u{2014} steady wind in range.",
+        WindVerdict::Gusty => "Marginal \u{2014} in range but gusty.",
+        WindVerdict::TooStrong => "Too strong \u{2014} stay ashore unless very experienced.",
+    };
+    output.push_str(&format!(
+        "\nFor {} ({:.0}-{:.0} kn steady): {}\n",
+        sport.name(),
+        min,
+        max,
+        note
+    ));
+    output
+}
+
+/// Formats current conditions as a single line for compact UIs, e.g.
+/// "Berlin: 14°C, partly cloudy, light wind."
+pub fn format_oneliner(
+    current: &OpenMeteoCurrentResponse,
+    name: Option<&str>,
+    units: Units,
+) -> String {
+    let conditions = &current.current;
+    let mut parts = Vec::new();
+    if let Some(temperature) = conditions.temperature {
+        let unit = current
+            .current_units
+            .get("temperature_2m")
+            .map(String::as_str)
+            .unwrap_or("\u{00b0}C");
+        parts.push(format_temperature(temperature, unit, units, 0));
+    }
+    if let Some(code) = conditions.weather_code {
+        parts.push(weather_code_to_description(code).to_lowercase());
+    }
+    if let Some(speed) = conditions.wind_speed {
+        let speed_kmh = match units {
+            Units::Imperial => mph_to_kmh(speed),
+            Units::Metric | Units::Kelvin => speed,
+        };
+        let wind = match to_beaufort(speed_kmh).0 {
+            0 => "calm",
+            1..=3 => "light wind",
+            4..=5 => "moderate wind",
+            6..=7 => "strong wind",
+            _ => "gale-force wind",
+        };
+        parts.push(wind.to_string());
+    }
+    if parts.is_empty() {
+        parts.push("conditions unavailable".to_string());
+    }
+
+    let place = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        // Keep the output to one line whatever the caller passes
+        Some(name) => name.split_whitespace().collect::<Vec<_>>().join(" "),
+        None => format_coords(current.latitude, current.longitude),
+    };
+    format!("{}: {}.", place, parts.join(", "))
+}
+
+/// Limits for "nice" weather, in the unit system of the data they are checked against
+#[derive(Debug, Clone, Copy)]
+pub struct NiceWeather {
+    pub min_temperature: f64,
+    pub max_temperature: f64,
+    pub max_precipitation_probability: f64,
+    pub max_wind_speed: f64,
+}
+
+impl NiceWeather {
+    /// Lists the ways conditions miss the limits; empty when they are nice.
+    ///
+    /// Adverse weather codes always count against, whatever the other values.
+    fn shortfalls(
+        &self,
+        temperature: f64,
+        precipitation_probability: Option<f64>,
+        wind_speed: f64,
+        weather_code: i32,
+        unit_labels: (&str, &str),
+        units: Units,
+    ) -> Vec<String> {
+        let (temperature_unit, wind_unit) = unit_labels;
+        let temperature_label = |value| format_temperature(value, temperature_unit, units, 1);
+
+        let mut shortfalls = Vec::new();
+        if temperature < self.min_temperature {
+            shortfalls.push(format!(
+                "{} is below {}",
+                temperature_label(temperature),
+                temperature_label(self.min_temperature)
+            ));
+        } else if temperature > self.max_temperature {
+            shortfalls.push(format!(
+                "{} is above {}",
+                temperature_label(temperature),
+                temperature_label(self.max_temperature)
+            ));
+        }
+        if let Some(probability) =
+            precipitation_probability.filter(|p| *p > self.max_precipitation_probability)
+        {
+            shortfalls.push(format!(
+                "{:.0}% chance of precipitation is above {:.0}%",
+                probability, self.max_precipitation_probability
+            ));
+        }
+        if wind_speed > self.max_wind_speed {
+            shortfalls.push(format!(
+                "wind {:.1} {} is above {:.1} {}",
+                wind_speed, wind_unit, self.max_wind_speed, wind_unit
+            ));
+        }
+        if is_adverse_weather(weather_code) {
+            shortfalls.push(weather_code_to_description(weather_code).to_lowercase());
+        }
+        shortfalls
+    }
+}
+
+/// Answers whether current conditions are nice, and if not, the first forecast day
+/// that is. Days are judged by their high, precipitation chance, and peak wind.
+pub fn format_nice_weather(
+    current: &OpenMeteoCurrentResponse,
+    forecast: &OpenMeteoResponse,
+    limits: &NiceWeather,
+    units: Units,
+) -> String {
+    let daily_units = forecast.units_or_default(units);
+    let unit_labels = (
+        daily_units.temperature_max.as_str(),
+        daily_units.wind_speed_max.as_str(),
+    );
+    let temperature = |value| format_temperature(value, unit_labels.0, units, 1);
+
     let mut output = format!(
-        "Weather Forecast (Open-Meteo)\nLocation: {:.4}, {:.4}\nTimezone: {}\n\n",
-        forecast.latitude, forecast.longitude, forecast.timezone
+        "Nice Weather Check (Open-Meteo)\nLocation: {}\nCriteria: {} to {}, precipitation chance at most {:.0}%, wind at most {:.1} {}\n\n",
+        format_coords(current.latitude, current.longitude),
+        temperature(limits.min_temperature),
+        temperature(limits.max_temperature),
+        limits.max_precipitation_probability,
+        limits.max_wind_speed,
+        unit_labels.1
     );
 
-    for i in 0..forecast.daily.time.len().min(7) {
-        let weather_desc = weather_code_to_description(forecast.daily.weather_code[i]);
+    let now = &current.current;
+    let (Some(now_temperature), Some(now_wind), Some(now_code)) =
+        (now.temperature, now.wind_speed, now.weather_code)
+    else {
+        output.push_str("Now: unknown, current conditions are unavailable\n");
+        return output;
+    };
+    let shortfalls =
+        limits.shortfalls(now_temperature, None, now_wind, now_code, unit_labels, units);
+    if shortfalls.is_empty() {
         output.push_str(&format!(
-            "{}:\n  Temperature: {:.1}\u{00b0}{} - {:.1}\u{00b0}{}\n  Conditions: {}\n  Wind Speed: {:.1} {}\n  Precipitation: {:.1} {}\n\n",
-            forecast.daily.time[i],
-            forecast.daily.temperature_min[i],
-            forecast.daily_units.temperature_max,
-            forecast.daily.temperature_max[i],
-            forecast.daily_units.temperature_max,
-            weather_desc,
-            forecast.daily.wind_speed_max[i],
-            forecast.daily_units.wind_speed_max,
-            forecast.daily.precipitation_sum[i],
-            forecast.daily_units.precipitation_sum
+            "Now: Yes \u{2014} {}, wind {:.1} {}, {}\n",
+            temperature(now_temperature),
+            now_wind,
+            unit_labels.1,
+            weather_code_to_description(now_code).to_lowercase()
         ));
+        return output;
+    }
+    output.push_str(&format!("Now: No \u{2014} {}\n", shortfalls.join("; ")));
+
+    let daily = &forecast.daily;
+    let nice_day = (0..daily.time.len()).find(|&i| {
+        let probability = daily.precipitation_probability_max.get(i).copied().flatten();
+        limits
+            .shortfalls(
+                daily.temperature_max[i],
+                probability,
+                daily.wind_speed_max[i],
+                daily.weather_code[i],
+                unit_labels,
+                units,
+            )
+            .is_empty()
+    });
+    match nice_day {
+        Some(i) => output.push_str(&format!(
+            "Next nice day: {} (high {}, {}, wind {:.1} {})\n",
+            daily.time[i],
+            temperature(daily.temperature_max[i]),
+            weather_code_to_description(daily.weather_code[i]).to_lowercase(),
+            daily.wind_speed_max[i],
+            unit_labels.1
+        )),
+        None => output.push_str(&format!(
+            "Next nice day: none in the next {} days\n",
+            daily.time.len()
+        )),
+    }
+    output
+}
+
+/// Whether a WMO weather code counts as adverse weather.
+///
+/// The set comes from `MCP_WEATHER_BAD_CODES` when it lists any valid codes, and
+/// from `DEFAULT_ADVERSE_WEATHER_CODES` otherwise. It is read once per process.
+pub fn is_adverse_weather(code: i32) -> bool {
+    static CODES: OnceLock<Vec<i32>> = OnceLock::new();
+    CODES
+        .get_or_init(|| {
+            adverse_weather_codes(&std::env::var(BAD_CODES_ENV_VAR).unwrap_or_default())
+        })
+        .contains(&code)
+}
+
+/// Parses a comma-separated list of adverse weather codes, skipping invalid entries
+/// and falling back to `DEFAULT_ADVERSE_WEATHER_CODES` when none are valid
+fn adverse_weather_codes(configured: &str) -> Vec<i32> {
+    let codes: Vec<i32> = configured
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .filter_map(|part| match part.trim().parse() {
+            Ok(code) => Some(code),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid weather code '{}' in {}", part, BAD_CODES_ENV_VAR);
+                None
+            }
+        })
+        .collect();
+    if codes.is_empty() {
+        DEFAULT_ADVERSE_WEATHER_CODES.to_vec()
+    } else {
+        codes
+    }
+}
+
+/// Formats the current NWS forecast period against the nearest station's latest
+/// observation, e.g. "Forecast 72°F, observed 68°F, off by 4°"
+pub fn format_forecast_accuracy(
+    period: &ForecastPeriod,
+    station: &StationProperties,
+    observation: &ObservationProperties,
+    latitude: f64,
+    longitude: f64,
+) -> String {
+    let mut output = format!(
+        "Forecast Accuracy (NWS)\nLocation: {}\nStation: {} ({})\nObserved at: {}",
+        format_coords(latitude, longitude),
+        station.station_identifier,
+        station.name,
+        observation.timestamp
+    );
+    if let Some(description) = observation.text_description.as_deref().filter(|d| !d.is_empty()) {
+        output.push_str(&format!(", {}", description));
+    }
+    output.push_str("\n\n");
+
+    // Observations are reported in Celsius; convert to the forecast's unit
+    let unit = period.temperature_unit();
+    let observed = observation.temperature.value.map(|value| {
+        match (observation.temperature.unit_code.as_deref(), unit) {
+            (Some("wmoUnit:degF"), "C") => (value - 32.0) * 5.0 / 9.0,
+            (Some("wmoUnit:degF"), _) | (_, "C") => value,
+            _ => celsius_to_fahrenheit(value),
+        }
+        .round()
+    });
+
+    match observed {
+        Some(observed) => output.push_str(&format!(
+            "{}: forecast {}\u{00b0}{}, observed {:.0}\u{00b0}{}, off by {:.0}\u{00b0}\n",
+            period.name,
+            period.temperature,
+            unit,
+            observed,
+            unit,
+            (observed - period.temperature as f64).abs()
+        )),
+        None => output.push_str(&format!(
+            "{}: forecast {}\u{00b0}{}, but {} did not report a temperature\n",
+            period.name, period.temperature, unit, station.station_identifier
+        )),
+    }
+    output
+}
+
+/// Ranks a WMO weather code from 0 (clear sky) to 10 (thunderstorm with hail)
+pub fn weather_code_severity(code: i32) -> u8 {
+    match code {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 3,
+        45 | 48 => 4,
+        51 | 53 | 55 => 5,
+        61 | 63 | 65 | 80..=82 => 6,
+        71 | 73 | 75 | 77 | 85 | 86 => 7,
+        95 => 9,
+        96 | 99 => 10,
+        _ => 0,
+    }
+}
+
+/// WMO weather codes Open-Meteo reports that `weather_code_to_description` names
+const WEATHER_CODES: &[i32] = &[
+    0, 1, 2, 3, 45, 48, 51, 53, 55, 61, 63, 65, 71, 73, 75, 77, 80, 81, 82, 85, 86, 95, 96, 99,
+];
+
+/// Formats the WMO weather code table as a two-column list sorted by code
+pub fn format_weather_code_legend() -> String {
+    let mut output = String::from("WMO Weather Codes:\n\n");
+    for &code in WEATHER_CODES {
+        output.push_str(&format!("  {:>2}  {}\n", code, weather_code_to_description(code)));
     }
     output
 }
@@ -83,10 +2664,793 @@ fn weather_code_to_description(code: i32) -> &'static str {
         61 | 63 | 65 => "Rain",
         71 | 73 | 75 => "Snow",
         77 => "Snow grains",
-        80 | 81 | 82 => "Rain showers",
+        80..=82 => "Rain showers",
         85 | 86 => "Snow showers",
         95 => "Thunderstorm",
         96 | 99 => "Thunderstorm with hail",
         _ => "Unknown",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_wind_speed_takes_the_upper_bound() {
+        assert_eq!(parse_wind_speed_mph("10 mph"), Some(10.0));
+        assert_eq!(parse_wind_speed_mph("5 to 15 mph"), Some(15.0));
+        assert_eq!(parse_wind_speed_mph("calm"), None);
+    }
+
+    /// An Open-Meteo daily forecast for Berlin from the given `daily` block
+    fn open_meteo(daily: serde_json::Value) -> OpenMeteoResponse {
+        serde_json::from_value(serde_json::json!({
+            "latitude": 52.52,
+            "longitude": 13.41,
+            "timezone": "Europe/Berlin",
+            "daily": daily,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn narrative_describes_changes_over_the_period() {
+        let forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15", "2024-06-16", "2024-06-17"],
+            "temperature_2m_max": [20.0, 22.0, 25.0],
+            "temperature_2m_min": [10.0, 11.0, 12.0],
+            "weather_code": [0, 0, 61],
+            "wind_speed_10m_max": [10.0, 12.0, 20.0],
+            "precipitation_sum": [0.0, 0.0, 5.2],
+        }));
+        let narrative = format_open_meteo_narrative(forecast, FormatOptions::default());
+        assert_eq!(
+            narrative.entries,
+            vec![
+                "Expect clear sky today with a high near 20°C and a low around 10°C. \
+                 Conditions turn to rain by Monday. Highs climb to around 25°C by Monday. \
+                 The wettest day looks to be Monday, with about 5 mm of precipitation.\n"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn narrative_handles_an_empty_forecast() {
+        let forecast = open_meteo(serde_json::json!({
+            "time": [],
+            "temperature_2m_max": [],
+            "temperature_2m_min": [],
+            "weather_code": [],
+            "wind_speed_10m_max": [],
+            "precipitation_sum": [],
+        }));
+        let narrative = format_open_meteo_narrative(forecast, FormatOptions::default());
+        assert_eq!(narrative.entries, vec!["No forecast data is available.\n".to_string()]);
+    }
+
+    #[test]
+    fn adverse_codes_come_from_the_configured_list() {
+        assert_eq!(adverse_weather_codes("45, 61,x,95"), vec![45, 61, 95]);
+        assert_eq!(adverse_weather_codes(""), DEFAULT_ADVERSE_WEATHER_CODES);
+        assert_eq!(adverse_weather_codes("rain"), DEFAULT_ADVERSE_WEATHER_CODES);
+    }
+
+    #[test]
+    fn default_adverse_codes_cover_precipitation_but_not_clouds() {
+        assert!(is_adverse_weather(61));
+        assert!(is_adverse_weather(95));
+        assert!(!is_adverse_weather(0));
+        assert!(!is_adverse_weather(3));
+    }
+
+    /// Current conditions in Berlin, raining, with the given `is_day` flag
+    fn light_rain(is_day: Option<i32>) -> OpenMeteoCurrentResponse {
+        serde_json::from_value(serde_json::json!({
+            "latitude": 52.52,
+            "longitude": 13.41,
+            "current": {
+                "time": "2024-06-15T22:00",
+                "precipitation": 0.4,
+                "weather_code": 61,
+                "is_day": is_day,
+            },
+            "current_units": { "precipitation": "mm" },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn is_it_raining_says_whether_it_is_day_or_night() {
+        let answer = |is_day| {
+            let text = format_is_it_raining(&light_rain(is_day));
+            text.lines().next().unwrap().to_string()
+        };
+        assert_eq!(answer(Some(1)), "Yes, rain (0.4 mm in the last hour), currently daytime.");
+        assert_eq!(answer(Some(0)), "Yes, rain (0.4 mm in the last hour), currently nighttime.");
+        assert_eq!(answer(None), "Yes, rain (0.4 mm in the last hour).");
+    }
+
+    #[test]
+    fn degree_days_keep_running_totals_and_skip_missing_days() {
+        let day = |heating, cooling| Some(DegreeDays { heating, cooling });
+        let days = [
+            ("2024-03-01".to_string(), day(8.0, 0.0)),
+            ("2024-03-02".to_string(), None),
+            ("2024-03-03".to_string(), day(2.5, 1.0)),
+        ];
+        let text = format_degree_days(52.52, 13.41, 18.0, "°C", Units::Metric, &days);
+        assert!(text.contains("2024-03-01: HDD 8.0, CDD 0.0 (cumulative HDD 8.0, CDD 0.0)\n"));
+        assert!(text.contains("2024-03-02: no data\n"));
+        assert!(text.contains("2024-03-03: HDD 2.5, CDD 1.0 (cumulative HDD 10.5, CDD 1.0)\n"));
+        assert!(text.contains("Total over 2 days: HDD 10.5, CDD 1.0\n"));
+        assert!(text.contains("1 of 3 days had no data and are not counted.\n"));
+    }
+
+    #[test]
+    fn truncation_counts_accented_names_as_characters() {
+        let mut forecast = FormattedForecast {
+            header: "Forecast for Zürich\n".to_string(),
+            entries: vec![
+                "Mañana: 12°C, überwiegend bewölkt\n".to_string(),
+                "Später: 9°C, Schneefall mit Böen bis 60 km/h über dem Üetliberg\n".to_string(),
+            ],
+            footer: String::new(),
+        };
+        let chars = forecast.clone().into_text().chars().count();
+        assert!(forecast.clone().into_text().len() > chars);
+
+        let mut fits = forecast.clone();
+        fits.truncate(chars);
+        assert_eq!(fits.entries, forecast.entries);
+
+        // Room for the header, the first entry, and the note, but not the second entry
+        let first = forecast.header.chars().count() + forecast.entries[0].chars().count();
+        forecast.truncate(first + TRUNCATION_NOTE.chars().count());
+        assert_eq!(forecast.entries.len(), 1);
+        assert!(forecast.entries[0].ends_with(TRUNCATION_NOTE));
+    }
+
+    #[test]
+    fn accented_alert_areas_and_locations_are_kept_intact() {
+        let alerts: AlertResponse = serde_json::from_value(serde_json::json!({
+            "features": [{
+                "properties": {
+                    "id": "urn:oid:2.49.0.1.840.0.1",
+                    "event": "Flood Advisory",
+                    "severity": "Minor",
+                    "areaDesc": "Mayagüez; Añasco; Peñuelas",
+                },
+            }],
+        }))
+        .unwrap();
+        assert!(format_alerts(alerts).contains("  Area: Mayagüez; Añasco; Peñuelas\n"));
+
+        let ranked = [
+            RankedLocation {
+                name: "São Paulo".to_string(),
+                mean_high: 27.0,
+                days: 7,
+            },
+            RankedLocation {
+                name: "Zürich".to_string(),
+                mean_high: 18.5,
+                days: 7,
+            },
+        ];
+        let table = format_location_ranking("this week", &ranked, &[], "°C", Units::Metric);
+        let rows: Vec<&str> = table.lines().skip_while(|line| !line.starts_with("Rank")).collect();
+        assert_eq!(rows[1], "   1     27.0°C     7  São Paulo");
+        assert_eq!(rows[2], "   2     18.5°C     7  Zürich");
+    }
+
+    #[test]
+    fn geojson_is_a_point_feature_with_daily_properties() {
+        let forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15", "2024-06-16"],
+            "temperature_2m_max": [20.0, 22.0],
+            "temperature_2m_min": [10.0, 11.0],
+            "weather_code": [0, 61],
+            "wind_speed_10m_max": [10.0, 12.0],
+            "precipitation_sum": [0.0, 5.2],
+        }));
+        let text = open_meteo_geojson(&forecast, Units::Metric).to_string();
+        let feature: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(feature["type"], "Feature");
+        assert_eq!(feature["geometry"]["type"], "Point");
+        assert_eq!(feature["geometry"]["coordinates"], serde_json::json!([13.41, 52.52]));
+        let properties = feature["properties"].as_object().unwrap();
+        let keys: Vec<&str> = properties.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["daily", "source", "timezone", "units"]);
+        assert_eq!(properties["units"]["temperature"], "°C");
+
+        let days = properties["daily"].as_array().unwrap();
+        assert_eq!(days.len(), 2);
+        let day_keys: Vec<&str> = days[1].as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(
+            day_keys,
+            ["conditions", "date", "high", "low", "precipitation", "weather_code"]
+        );
+        assert_eq!(days[1]["conditions"], "Rain");
+        assert_eq!(days[1]["precipitation"], 5.2);
+    }
+
+    #[test]
+    fn alert_text_decodes_entities_and_collapses_blank_lines() {
+        let text = "Thunderstorms &amp; hail expected.  \r\n\r\n\r\n \n\
+                    Don&#39;t drive through flooded roads &lt;1 ft&gt; &x27; &amp\n\
+                    Stay&#x21;";
+        assert_eq!(
+            clean_alert_text(text),
+            "Thunderstorms & hail expected.\n\n\
+             Don't drive through flooded roads <1 ft> &x27; &amp\n\
+             Stay!"
+        );
+    }
+
+    /// Forecast arrays for Berlin in °C, as `get_forecast` returns them
+    fn arrays(
+        dates: &[&str],
+        highs: &[f64],
+        lows: &[f64],
+        precipitation: &[f64],
+    ) -> ForecastArrays {
+        serde_json::from_value(serde_json::json!({
+            "latitude": 52.52,
+            "longitude": 13.41,
+            "units": { "temperature": "°C", "precipitation": "mm" },
+            "dates": dates,
+            "highs": highs,
+            "lows": lows,
+            "precipitation": precipitation,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn forecast_diff_compares_shared_days() {
+        let previous = arrays(
+            &["2024-06-14", "2024-06-15", "2024-06-16"],
+            &[17.0, 18.0, 20.0],
+            &[9.0, 10.0, 12.0],
+            &[0.0, 0.0, 4.0],
+        );
+        let current = arrays(
+            &["2024-06-15", "2024-06-16", "2024-06-17"],
+            &[21.0, 20.3, 23.0],
+            &[10.0, 11.0, 13.0],
+            &[0.0, 4.0, 0.0],
+        );
+        let changes = diff_forecast_arrays(&previous, &current);
+        assert_eq!(
+            changes.iter().map(|change| change.date.as_str()).collect::<Vec<_>>(),
+            ["2024-06-15", "2024-06-16"]
+        );
+
+        let text = format_forecast_diff(&previous, &current, &changes, DateFormat::Iso);
+        assert!(text.contains("2024-06-15: high 18.0°C \u{2192} 21.0°C (revised up 3.0)\n"));
+        assert!(text.contains("2024-06-16: low 12.0°C \u{2192} 11.0°C (revised down 1.0)\n"));
+        assert!(text.contains("\n1 day(s) added since the earlier forecast.\n"));
+    }
+
+    #[test]
+    fn yesterday_comparison_reports_the_change_in_high() {
+        let compare = |highs, unit, units| {
+            let text = format_yesterday_comparison(52.52, 13.41, highs, unit, units);
+            text.lines().last().unwrap().to_string()
+        };
+        assert_eq!(
+            compare((Some(20.0), Some(22.5)), "°C", Units::Metric),
+            "2.5°C warmer than yesterday."
+        );
+        assert_eq!(
+            compare((Some(20.0), Some(7.0)), "°C", Units::Kelvin),
+            "13.0K cooler than yesterday."
+        );
+        assert_eq!(
+            compare((Some(70.0), Some(70.8)), "°F", Units::Imperial),
+            "About the same as yesterday."
+        );
+        assert_eq!(
+            compare((None, Some(22.5)), "°C", Units::Metric),
+            "Yesterday: not available yet, so there's nothing to compare."
+        );
+    }
+
+    #[test]
+    fn current_weather_header_marks_day_or_night() {
+        let header = |is_day: Option<u8>| {
+            let current: CurrentWeather = serde_json::from_value(serde_json::json!({
+                "temperature": 14.0,
+                "windspeed": 9.0,
+                "winddirection": 270.0,
+                "weathercode": 0,
+                "is_day": is_day,
+            }))
+            .unwrap();
+            let units = DailyUnits::defaults(Units::Metric);
+            format_current_weather(&current, &units, FormatOptions::default())
+        };
+        assert!(header(Some(1)).starts_with("Current: 14.0°C, Clear sky (day), wind"));
+        assert!(header(Some(0)).starts_with("Current: 14.0°C, Clear sky (night), wind"));
+        assert!(header(None).starts_with("Current: 14.0°C, Clear sky, wind"));
+    }
+
+    /// An NWS forecast period in Topeka's time zone; `start` and `end` are local
+    /// "YYYY-MM-DDTHH:MM" times
+    fn nws_period(
+        name: &str,
+        (start, end): (&str, &str),
+        temperature: i32,
+        short_forecast: &str,
+    ) -> serde_json::Value {
+        let is_daytime = start.ends_with("06:00");
+        serde_json::json!({
+            "name": name,
+            "startTime": format!("{}:00-05:00", start),
+            "endTime": format!("{}:00-05:00", end),
+            "isDaytime": is_daytime,
+            "temperature": temperature,
+            "temperatureUnit": "F",
+            "windSpeed": "5 to 10 mph",
+            "windDirection": "S",
+            "shortForecast": short_forecast,
+            "detailedForecast": format!("{}.", short_forecast),
+        })
+    }
+
+    fn nws_forecast(periods: Vec<serde_json::Value>) -> ForecastResponse {
+        serde_json::from_value(serde_json::json!({ "properties": { "periods": periods } }))
+            .unwrap()
+    }
+
+    #[test]
+    fn merged_forecast_pairs_each_day_with_its_night() {
+        let forecast = nws_forecast(vec![
+            nws_period("Tonight", ("2024-06-17T18:00", "2024-06-18T06:00"), 62, "Mostly Clear"),
+            nws_period("Tuesday", ("2024-06-18T06:00", "2024-06-18T18:00"), 85, "Sunny"),
+            nws_period("Tuesday Night", ("2024-06-18T18:00", "2024-06-19T06:00"), 65, "Rain"),
+            nws_period("Wednesday", ("2024-06-19T06:00", "2024-06-19T18:00"), 80, "Cloudy"),
+        ]);
+        let merged = format_forecast_merged(forecast, 39.05, -95.68);
+        assert_eq!(
+            merged.entries,
+            [
+                "Tonight:\n  Low: 62°F\n  Wind: 5 to 10 mph S\n  Conditions: Mostly Clear\n\n",
+                "Tuesday:\n  High: 85°F, Low: 65°F\n  Wind: 5 to 10 mph S\n  \
+                 Conditions: Sunny, then Rain\n\n",
+                "Wednesday:\n  High: 80°F\n  Wind: 5 to 10 mph S\n  Conditions: Cloudy\n\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn dates_render_in_each_format() {
+        let date = |name| format_date("2024-06-15", DateFormat::parse(name).unwrap());
+        assert_eq!(date("iso"), "2024-06-15");
+        assert_eq!(date("short"), "Sat Jun 15");
+        assert_eq!(date("Weekday"), "Saturday");
+        assert!(DateFormat::parse("long").is_none());
+        assert_eq!(format_date("not a date", DateFormat::Short), "not a date");
+    }
+
+    #[test]
+    fn coordinates_use_four_decimal_places() {
+        assert_eq!(format_coords(52.52, 13.41), "52.5200, 13.4100");
+        assert_eq!(format_coords(-33.868_82, 151.209_29), "-33.8688, 151.2093");
+    }
+
+    #[test]
+    fn temperatures_render_in_each_unit_system() {
+        assert_eq!(format_temperature(0.0, "°C", Units::Kelvin, 1), "273.2 K");
+        assert_eq!(format_temperature(21.46, "°C", Units::Metric, 1), "21.5°C");
+        assert_eq!(format_temperature(70.0, "°F", Units::Imperial, 0), "70°F");
+    }
+
+    #[test]
+    fn truncation_drops_whole_entries_and_notes_it() {
+        let day = |date: &str| format!("{}:\n  High: 21.0°C\n  Conditions: Clear sky\n\n", date);
+        let mut forecast = FormattedForecast {
+            header: "Weather Forecast (Open-Meteo)\n\n".to_string(),
+            entries: vec![day("2024-06-15"), day("2024-06-16"), day("2024-06-17")],
+            footer: "Source: Open-Meteo\n".to_string(),
+        };
+        let fixed = forecast.header.chars().count() + forecast.footer.chars().count();
+        let entry = forecast.entries[0].chars().count();
+        forecast.truncate(fixed + 2 * entry);
+
+        assert_eq!(forecast.entries.len(), 1);
+        assert_eq!(forecast.entries[0], format!("{}{}", day("2024-06-15"), TRUNCATION_NOTE));
+        assert_eq!(forecast.footer, "Source: Open-Meteo\n");
+        assert!(forecast.into_text().chars().count() <= fixed + 2 * entry);
+    }
+
+    #[test]
+    fn alert_headlines_leave_out_descriptions() {
+        let alerts: AlertResponse = serde_json::from_value(serde_json::json!({
+            "features": [{
+                "properties": {
+                    "id": "urn:oid:2.49.0.1.840.0.2",
+                    "event": "Winter Storm Warning",
+                    "headline": "Winter Storm Warning until 6 PM",
+                    "description": "Heavy snow expected. Total accumulations of 8 to 12 inches.",
+                    "instruction": "Travel could be very difficult.",
+                    "severity": "Severe",
+                    "areaDesc": "Summit County",
+                    "messageType": "Update",
+                },
+            }],
+        }))
+        .unwrap();
+        assert_eq!(
+            format_alert_headlines(alerts),
+            "Active Weather Alerts (1):\n\
+             - Winter Storm Warning [UPDATE] (Severe): Summit County\n"
+        );
+    }
+
+    #[test]
+    fn missing_daily_units_fall_back_to_metric_labels() {
+        let forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15"],
+            "temperature_2m_max": [21.0],
+            "temperature_2m_min": [11.0],
+            "weather_code": [61],
+            "wind_speed_10m_max": [14.0],
+            "precipitation_sum": [2.5],
+        }));
+        assert!(forecast.daily_units.is_none());
+
+        let text = format_open_meteo_forecast(forecast, FormatOptions::default()).into_text();
+        assert!(text.contains("Temperature: 11.0°C - 21.0°C\n"), "{}", text);
+        assert!(text.contains("Wind Speed: 14.0 km/h\n"), "{}", text);
+        assert!(text.contains("Precipitation: 2.5 mm\n"), "{}", text);
+    }
+
+    #[test]
+    fn nws_period_times_parse_with_their_offset() {
+        let forecast = nws_forecast(vec![
+            nws_period("Today", ("2024-06-15T06:00", "2024-06-15T18:00"), 88, "Sunny"),
+            nws_period("Tonight", ("2024-06-15T18:00", "2024-06-16T06:00"), 66, "Clear"),
+        ]);
+        let (today, tonight) = (&forecast.properties.periods[0], &forecast.properties.periods[1]);
+
+        let start = today.start().unwrap();
+        assert_eq!(start.offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(start.naive_utc().to_string(), "2024-06-15 11:00:00");
+        assert_eq!(tonight.end().unwrap().naive_local().to_string(), "2024-06-16 06:00:00");
+
+        let span = |period| period_span(period, TimeFormat::TwentyFourHour);
+        assert_eq!(span(today).as_deref(), Some("Sat Jun 15, 06:00-18:00"));
+        assert_eq!(span(tonight).as_deref(), Some("Sat Jun 15 18:00 - Sun Jun 16 06:00"));
+    }
+
+    #[test]
+    fn nws_period_without_valid_times_has_no_span() {
+        let mut period = nws_period("Today", ("2024-06-15T06:00", "2024-06-15T18:00"), 88, "Sunny");
+        period["startTime"] = serde_json::json!("sometime today");
+        let forecast = nws_forecast(vec![period]);
+        let period = &forecast.properties.periods[0];
+        assert!(period.start().is_none());
+        assert!(period.end().is_some());
+        assert!(period_span(period, TimeFormat::TwentyFourHour).is_none());
+    }
+
+    #[test]
+    fn is_it_raining_answers_no_on_a_dry_hour() {
+        let mut dry = light_rain(Some(1));
+        dry.current.precipitation = Some(0.0);
+        dry.current.weather_code = Some(0);
+        let text = format_is_it_raining(&dry);
+        assert_eq!(text.lines().next().unwrap(), "No, it's clear sky, currently daytime.");
+
+        // Drizzle starting between readings still counts as rain
+        dry.current.precipitation = Some(0.2);
+        dry.current.weather_code = Some(3);
+        let text = format_is_it_raining(&dry);
+        assert!(text.starts_with("Yes, overcast (0.2 mm in the last hour)"), "{}", text);
+    }
+
+    #[test]
+    fn polar_days_and_nights_replace_missing_sunrise_and_sunset() {
+        let forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-21", "2024-12-21", "2024-03-20"],
+            "temperature_2m_max": [12.0, -18.0, -2.0],
+            "temperature_2m_min": [4.0, -25.0, -9.0],
+            "weather_code": [0, 0, 0],
+            "wind_speed_10m_max": [10.0, 10.0, 10.0],
+            "precipitation_sum": [0.0, 0.0, 0.0],
+            "sunrise": [null, null, "2024-03-20T06:02"],
+            "sunset": [null, null, "2024-03-20T18:14"],
+            "daylight_duration": [86400.0, 0.0, 43920.0],
+        }));
+        let entries = format_open_meteo_forecast(forecast, FormatOptions::default()).entries;
+        assert!(entries[0].contains("  Sun: Polar day (no sunset)\n"), "{}", entries[0]);
+        assert!(entries[1].contains("  Sun: Polar night (no sunrise)\n"), "{}", entries[1]);
+        assert!(entries[2].contains("  Sunrise: 06:02, Sunset: 18:14\n"), "{}", entries[2]);
+        assert!(!entries[..2].iter().any(|entry| entry.contains("Sunrise")));
+    }
+
+    #[test]
+    fn periods_without_a_temperature_unit_render_fahrenheit() {
+        let mut missing =
+            nws_period("Today", ("2024-06-15T06:00", "2024-06-15T18:00"), 88, "Sunny");
+        missing.as_object_mut().unwrap().remove("temperatureUnit");
+        let mut empty =
+            nws_period("Tonight", ("2024-06-15T18:00", "2024-06-16T06:00"), 66, "Clear");
+        empty["temperatureUnit"] = serde_json::json!(" ");
+
+        let text = format_forecast(
+            nws_forecast(vec![missing, empty]),
+            39.7456,
+            -97.0892,
+            TimeFormat::TwentyFourHour,
+            false,
+        )
+        .into_text();
+        assert!(text.contains("Today:\n  Period: Sat Jun 15, 06:00-18:00\n  Temperature: 88°F\n"));
+        assert!(text.contains("  Temperature: 66°F\n"), "{}", text);
+    }
+
+    #[test]
+    fn arrays_are_trimmed_to_the_shortest_series() {
+        let forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15", "2024-06-16", "2024-06-17", "2024-06-18"],
+            "temperature_2m_max": [20.0, 22.0, 25.0],
+            "temperature_2m_min": [10.0, 11.0, 12.0, 13.0],
+            "weather_code": [0, 0, 61, 61],
+            "wind_speed_10m_max": [10.0, 12.0, 20.0, 18.0],
+            "precipitation_sum": [0.0, 0.4],
+        }));
+        let arrays = open_meteo_arrays(&forecast, Units::Metric);
+        assert_eq!(arrays["dates"], serde_json::json!(["2024-06-15", "2024-06-16"]));
+        assert_eq!(arrays["highs"], serde_json::json!([20.0, 22.0]));
+        assert_eq!(arrays["lows"], serde_json::json!([10.0, 11.0]));
+        assert_eq!(arrays["precipitation"], serde_json::json!([0.0, 0.4]));
+
+        // The result round-trips through the type diff_forecast accepts
+        let parsed: ForecastArrays = serde_json::from_value(arrays).unwrap();
+        assert_eq!(parsed.units.temperature, "°C");
+    }
+
+    #[test]
+    fn twelve_hour_clock_handles_midnight_and_noon() {
+        let at = |hour: u32, minute: u32| {
+            let time = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap().and_hms_opt(hour, minute, 0);
+            TimeFormat::TwelveHour.format(time.unwrap())
+        };
+        assert_eq!(at(13, 0), "1:00 PM");
+        assert_eq!(at(0, 0), "12:00 AM");
+        assert_eq!(at(12, 0), "12:00 PM");
+        assert_eq!(at(9, 5), "9:05 AM");
+        assert_eq!(TimeFormat::parse("12H"), Some(TimeFormat::TwelveHour));
+        assert_eq!(TimeFormat::parse("noon"), None);
+    }
+
+    #[test]
+    fn brief_severe_hours_are_noted_after_the_daily_conditions() {
+        let mut forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15", "2024-06-16"],
+            "temperature_2m_max": [24.0, 22.0],
+            "temperature_2m_min": [14.0, 13.0],
+            "weather_code": [3, 3],
+            "wind_speed_10m_max": [10.0, 12.0],
+            "precipitation_sum": [4.0, 0.0],
+        }));
+        forecast.hourly = Some(
+            serde_json::from_value(serde_json::json!({
+                "time": [
+                    "2024-06-15T14:00", "2024-06-15T15:00", "2024-06-15T16:00",
+                    "2024-06-15T17:00", "2024-06-16T15:00",
+                ],
+                "weather_code": [3, 95, 95, 3, 2],
+            }))
+            .unwrap(),
+        );
+        let options = FormatOptions {
+            include_hourly_worst: true,
+            ..FormatOptions::default()
+        };
+
+        let entries = format_open_meteo_forecast(forecast, options).entries;
+        assert!(
+            entries[0].contains("  Conditions: Overcast, but thunderstorm possible 15:00-17:00\n"),
+            "{}",
+            entries[0]
+        );
+        // An hour no worse than the daily code adds nothing
+        assert!(entries[1].contains("  Conditions: Overcast\n"), "{}", entries[1]);
+    }
+
+    #[test]
+    fn nice_weather_answers_for_now_and_finds_the_next_nice_day() {
+        let limits = NiceWeather {
+            min_temperature: 18.0,
+            max_temperature: 28.0,
+            max_precipitation_probability: 20.0,
+            max_wind_speed: 20.0,
+        };
+        let forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15", "2024-06-16", "2024-06-17"],
+            "temperature_2m_max": [15.0, 21.0, 23.0],
+            "temperature_2m_min": [9.0, 11.0, 12.0],
+            "weather_code": [61, 2, 0],
+            "wind_speed_10m_max": [12.0, 30.0, 10.0],
+            "precipitation_sum": [6.0, 0.0, 0.0],
+            "precipitation_probability_max": [90.0, 10.0, 5.0],
+        }));
+        let mut current = light_rain(Some(1));
+        let answer = |current: &OpenMeteoCurrentResponse| {
+            let text = format_nice_weather(current, &forecast, &limits, Units::Metric);
+            text.lines().skip_while(|line| !line.starts_with("Now")).collect::<Vec<_>>().join("\n")
+        };
+
+        current.current.temperature = Some(22.0);
+        current.current.wind_speed = Some(8.0);
+        current.current.weather_code = Some(0);
+        assert_eq!(answer(&current), "Now: Yes \u{2014} 22.0°C, wind 8.0 km/h, clear sky");
+
+        current.current.temperature = Some(12.0);
+        current.current.weather_code = Some(61);
+        assert_eq!(
+            answer(&current),
+            "Now: No \u{2014} 12.0°C is below 18.0°C; rain\n\
+             Next nice day: 2024-06-17 (high 23.0°C, clear sky, wind 10.0 km/h)"
+        );
+    }
+
+    #[test]
+    fn dry_days_show_no_precipitation_unless_zero_amounts_are_requested() {
+        let forecast = || {
+            open_meteo(serde_json::json!({
+                "time": ["2024-06-15", "2024-06-16", "2024-06-17"],
+                "temperature_2m_max": [21.0, 22.0, 20.0],
+                "temperature_2m_min": [11.0, 12.0, 10.0],
+                "weather_code": [0, 51, 3],
+                "wind_speed_10m_max": [10.0, 10.0, 10.0],
+                "precipitation_sum": [0.0, 0.3, 0.04],
+            }))
+        };
+        let precipitation = |options: FormatOptions| {
+            format_open_meteo_forecast(forecast(), options)
+                .entries
+                .iter()
+                .map(|entry| {
+                    let line = entry.lines().find(|line| line.contains("Precipitation"));
+                    line.unwrap().to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            precipitation(FormatOptions::default()),
+            ["  Precipitation: none", "  Precipitation: 0.3 mm", "  Precipitation: none"]
+        );
+        let options = FormatOptions {
+            show_zero_precipitation: true,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            precipitation(options),
+            ["  Precipitation: 0.0 mm", "  Precipitation: 0.3 mm", "  Precipitation: 0.0 mm"]
+        );
+    }
+
+    #[test]
+    fn ocean_points_get_the_open_water_note() {
+        let daily = serde_json::json!({
+            "time": ["2024-06-15"],
+            "temperature_2m_max": [19.0],
+            "temperature_2m_min": [16.0],
+            "weather_code": [1],
+            "wind_speed_10m_max": [28.0],
+            "precipitation_sum": [0.0],
+        });
+        // A mid-Atlantic point, where Open-Meteo reports sea level exactly
+        let ocean: OpenMeteoResponse = serde_json::from_value(serde_json::json!({
+            "latitude": 35.0,
+            "longitude": -40.0,
+            "elevation": 0.0,
+            "timezone": "GMT",
+            "daily": daily,
+        }))
+        .unwrap();
+        assert!(ocean.is_open_water());
+        let header = format_open_meteo_forecast(ocean, FormatOptions::default()).header;
+        assert!(header.starts_with("Note: these coordinates appear to be over open water"));
+
+        let mut land = open_meteo(daily);
+        assert!(!land.is_open_water());
+        land.elevation = Some(38.0);
+        assert!(!land.is_open_water());
+        assert_eq!(open_water_note(&land), "");
+    }
+
+    #[test]
+    fn oneliner_is_a_single_sentence() {
+        let mut current = light_rain(Some(1));
+        current.current.temperature = Some(14.2);
+        current.current.wind_speed = Some(9.0);
+        current.current.weather_code = Some(2);
+
+        let line = format_oneliner(&current, Some(" Berlin\nMitte "), Units::Metric);
+        assert_eq!(line, "Berlin Mitte: 14°C, partly cloudy, light wind.");
+        assert!(!line.contains('\n'));
+        assert_eq!(line.matches('.').count(), 1);
+
+        current.current.temperature = None;
+        current.current.wind_speed = None;
+        current.current.weather_code = None;
+        assert_eq!(
+            format_oneliner(&current, None, Units::Metric),
+            "52.5200, 13.4100: conditions unavailable."
+        );
+    }
+
+    #[test]
+    fn hourly_lines_show_the_feels_like_temperature_when_known() {
+        let mut forecast = open_meteo(serde_json::json!({
+            "time": ["2024-06-15"],
+            "temperature_2m_max": [21.0],
+            "temperature_2m_min": [11.0],
+            "weather_code": [2],
+            "wind_speed_10m_max": [10.0],
+            "precipitation_sum": [0.0],
+        }));
+        forecast.hourly = Some(
+            serde_json::from_value(serde_json::json!({
+                "time": ["2024-06-15T08:00", "2024-06-15T09:00"],
+                "temperature_2m": [14.0, 15.5],
+                "apparent_temperature": [11.6, null],
+                "weather_code": [2, 2],
+            }))
+            .unwrap(),
+        );
+
+        let hourly = format_today_hourly(&forecast, FormatOptions::default()).unwrap();
+        assert_eq!(
+            hourly.lines().collect::<Vec<_>>(),
+            [
+                "Hourly for 2024-06-15:",
+                "  08:00  14.0°C (feels 11.6°C)  Partly cloudy",
+                "  09:00  15.5°C  Partly cloudy",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn compass_points_and_bearings_map_to_arrows() {
+        assert_eq!(cardinal_to_arrow("N"), Some('↑'));
+        assert_eq!(cardinal_to_arrow(" se "), Some('↘'));
+        assert_eq!(cardinal_to_arrow("W"), Some('←'));
+        // Half-way points sit exactly between two arrows and round clockwise
+        assert_eq!(cardinal_to_arrow("NNE"), Some('↗'));
+        assert_eq!(cardinal_to_arrow("NNW"), Some('↑'));
+        assert_eq!(cardinal_to_arrow("variable"), None);
+
+        assert_eq!(degrees_to_arrow(0.0), '↑');
+        assert_eq!(degrees_to_arrow(135.0), '↘');
+        assert_eq!(degrees_to_arrow(22.4), '↑');
+        assert_eq!(degrees_to_arrow(350.0), '↑');
+        assert_eq!(degrees_to_arrow(-90.0), '←');
+        assert_eq!(with_arrow("SE", true), "SE ↘");
+        assert_eq!(with_arrow("SE", false), "SE");
+    }
+
+    #[test]
+    fn local_time_applies_the_utc_offset() {
+        let now = "2024-06-15T12:37:00Z".parse::<DateTime<Utc>>().unwrap();
+        let local_time = |offset| format_local_time(offset, now, TimeFormat::TwentyFourHour);
+        assert_eq!(local_time(7200), "Local time: 14:37\n");
+        assert_eq!(local_time(-16_200), "Local time: 08:07\n");
+        // Across midnight only the time of day is shown
+        assert_eq!(local_time(43_200), "Local time: 00:37\n");
+        assert_eq!(
+            format_local_time(7200, now, TimeFormat::TwelveHour),
+            "Local time: 2:37 PM\n"
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! Weather forecasts and alerts from the National Weather Service and Open-Meteo.
+//!
+//! [`Weather`] serves them as MCP tools, and its public methods such as
+//! [`Weather::forecast`] can also be called directly from Rust without MCP.
+
+mod astro;
+mod breaker;
+mod chart;
+mod constants;
+mod disk_cache;
+mod formatters;
+mod models;
+mod retry;
+mod service;
+mod stats;
+#[cfg(test)]
+mod test_support;
+mod units;
+
+pub use formatters::FormattedForecast;
+pub use models::ForecastOptions;
+pub use service::{ForecastSummary, Weather};
@@ -1,30 +1,39 @@
-mod constants;
-mod formatters;
-mod models;
-mod service;
-
 use anyhow::Result;
 use rmcp::ServiceExt;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use service::Weather;
+use mcp_weather_server::Weather;
+
+/// Log directives used when `RUST_LOG` is unset: the server at info, dependencies
+/// such as reqwest and hyper at warn
+const DEFAULT_LOG_FILTER: &str = "warn,mcp_weather_server=info";
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "mcp_rust_weather=info".into()),
+                .unwrap_or_else(|_| DEFAULT_LOG_FILTER.into()),
         )
         .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
     tracing::info!("Starting MCP weather server");
 
-    let weather = Weather::new()?;
+    let weather = Weather::new()?.over_stdio();
     let server = weather.serve(rmcp::transport::stdio()).await?;
     server.waiting().await?;
 
     tracing::info!("Server shutdown complete");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_log_filter_parses() {
+        assert!(tracing_subscriber::EnvFilter::try_new(DEFAULT_LOG_FILTER).is_ok());
+    }
+}
@@ -1,35 +1,127 @@
+use chrono::{DateTime, FixedOffset};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+use crate::units::Units;
 
 // ============================================================================
 // Open-Meteo API Models
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct OpenMeteoResponse {
     pub latitude: f64,
     pub longitude: f64,
     pub timezone: String,
+    /// e.g. "CEST"; absent from some cached and hand-built responses
+    #[serde(default)]
+    pub timezone_abbreviation: Option<String>,
+    #[serde(default)]
+    pub utc_offset_seconds: Option<i64>,
+    /// Grid cell elevation in metres; exactly 0 over open water
+    #[serde(default)]
+    pub elevation: Option<f64>,
     pub daily: DailyData,
-    pub daily_units: DailyUnits,
+    #[serde(default)]
+    pub daily_units: Option<DailyUnits>,
+    /// Legacy current-conditions block, present when `current_weather=true` is requested
+    #[serde(default)]
+    pub current_weather: Option<CurrentWeather>,
+    /// Hourly series, present only when hourly variables are requested alongside daily
+    #[serde(default)]
+    pub hourly: Option<HourlyData>,
 }
 
-#[derive(Debug, Deserialize)]
+impl OpenMeteoResponse {
+    /// Returns the response's unit labels, or the defaults for `units` if they were omitted
+    pub fn units_or_default(&self, units: Units) -> DailyUnits {
+        self.daily_units
+            .clone()
+            .unwrap_or_else(|| DailyUnits::defaults(units))
+    }
+
+    /// Whether the coordinates look like open water.
+    ///
+    /// A heuristic: Open-Meteo reports an elevation of exactly 0 for sea cells, while
+    /// even low-lying land rarely lands on 0 exactly.
+    pub fn is_open_water(&self) -> bool {
+        self.elevation == Some(0.0)
+    }
+
+    /// Sorts the daily series by date and drops repeated dates, keeping the first.
+    ///
+    /// Open-Meteo can occasionally return dates out of order or twice, e.g. around
+    /// timezone changes; every daily vector is reordered the same way so they stay
+    /// aligned. Vectors that weren't requested stay empty.
+    pub fn normalize_daily(&mut self) {
+        let daily = &mut self.daily;
+        let mut order: Vec<usize> = (0..daily.time.len()).collect();
+        order.sort_by(|&a, &b| daily.time[a].cmp(&daily.time[b]));
+        order.dedup_by(|a, b| daily.time[*a] == daily.time[*b]);
+        if order.iter().copied().eq(0..daily.time.len()) {
+            return;
+        }
+
+        fn reorder<T: Clone>(values: &mut Vec<T>, order: &[usize]) {
+            *values = order.iter().filter_map(|&i| values.get(i).cloned()).collect();
+        }
+        reorder(&mut daily.time, &order);
+        reorder(&mut daily.temperature_max, &order);
+        reorder(&mut daily.temperature_min, &order);
+        reorder(&mut daily.weather_code, &order);
+        reorder(&mut daily.wind_speed_max, &order);
+        reorder(&mut daily.precipitation_sum, &order);
+        reorder(&mut daily.sunrise, &order);
+        reorder(&mut daily.sunset, &order);
+        reorder(&mut daily.daylight_duration, &order);
+        reorder(&mut daily.precipitation_probability_max, &order);
+        reorder(&mut daily.wind_direction_dominant, &order);
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct DailyData {
     pub time: Vec<String>,
     #[serde(rename = "temperature_2m_max")]
     pub temperature_max: Vec<f64>,
     #[serde(rename = "temperature_2m_min")]
     pub temperature_min: Vec<f64>,
-    #[serde(rename = "weather_code")]
+    #[serde(rename = "weather_code", deserialize_with = "weather_codes")]
     pub weather_code: Vec<i32>,
     #[serde(rename = "wind_speed_10m_max")]
     pub wind_speed_max: Vec<f64>,
     #[serde(rename = "precipitation_sum")]
     pub precipitation_sum: Vec<f64>,
+    /// Null during polar day or night
+    #[serde(default)]
+    pub sunrise: Vec<Option<String>>,
+    #[serde(default)]
+    pub sunset: Vec<Option<String>>,
+    /// Seconds of daylight; 0 during polar night and 86400 during polar day
+    #[serde(default)]
+    pub daylight_duration: Vec<Option<f64>>,
+    #[serde(default)]
+    pub precipitation_probability_max: Vec<Option<f64>>,
+    /// Degrees the wind mostly blows from
+    #[serde(rename = "wind_direction_10m_dominant", default)]
+    pub wind_direction_dominant: Vec<Option<f64>>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Open-Meteo's legacy `current_weather` block, which predates `current` and uses
+/// unsuffixed field names
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CurrentWeather {
+    pub temperature: f64,
+    pub windspeed: f64,
+    pub winddirection: f64,
+    #[serde(deserialize_with = "weather_code")]
+    pub weathercode: i32,
+    #[serde(default)]
+    pub is_day: Option<u8>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct DailyUnits {
     #[serde(rename = "temperature_2m_max")]
     pub temperature_max: String,
@@ -39,36 +131,410 @@ pub struct DailyUnits {
     pub precipitation_sum: String,
 }
 
+impl DailyUnits {
+    /// Unit labels Open-Meteo uses for the given unit system
+    pub fn defaults(units: Units) -> Self {
+        let (temperature, wind_speed, precipitation) = match units {
+            Units::Metric | Units::Kelvin => ("\u{00b0}C", "km/h", "mm"),
+            Units::Imperial => ("\u{00b0}F", "mp/h", "inch"),
+        };
+        Self {
+            temperature_max: temperature.to_string(),
+            wind_speed_max: wind_speed.to_string(),
+            precipitation_sum: precipitation.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct OpenMeteoHourlyResponse {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: String,
+    #[serde(default)]
+    pub utc_offset_seconds: i64,
+    pub hourly: HourlyData,
+    #[serde(default)]
+    pub hourly_units: HashMap<String, String>,
+}
+
+/// Hourly series; each field is only present when requested
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct HourlyData {
+    pub time: Vec<String>,
+    #[serde(default)]
+    pub precipitation: Vec<Option<f64>>,
+    #[serde(default)]
+    pub precipitation_probability: Vec<Option<f64>>,
+    #[serde(rename = "temperature_2m", default)]
+    pub temperature: Vec<Option<f64>>,
+    /// Felt temperature, combining air temperature with wind and humidity
+    #[serde(default)]
+    pub apparent_temperature: Vec<Option<f64>>,
+    #[serde(default, deserialize_with = "optional_weather_codes")]
+    pub weather_code: Vec<Option<i32>>,
+    #[serde(default)]
+    pub cloud_cover: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct OpenMeteoCurrentResponse {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub current: CurrentData,
+    #[serde(default)]
+    pub current_units: HashMap<String, String>,
+}
+
+/// Current conditions; each field is only present when requested
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CurrentData {
+    pub time: String,
+    pub precipitation: Option<f64>,
+    #[serde(default, deserialize_with = "optional_weather_code")]
+    pub weather_code: Option<i32>,
+    #[serde(rename = "temperature_2m")]
+    pub temperature: Option<f64>,
+    #[serde(rename = "wind_speed_10m")]
+    pub wind_speed: Option<f64>,
+    #[serde(rename = "wind_gusts_10m")]
+    pub wind_gusts: Option<f64>,
+    /// Degrees the wind blows from, clockwise from north
+    #[serde(rename = "wind_direction_10m")]
+    pub wind_direction: Option<f64>,
+    /// 1 during daylight, 0 at night
+    pub is_day: Option<i32>,
+}
+
+/// Daily snowfall totals from Open-Meteo
+#[derive(Debug, Deserialize)]
+pub struct SnowfallResponse {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub daily: SnowfallDaily,
+    #[serde(default)]
+    pub daily_units: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnowfallDaily {
+    pub time: Vec<String>,
+    /// Centimetres, or inches with imperial units
+    pub snowfall_sum: Vec<Option<f64>>,
+}
+
+/// Daily weather scored by `get_activity_forecast`
+#[derive(Debug, Deserialize)]
+pub struct ActivityResponse {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub daily: ActivityDaily,
+    #[serde(default)]
+    pub daily_units: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityDaily {
+    pub time: Vec<String>,
+    #[serde(rename = "temperature_2m_max")]
+    pub temperature_max: Vec<Option<f64>>,
+    pub precipitation_sum: Vec<Option<f64>>,
+    #[serde(rename = "wind_speed_10m_max")]
+    pub wind_speed_max: Vec<Option<f64>>,
+    pub snowfall_sum: Vec<Option<f64>>,
+    /// Seconds, whatever the units
+    pub sunshine_duration: Vec<Option<f64>>,
+}
+
+/// Hourly temperatures from every member of an Open-Meteo ensemble model
+#[derive(Debug, Deserialize)]
+pub struct EnsembleResponse {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub hourly: EnsembleHourly,
+    #[serde(default)]
+    pub hourly_units: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnsembleHourly {
+    pub time: Vec<String>,
+    /// One series per member: "temperature_2m" for the control run and
+    /// "temperature_2m_memberNN" for the perturbed runs
+    #[serde(flatten)]
+    pub members: HashMap<String, Vec<Option<f64>>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PollenResponse {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub current: Option<PollenCurrent>,
+    pub hourly: PollenHourly,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PollenCurrent {
+    pub alder_pollen: Option<f64>,
+    pub birch_pollen: Option<f64>,
+    pub grass_pollen: Option<f64>,
+    pub ragweed_pollen: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PollenHourly {
+    pub time: Vec<String>,
+    pub alder_pollen: Vec<Option<f64>>,
+    pub birch_pollen: Vec<Option<f64>>,
+    pub grass_pollen: Vec<Option<f64>>,
+    pub ragweed_pollen: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveResponse {
+    pub daily: ArchiveDaily,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ArchiveDaily {
+    pub time: Vec<String>,
+    #[serde(rename = "temperature_2m_max")]
+    pub temperature_max: Vec<Option<f64>>,
+    #[serde(rename = "temperature_2m_min", default)]
+    pub temperature_min: Vec<Option<f64>>,
+    #[serde(default)]
+    pub precipitation_sum: Vec<Option<f64>>,
+    #[serde(rename = "wind_speed_10m_max", default)]
+    pub wind_speed_max: Vec<Option<f64>>,
+}
+
+impl ArchiveDaily {
+    /// Appends the days of a later range, padding any variable it lacks with `None`
+    /// so every array stays aligned with `time`
+    pub fn append(&mut self, mut later: ArchiveDaily) {
+        let days = later.time.len();
+        for (values, more) in [
+            (&mut self.temperature_max, &mut later.temperature_max),
+            (&mut self.temperature_min, &mut later.temperature_min),
+            (&mut self.precipitation_sum, &mut later.precipitation_sum),
+            (&mut self.wind_speed_max, &mut later.wind_speed_max),
+        ] {
+            values.resize(self.time.len(), None);
+            more.resize(days, None);
+            values.append(more);
+        }
+        self.time.append(&mut later.time);
+    }
+}
+
+/// Daily forecast from a single weather model, used for model comparison
+#[derive(Debug, Deserialize)]
+pub struct ModelForecastResponse {
+    pub daily: ModelDaily,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelDaily {
+    pub time: Vec<String>,
+    #[serde(rename = "temperature_2m_max")]
+    pub temperature_max: Vec<Option<f64>>,
+    pub precipitation_sum: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeocodingResponse {
+    #[serde(default)]
+    pub results: Vec<GeocodingResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeocodingResult {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub admin1: Option<String>,
+    /// GeoNames feature code, e.g. "PPL" for a populated place or "PCLI" for a country
+    pub feature_code: Option<String>,
+}
+
+/// A Nominatim search result, used when the Open-Meteo geocoder fails
+#[derive(Debug, Deserialize)]
+pub struct NominatimPlace {
+    /// Coordinates come back as decimal strings
+    pub lat: String,
+    pub lon: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub display_name: String,
+    /// Kind of place, e.g. "city", "state", or "country"
+    #[serde(default)]
+    pub addresstype: Option<String>,
+    #[serde(default)]
+    pub address: Option<NominatimAddress>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NominatimAddress {
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+}
+
+impl NominatimPlace {
+    /// Converts to the Open-Meteo result shape, mapping broad place kinds to their
+    /// GeoNames feature codes. Returns `None` if the coordinates don't parse.
+    pub fn into_geocoding_result(self) -> Option<GeocodingResult> {
+        let feature_code = match self.addresstype.as_deref() {
+            Some("country") => Some("PCLI"),
+            Some("state") => Some("ADM1"),
+            Some("continent") => Some("CONT"),
+            Some("ocean") => Some("OCN"),
+            Some("sea") => Some("SEA"),
+            _ => None,
+        };
+        let address = self.address.unwrap_or(NominatimAddress {
+            country_code: None,
+            country: None,
+            state: None,
+        });
+        let name = self.name.filter(|name| !name.is_empty()).unwrap_or_else(|| {
+            let first = self.display_name.split(',').next().unwrap_or_default();
+            first.trim().to_string()
+        });
+        Some(GeocodingResult {
+            name,
+            latitude: self.lat.parse().ok()?,
+            longitude: self.lon.parse().ok()?,
+            country_code: address.country_code.map(|code| code.to_uppercase()),
+            country: address.country,
+            admin1: address.state,
+            feature_code: feature_code.map(str::to_string),
+        })
+    }
+}
+
 // ============================================================================
 // National Weather Service API Models
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct AlertResponse {
     pub features: Vec<AlertFeature>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct AlertFeature {
     pub properties: AlertProperties,
+    /// The alert's area; null for alerts issued by county or zone only
+    #[serde(default)]
+    pub geometry: Option<AlertGeometry>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A GeoJSON Polygon or MultiPolygon outlining an alert's area
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AlertGeometry {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Kept as raw JSON since the nesting depends on `kind`
+    #[serde(default)]
+    pub coordinates: serde_json::Value,
+}
+
+impl AlertGeometry {
+    /// The outer ring of each polygon as (latitude, longitude) vertices; empty for
+    /// other geometry types
+    pub fn outer_rings(&self) -> Vec<Vec<(f64, f64)>> {
+        // GeoJSON positions are [longitude, latitude]
+        let ring = |value: &serde_json::Value| -> Option<Vec<(f64, f64)>> {
+            value
+                .as_array()?
+                .iter()
+                .map(|position| Some((position.get(1)?.as_f64()?, position.get(0)?.as_f64()?)))
+                .collect()
+        };
+        let polygons = match self.kind.as_str() {
+            "Polygon" => vec![&self.coordinates],
+            "MultiPolygon" => self
+                .coordinates
+                .as_array()
+                .map(|polygons| polygons.iter().collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        polygons
+            .into_iter()
+            .filter_map(|polygon| ring(polygon.get(0)?))
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct AlertProperties {
+    pub id: String,
     pub event: String,
     pub headline: Option<String>,
     pub description: Option<String>,
+    /// Recommended actions, e.g. "Take shelter now in a basement"
+    #[serde(default)]
+    pub instruction: Option<String>,
     pub severity: String,
     #[serde(rename = "areaDesc")]
     pub area_desc: String,
+    /// "Alert", "Update", or "Cancel"
+    #[serde(rename = "messageType", default)]
+    pub message_type: Option<String>,
+    #[serde(default)]
+    pub parameters: Option<HashMap<String, Vec<String>>>,
+    #[serde(default)]
+    pub geocode: Option<AlertGeocode>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Codes for the counties and zones an alert covers
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AlertGeocode {
+    /// Six-digit SAME codes: a leading 0 followed by the county FIPS code
+    #[serde(rename = "SAME", default)]
+    pub same: Vec<String>,
+    /// NWS county ("CAC037") and forecast zone ("CAZ041") codes
+    #[serde(rename = "UGC", default)]
+    pub ugc: Vec<String>,
+}
+
+impl AlertProperties {
+    /// Marker for updated or cancelled alerts, or `None` for original issuances
+    pub fn message_marker(&self) -> Option<&'static str> {
+        match self.message_type.as_deref() {
+            Some(kind) if kind.eq_ignore_ascii_case("update") => Some("UPDATE"),
+            Some(kind) if kind.eq_ignore_ascii_case("cancel") => Some("CANCEL"),
+            _ => None,
+        }
+    }
+
+    pub fn is_cancel(&self) -> bool {
+        self.message_marker() == Some("CANCEL")
+    }
+
+    /// Ranks the CAP severity from 4 for "Extreme" down to 0 for "Unknown" or
+    /// anything unrecognized
+    pub fn severity_rank(&self) -> u8 {
+        match self.severity.to_ascii_lowercase().as_str() {
+            "extreme" => 4,
+            "severe" => 3,
+            "moderate" => 2,
+            "minor" => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PointsResponse {
     pub properties: PointsProperties,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct PointsProperties {
     #[serde(rename = "gridId")]
     pub grid_id: String,
@@ -78,22 +544,29 @@ pub struct PointsProperties {
     pub grid_y: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ForecastResponse {
     pub properties: ForecastProperties,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ForecastProperties {
     pub periods: Vec<ForecastPeriod>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ForecastPeriod {
     pub name: String,
+    #[serde(rename = "startTime")]
+    pub start_time: String,
+    #[serde(rename = "endTime")]
+    pub end_time: String,
+    #[serde(rename = "isDaytime")]
+    pub is_daytime: bool,
     pub temperature: i32,
-    #[serde(rename = "temperatureUnit")]
-    pub temperature_unit: String,
+    /// Occasionally missing, null, or empty; use `temperature_unit()` to read it
+    #[serde(rename = "temperatureUnit", default)]
+    pub temperature_unit: Option<String>,
     #[serde(rename = "windSpeed")]
     pub wind_speed: String,
     #[serde(rename = "windDirection")]
@@ -102,6 +575,73 @@ pub struct ForecastPeriod {
     pub short_forecast: String,
     #[serde(rename = "detailedForecast")]
     pub detailed_forecast: String,
+    #[serde(rename = "relativeHumidity", default)]
+    pub relative_humidity: Option<QuantitativeValue>,
+    /// "rising" or "falling" when the temperature moves against the usual daily
+    /// cycle during the period; usually null
+    #[serde(rename = "temperatureTrend", default)]
+    pub temperature_trend: Option<String>,
+}
+
+impl ForecastPeriod {
+    /// Parses the period's start time, which NWS gives in local time with an offset
+    pub fn start(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.start_time).ok()
+    }
+
+    /// The temperature unit, defaulting to "F" (NWS always reports Fahrenheit) when
+    /// the field is missing or empty
+    pub fn temperature_unit(&self) -> &str {
+        self.temperature_unit
+            .as_deref()
+            .map(str::trim)
+            .filter(|unit| !unit.is_empty())
+            .unwrap_or("F")
+    }
+
+    /// Parses the period's end time
+    pub fn end(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.end_time).ok()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct QuantitativeValue {
+    pub value: Option<f64>,
+    /// WMO unit such as "wmoUnit:degC"; absent on some forecast fields
+    #[serde(rename = "unitCode", default)]
+    pub unit_code: Option<String>,
+}
+
+/// Observation stations for a gridpoint, nearest first
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StationsResponse {
+    pub features: Vec<StationFeature>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StationFeature {
+    pub properties: StationProperties,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct StationProperties {
+    #[serde(rename = "stationIdentifier")]
+    pub station_identifier: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ObservationResponse {
+    pub properties: ObservationProperties,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ObservationProperties {
+    pub timestamp: String,
+    #[serde(rename = "textDescription", default)]
+    pub text_description: Option<String>,
+    pub temperature: QuantitativeValue,
 }
 
 // ============================================================================
@@ -111,10 +651,461 @@ pub struct ForecastPeriod {
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct GetAlertsRequest {
     pub state: String,
+    /// Emit one line per alert (event, area, severity) without descriptions
+    pub headlines_only: Option<bool>,
+    /// Only include alerts covering this county: a 5-digit FIPS, 6-digit SAME, or UGC code
+    pub county: Option<String>,
+    /// Include alerts whose message type is "Cancel" (default false)
+    pub include_cancelled: Option<bool>,
+    /// Only include these event types, case-insensitive, e.g. ["Tornado Warning"]
+    pub event_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetAlertChangesRequest {
+    pub state: String,
+    /// Alert IDs returned by a previous call
+    pub seen_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct WatchAlertsRequest {
+    pub state: String,
+    /// Seconds between polls, 30-900 (default 60)
+    pub interval_seconds: Option<u64>,
+    /// How long to watch in minutes, 1-60 (default 15)
+    pub duration_minutes: Option<u64>,
+}
+
+// Open-Meteo documents weather codes as integers but has been seen sending them as
+// floats such as 3.0, so they are read as numbers of either kind and truncated
+
+fn weather_code<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    Ok(f64::deserialize(deserializer)? as i32)
+}
+
+fn optional_weather_code<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<i32>, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.map(|code| code as i32))
+}
+
+fn weather_codes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<i32>, D::Error> {
+    let codes = Vec::<f64>::deserialize(deserializer)?;
+    Ok(codes.into_iter().map(|code| code as i32).collect())
+}
+
+fn optional_weather_codes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Option<i32>>, D::Error> {
+    let codes = Vec::<Option<f64>>::deserialize(deserializer)?;
+    Ok(codes.into_iter().map(|code| code.map(|code| code as i32)).collect())
+}
+
+/// Deserializes a coordinate from a number or a string, tolerating surrounding
+/// whitespace and quotes and a comma decimal separator, e.g. "52,52" or "\"13.41\""
+fn lenient_coordinate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(f64),
+        String(String),
+    }
+
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(value) => Ok(value),
+        NumberOrString::String(raw) => raw
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .trim()
+            .replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| {
+                D::Error::custom(format!(
+                    "invalid coordinate '{}': expected a number such as 52.52",
+                    raw
+                ))
+            }),
+    }
+}
+
+/// Schema for `lenient_coordinate` fields, which accept a number or a string
+/// holding one
+fn lenient_coordinate_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": ["number", "string"],
+        "examples": [52.52, "52,52"]
+    })
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetActivityForecastRequest {
+    #[serde(deserialize_with = "lenient_coordinate")]
+    #[schemars(schema_with = "lenient_coordinate_schema")]
+    pub latitude: f64,
+    #[serde(deserialize_with = "lenient_coordinate")]
+    #[schemars(schema_with = "lenient_coordinate_schema")]
+    pub longitude: f64,
+    /// One of "running", "cycling", "beach", "skiing", or "gardening"
+    pub activity: String,
+    #[serde(flatten)]
+    pub options: ForecastOptions,
 }
 
+/// Just a location, for tools that take no forecast options
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct LocationRequest {
+    #[serde(deserialize_with = "lenient_coordinate")]
+    #[schemars(schema_with = "lenient_coordinate_schema")]
+    pub latitude: f64,
+    #[serde(deserialize_with = "lenient_coordinate")]
+    #[schemars(schema_with = "lenient_coordinate_schema")]
+    pub longitude: f64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
 pub struct GetForecastRequest {
+    #[serde(deserialize_with = "lenient_coordinate")]
+    #[schemars(schema_with = "lenient_coordinate_schema")]
+    pub latitude: f64,
+    #[serde(deserialize_with = "lenient_coordinate")]
+    #[schemars(schema_with = "lenient_coordinate_schema")]
+    pub longitude: f64,
+    #[serde(flatten)]
+    pub options: ForecastOptions,
+}
+
+/// Forecast options, shared by the `get_forecast` tool and `Weather::forecast`
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct ForecastOptions {
+    /// Output style: "text" (default), "narrative" prose, "arrays" (JSON for charting),
+    /// or "geojson" (a GeoJSON Feature for mapping tools)
+    pub format: Option<String>,
+    /// Show an arrow next to each wind direction, e.g. "NW ↖"
+    pub arrows: Option<bool>,
+    /// Merge NWS day/night periods into one entry per day (US only)
+    pub merged: Option<bool>,
+    /// Maximum number of NWS half-day periods to show (14 periods is about 7 days)
+    pub periods: Option<usize>,
+    /// Include active NWS alerts for the point above the forecast (US only)
+    pub include_alerts: Option<bool>,
+    /// Date display for Open-Meteo forecasts: "iso" (default), "short", or "weekday"
+    pub date_format: Option<String>,
+    /// Return each forecast day (or NWS period) as a separate content block
+    pub split_days: Option<bool>,
+    /// Mark the day with the most severe adverse weather (Open-Meteo only)
+    pub highlight_worst: Option<bool>,
+    /// Show "none" instead of "0.0 mm" for dry days (default true; Open-Meteo only)
+    pub hide_zero_precip: Option<bool>,
+    /// Unit system for Open-Meteo forecasts: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+    /// Wind display for Open-Meteo forecasts: "exact" (default) or "beaufort"
+    pub wind_scale: Option<String>,
+    /// Clock style for times of day: "24h" (default) or "12h"
+    pub time_format: Option<String>,
+    /// Include morning and evening golden-hour windows (Open-Meteo only)
+    pub show_golden_hour: Option<bool>,
+    /// Truncate the output to at most this many characters on a whole-day boundary
+    pub max_chars: Option<usize>,
+    /// Show the current conditions above the forecast, from Open-Meteo's legacy
+    /// `current_weather` block (Open-Meteo only)
+    pub include_current: Option<bool>,
+    /// Append an hourly breakdown of the first day (Open-Meteo only)
+    pub include_today_hourly: Option<bool>,
+    /// Note when a briefly more severe condition, such as a thunderstorm, is hidden by
+    /// the daily summary (Open-Meteo only)
+    pub include_hourly_worst: Option<bool>,
+    /// Return the temperature trend as a PNG chart with text sparklines instead of the forecast
+    pub as_chart: Option<bool>,
+    /// Append a data source attribution footer (default true)
+    pub show_attribution: Option<bool>,
+    /// On an NWS error, serve the last forecast fetched for the point, marked as
+    /// possibly stale (default true)
+    pub serve_stale_on_error: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetRelativeForecastRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// One of "today", "tomorrow", "this weekend", or "next 3 days"
+    pub when: String,
+    /// Unit system: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+}
+
+/// The JSON `get_forecast` returns with `format: "arrays"`
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ForecastArrays {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    pub units: ForecastArrayUnits,
+    pub dates: Vec<String>,
+    pub highs: Vec<f64>,
+    pub lows: Vec<f64>,
+    pub precipitation: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ForecastArrayUnits {
+    pub temperature: String,
+    pub precipitation: String,
+}
+
+impl ForecastArrayUnits {
+    /// The unit system these labels came from
+    pub fn units(&self) -> Units {
+        match self.temperature.as_str() {
+            "K" => Units::Kelvin,
+            unit if unit.ends_with('F') => Units::Imperial,
+            _ => Units::Metric,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DiffForecastRequest {
+    /// JSON returned earlier by get_forecast with format "arrays", unchanged
+    pub previous: ForecastArrays,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CheckNiceWeatherRequest {
     pub latitude: f64,
     pub longitude: f64,
+    /// Lowest acceptable temperature (default 18°C or 64°F)
+    pub min_temperature: Option<f64>,
+    /// Highest acceptable temperature (default 28°C or 82°F)
+    pub max_temperature: Option<f64>,
+    /// Highest acceptable chance of precipitation in percent (default 20)
+    pub max_precipitation_probability: Option<f64>,
+    /// Highest acceptable wind speed (default 25 km/h or 16 mph)
+    pub max_wind_speed: Option<f64>,
+    /// Unit system for thresholds and output: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetOnelinerRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Place name to lead the line with, e.g. "Berlin" (default: the coordinates)
+    pub name: Option<String>,
+    /// Unit system: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetWindConditionsRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// "kitesurfing", "windsurfing", or "sailing"
+    pub sport: String,
+    /// Wind speed unit: "kn" (default), "kmh", "mph", or "ms"
+    pub wind_unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct NamedLocation {
+    /// Display name, e.g. "Lisbon"
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RankLocationsRequest {
+    /// Locations to compare, at most 10
+    pub locations: Vec<NamedLocation>,
+    /// First day of the window as YYYY-MM-DD, in each location's local date (default today)
+    pub start_date: Option<String>,
+    /// Last day of the window, inclusive (default start_date); within the next 7 days
+    pub end_date: Option<String>,
+    /// Unit system: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+    /// Rank coolest first instead of warmest first
+    pub coolest_first: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetEventScoreRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Event date as YYYY-MM-DD, today or later
+    pub date: String,
+    /// Unit system: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetDegreeDaysRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// First day as YYYY-MM-DD, in the location's local date
+    pub start_date: String,
+    /// Last day, inclusive; at most 366 days after start_date
+    pub end_date: String,
+    /// Base temperature in the requested units (default 18°C, 65°F, or 291.15 K)
+    pub base_temperature: Option<f64>,
+    /// Unit system: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetSunTimesRequest {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Date as YYYY-MM-DD; any date, not limited to the forecast window
+    pub date: String,
+    /// Hours from UTC for displaying local times, e.g. -5 or 5.5 (default 0, UTC)
+    pub utc_offset: Option<f64>,
+    /// Clock style: "24h" (default) or "12h"
+    pub time_format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetAreaForecastRequest {
+    pub min_latitude: f64,
+    pub max_latitude: f64,
+    pub min_longitude: f64,
+    pub max_longitude: f64,
+    /// Grid points per side, 2 to 5 (default 3, for 9 points)
+    pub resolution: Option<usize>,
+    /// Unit system: "metric" (default), "imperial", or "kelvin"
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetTravelImpactRequest {
+    pub origin_latitude: f64,
+    pub origin_longitude: f64,
+    pub destination_latitude: f64,
+    pub destination_longitude: f64,
+    /// Departure time in UTC as YYYY-MM-DDTHH:MM, within the next 7 days
+    pub departure_time: String,
+    /// Journey length in hours; estimated from the straight-line distance at 80 km/h if omitted
+    pub duration_hours: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetForecastByZipRequest {
+    /// Five-digit US ZIP code
+    pub zip: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetLandmarkForecastRequest {
+    /// Landmark or place name, e.g. "Eiffel Tower" or "Grand Canyon"
+    pub landmark: String,
+}
+
+/// Upstream endpoints that `debug_fetch` is allowed to query and whose schemas
+/// `get_response_schema` describes
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugUrlKind {
+    NwsPoints,
+    NwsForecast,
+    OpenMeteo,
+    Alerts,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GetResponseSchemaRequest {
+    pub url_kind: DebugUrlKind,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DebugFetchRequest {
+    pub url_kind: DebugUrlKind,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub state: Option<String>,
+    /// Also return the JSON schema the server expects for this endpoint
+    pub include_schema: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forecast_request(latitude: serde_json::Value) -> Result<GetForecastRequest, String> {
+        serde_json::from_value(serde_json::json!({ "latitude": latitude, "longitude": 13.41 }))
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn coordinates_accept_comma_decimals_and_quoted_numbers() {
+        assert_eq!(forecast_request("52,52".into()).unwrap().latitude, 52.52);
+        assert_eq!(forecast_request("\"13.41\"".into()).unwrap().latitude, 13.41);
+        assert_eq!(forecast_request(" 52.52 ".into()).unwrap().latitude, 52.52);
+        assert_eq!(forecast_request(52.52.into()).unwrap().latitude, 52.52);
+    }
+
+    #[test]
+    fn coordinates_reject_text() {
+        let error = forecast_request("not a number".into()).unwrap_err();
+        assert!(error.contains("invalid coordinate 'not a number'"), "{}", error);
+    }
+
+    #[test]
+    fn coordinate_schema_allows_strings() {
+        let schema = serde_json::to_value(schemars::schema_for!(GetForecastRequest)).unwrap();
+        for field in ["latitude", "longitude"] {
+            let types = &schema["properties"][field]["type"];
+            assert_eq!(types, &serde_json::json!(["number", "string"]), "{}", field);
+        }
+    }
+
+    #[test]
+    fn daily_series_are_sorted_and_deduplicated_together() {
+        let mut forecast: OpenMeteoResponse = serde_json::from_value(serde_json::json!({
+            "latitude": 52.52,
+            "longitude": 13.41,
+            "timezone": "Europe/Berlin",
+            "daily": {
+                "time": ["2024-06-17", "2024-06-15", "2024-06-16", "2024-06-15"],
+                "temperature_2m_max": [25.0, 20.0, 22.0, 21.0],
+                "temperature_2m_min": [12.0, 10.0, 11.0, 10.5],
+                "weather_code": [61, 0, 3, 1],
+                "wind_speed_10m_max": [20.0, 10.0, 12.0, 11.0],
+                "precipitation_sum": [5.2, 0.0, 0.1, 0.0]
+            }
+        }))
+        .unwrap();
+        forecast.normalize_daily();
+
+        let daily = &forecast.daily;
+        assert_eq!(daily.time, ["2024-06-15", "2024-06-16", "2024-06-17"]);
+        assert_eq!(daily.temperature_max, [20.0, 22.0, 25.0]);
+        assert_eq!(daily.weather_code, [0, 3, 61]);
+        assert_eq!(daily.precipitation_sum, [0.0, 0.1, 5.2]);
+        // Variables that weren't requested stay empty
+        assert!(daily.sunrise.is_empty());
+    }
+
+    #[test]
+    fn weather_codes_accept_floats() {
+        let daily: DailyData = serde_json::from_value(serde_json::json!({
+            "time": ["2024-06-15", "2024-06-16", "2024-06-17"],
+            "temperature_2m_max": [20.0, 22.0, 25.0],
+            "temperature_2m_min": [10.0, 11.0, 12.0],
+            "weather_code": [0.0, 3.0, 61.0],
+            "wind_speed_10m_max": [10.0, 12.0, 20.0],
+            "precipitation_sum": [0.0, 0.0, 5.2],
+        }))
+        .unwrap();
+        assert_eq!(daily.weather_code, [0, 3, 61]);
+
+        let hourly: HourlyData = serde_json::from_value(serde_json::json!({
+            "time": ["2024-06-15T00:00", "2024-06-15T01:00"],
+            "weather_code": [2.0, null],
+        }))
+        .unwrap();
+        assert_eq!(hourly.weather_code, [Some(2), None]);
+    }
 }
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Retries of a rate-limited request before its 429 is returned
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Wait before the first retry when the upstream gives no `Retry-After`; each
+/// further retry doubles it
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+/// Longest `Retry-After` wait honored before retrying a rate-limited request
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Upstream requests, retries included, a batch tool may make per item it fans out to
+pub const BATCH_REQUESTS_PER_ITEM: u32 = 2;
+
+/// Requests a batch tool may have in flight at once
+pub const BATCH_CONCURRENCY: usize = 8;
+
+/// A cap on the upstream requests made for one batch tool call.
+///
+/// Every sub-request draws on the same budget, so retries against a struggling
+/// upstream can't multiply the batch's load: once the budget is spent, further
+/// attempts fail instead of reaching the upstream.
+pub struct RequestBudget {
+    /// Limits how many of the batch's requests are in flight at once
+    in_flight: Semaphore,
+    /// Requests the batch may still make
+    remaining: AtomicU32,
+}
+
+impl RequestBudget {
+    /// A budget for a batch fanning out to `items` requests
+    pub fn for_batch(items: usize) -> Self {
+        Self {
+            in_flight: Semaphore::new(BATCH_CONCURRENCY),
+            remaining: AtomicU32::new(BATCH_REQUESTS_PER_ITEM.saturating_mul(items as u32)),
+        }
+    }
+
+    /// Waits for an in-flight slot and takes one request from the budget, or
+    /// returns `None` once it is spent. The slot is freed when the permit drops.
+    pub async fn spend(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = self.in_flight.acquire().await.ok()?;
+        self.remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .ok()?;
+        Some(permit)
+    }
+}
+
+/// Waits between retries.
+///
+/// Requests go through this rather than calling `tokio::time::sleep` directly, so
+/// tests can record the delays instead of waiting them out.
+pub trait Sleeper: Send + Sync {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// Sleeps on the tokio timer
+pub struct TokioSleeper;
+
+impl Sleeper for TokioSleeper {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        tokio::time::sleep(duration).boxed()
+    }
+}
+
+/// The exponential backoff before retry number `retry`, counting from 0: 1s, 2s, 4s
+pub fn backoff_delay(retry: u32) -> Duration {
+    BACKOFF_BASE * 2u32.saturating_pow(retry)
+}
+
+/// Parses a `Retry-After` header, either delay seconds or an HTTP date, into the
+/// time left to wait from `now`, clamped to `MAX_RETRY_AFTER`; dates in the past
+/// mean no wait
+pub fn retry_after_delay(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    let seconds = match value.parse::<u64>() {
+        Ok(seconds) => seconds,
+        // HTTP dates are the RFC 2822 subset "Sun, 06 Nov 1994 08:49:37 GMT"
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?;
+            (date.with_timezone(&Utc) - now).num_seconds().max(0) as u64
+        }
+    };
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_from_one_second() {
+        let delays: Vec<Duration> = (0..MAX_RATE_LIMIT_RETRIES).map(backoff_delay).collect();
+        assert_eq!(delays, [1, 2, 4].map(Duration::from_secs));
+    }
+
+    #[tokio::test]
+    async fn budget_is_shared_until_spent() {
+        let budget = RequestBudget::for_batch(2);
+        for _ in 0..2 * BATCH_REQUESTS_PER_ITEM {
+            assert!(budget.spend().await.is_some());
+        }
+        assert!(budget.spend().await.is_none());
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc2822("Sun, 06 Nov 1994 08:49:37 GMT").unwrap().into()
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        assert_eq!(retry_after_delay("12", now()), Some(Duration::from_secs(12)));
+        assert_eq!(retry_after_delay(" 0 ", now()), Some(Duration::ZERO));
+        let date = "Sun, 06 Nov 1994 08:49:57 GMT";
+        assert_eq!(retry_after_delay(date, now()), Some(Duration::from_secs(20)));
+        assert_eq!(retry_after_delay("soon", now()), None);
+    }
+
+    #[test]
+    fn retry_after_is_clamped() {
+        let past = "Sun, 06 Nov 1994 08:00:00 GMT";
+        assert_eq!(retry_after_delay(past, now()), Some(Duration::ZERO));
+        assert_eq!(retry_after_delay("3600", now()), Some(MAX_RETRY_AFTER));
+        let far = "Mon, 07 Nov 1994 08:49:37 GMT";
+        assert_eq!(retry_after_delay(far, now()), Some(MAX_RETRY_AFTER));
+    }
+}
@@ -1,177 +1,1940 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Timelike, Utc, Weekday};
+use futures::future::{BoxFuture, FutureExt, Shared};
 use reqwest::Client;
 use rmcp::{
     handler::server::{wrapper::Parameters, ServerHandler, tool::ToolRouter},
-    model::{CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo},
+    model::{
+        CallToolResult, Content, ErrorCode, Implementation, Meta, ProgressNotificationParam,
+        ProtocolVersion, ServerCapabilities, ServerInfo,
+    },
     tool, tool_handler, tool_router,
-    ErrorData as McpError,
+    ErrorData as McpError, Peer, RoleServer,
 };
+use schemars::schema_for;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
 
-use crate::constants::{NWS_API_BASE, OPEN_METEO_API_BASE, USER_AGENT};
-use crate::formatters::{format_alerts, format_forecast, format_open_meteo_forecast};
+use crate::astro::{moon_illumination, sun_times};
+use crate::breaker::CircuitBreaker;
+use crate::chart::{base64_encode, line_chart_png, sparkline};
+use crate::constants::{
+    CACHE_DIR_ENV_VAR, DEBUG_ENV_VAR, DEFAULT_UNITS_ENV_VAR, ENABLED_TOOLS_ENV_VAR,
+    HTTP_TIMEOUT_ENV_VAR, MAX_ARCHIVE_DAYS_ENV_VAR, MAX_BATCH_LOCATIONS, MAX_FORECAST_DAYS_ENV_VAR,
+    NOMINATIM_API_BASE, NOMINATIM_BASE_ENV_VAR, NWS_API_BASE, NWS_ATTRIBUTION, NWS_BASE_ENV_VAR,
+    OPEN_METEO_AIR_QUALITY_API_BASE, OPEN_METEO_AIR_QUALITY_BASE_ENV_VAR, OPEN_METEO_API_BASE,
+    OPEN_METEO_ARCHIVE_API_BASE, OPEN_METEO_ARCHIVE_BASE_ENV_VAR, OPEN_METEO_ATTRIBUTION,
+    OPEN_METEO_BASE_ENV_VAR, OPEN_METEO_ENSEMBLE_API_BASE, OPEN_METEO_ENSEMBLE_BASE_ENV_VAR,
+    OPEN_METEO_GEOCODING_API_BASE, OPEN_METEO_GEOCODING_BASE_ENV_VAR, OPEN_METEO_MAX_FORECAST_DAYS,
+    POOL_IDLE_TIMEOUT_ENV_VAR, POOL_MAX_IDLE_ENV_VAR, USER_AGENT, US_STATES,
+};
+use crate::disk_cache::DiskCache;
+use crate::retry::{
+    backoff_delay, retry_after_delay, RequestBudget, Sleeper, TokioSleeper,
+    MAX_RATE_LIMIT_RETRIES,
+};
+use crate::formatters::{
+    diff_forecast_arrays, format_activity_forecast, format_alert_changes, format_alert_headlines,
+    format_alert_watch, format_alerts, format_area_forecast, format_coords, format_degree_days,
+    format_event_score, format_forecast, format_forecast_accuracy, format_forecast_diff,
+    format_forecast_merged, format_forecast_narrative, format_forecast_quartiles,
+    format_is_it_raining, format_location_ranking, format_model_agreement, format_nice_weather,
+    format_oneliner, format_open_meteo_forecast, format_open_meteo_narrative, format_place,
+    format_pollen, format_rain_timeline, format_safety_briefing, format_snow_outlook,
+    format_stargazing, format_states, format_sun_times, format_temperature,
+    format_temperature_anomaly, format_temperature_profile, format_today_hourly,
+    format_travel_impact, format_weather_code_legend, format_weather_trend, format_wind_conditions,
+    format_yesterday_comparison, open_meteo_arrays, open_meteo_geojson, weather_code_severity,
+    AreaSummary, DateFormat, DayQuartiles, EventConditions, FormatOptions, FormattedForecast,
+    ModelOutlook, NiceWeather, OutputFormat, RankedLocation, TemperatureAnomaly, TimeFormat,
+    TravelLeg,
+};
 use crate::models::{
-    AlertResponse, ForecastResponse, GetAlertsRequest, GetForecastRequest,
-    OpenMeteoResponse, PointsResponse,
+    ActivityResponse, AlertFeature, AlertResponse, ArchiveDaily, ArchiveResponse,
+    CheckNiceWeatherRequest, DailyUnits, DebugFetchRequest, DebugUrlKind, DiffForecastRequest,
+    EnsembleResponse, ForecastArrays, ForecastOptions, ForecastResponse, GeocodingResponse,
+    GeocodingResult, GetActivityForecastRequest, GetAlertChangesRequest, GetAlertsRequest,
+    GetAreaForecastRequest, GetDegreeDaysRequest, GetEventScoreRequest, GetForecastByZipRequest,
+    GetForecastRequest, GetLandmarkForecastRequest, GetOnelinerRequest, GetRelativeForecastRequest,
+    GetResponseSchemaRequest, GetSunTimesRequest, GetTravelImpactRequest, GetWindConditionsRequest,
+    LocationRequest, ModelForecastResponse, NominatimPlace, ObservationResponse,
+    OpenMeteoCurrentResponse, OpenMeteoHourlyResponse, OpenMeteoResponse, PointsProperties,
+    PointsResponse, PollenResponse, RankLocationsRequest, SnowfallResponse, StationsResponse,
+    WatchAlertsRequest,
+};
+use crate::stats::{
+    agreement_score, anomaly, daily_extremes, degree_days, event_score, great_circle_km,
+    longest_run_at_most, mean, point_in_ring, precipitation_blocks, quartiles, ring_center, spread,
+    stargazing_rating, summarize, travel_impact, Activity, DegreeDays, EventScore, WindSport,
 };
+use crate::units::{fahrenheit_to_celsius, mph_to_kmh, SpeedUnit, Units, WindScale};
+
+/// GeoNames feature code prefixes too broad to forecast as a single place:
+/// countries, first-level regions, continents, and bodies of water
+const NON_PLACE_FEATURE_CODES: &[&str] = &["PCL", "ADM1", "CONT", "RGN", "OCN", "SEA"];
+
+/// Open-Meteo daily variables for each day's high and low
+const DAILY_TEMPERATURE_FIELDS: &str = "temperature_2m_max,temperature_2m_min";
+
+/// Open-Meteo daily variables scored by `get_event_weather_score`
+const EVENT_DAILY_FIELDS: &str = "temperature_2m_max,precipitation_sum,wind_speed_10m_max";
+
+/// Open-Meteo daily variables scored by `get_activity_forecast`
+const ACTIVITY_DAILY_FIELDS: &str =
+    "temperature_2m_max,precipitation_sum,wind_speed_10m_max,snowfall_sum,sunshine_duration";
+
+/// Past years averaged for an event date beyond the forecast
+const EVENT_CLIMATOLOGY_YEARS: i32 = 10;
+
+/// Days either side of the event date included from each past year
+const EVENT_CLIMATOLOGY_WINDOW_DAYS: i64 = 3;
+
+/// Longest date range `get_degree_days` accepts, in days
+const MAX_DEGREE_DAY_RANGE: i64 = 366;
+
+/// How far back the Open-Meteo forecast API serves past days; older ranges need
+/// the archive
+const FORECAST_PAST_DAYS: i64 = 92;
+
+/// Longest range fetched from the Open-Meteo archive in one request, in days; short
+/// enough that a retried chunk repeats only about a season
+const ARCHIVE_CHUNK_DAYS: i64 = 90;
+
+/// Days the Open-Meteo archive lags behind today
+const ARCHIVE_DELAY_DAYS: i64 = 5;
+
+/// Bounds and default for the `watch_alerts` polling interval, in seconds
+const WATCH_INTERVAL_SECONDS: (u64, u64, u64) = (30, 900, 60);
+
+/// Bounds and default for how long `watch_alerts` runs, in minutes
+const WATCH_DURATION_MINUTES: (u64, u64, u64) = (1, 60, 15);
+
+/// NWS forecast periods, about two days, checked for hazards by `get_safety_briefing`
+const SAFETY_BRIEFING_PERIODS: usize = 4;
+
+/// Most NWS forecasts kept for serving stale when a fresh fetch fails
+const STALE_FORECAST_CAPACITY: usize = 256;
+
+/// Rejects an empty location list or one longer than `MAX_BATCH_LOCATIONS`, with the
+/// same message for every tool that takes several locations
+fn check_location_count(count: usize) -> Result<(), McpError> {
+    if count == 0 {
+        return Err(McpError::invalid_params("Provide at least one location.".to_string(), None));
+    }
+    if count > MAX_BATCH_LOCATIONS {
+        return Err(McpError::invalid_params(
+            format!("At most {} locations per request; got {}.", MAX_BATCH_LOCATIONS, count),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns true if the environment variable is set to "1" or "true"
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Reads the deployment's default unit system from the environment, falling back
+/// to metric when unset or unrecognized
+fn env_units(name: &str) -> Units {
+    let Ok(value) = std::env::var(name) else {
+        return Units::default();
+    };
+    Units::parse(value.trim()).unwrap_or_else(|| {
+        tracing::warn!("Ignoring invalid {}='{}'", name, value);
+        Units::default()
+    })
+}
+
+/// Reads a day-count limit from the environment, ignoring values that aren't a
+/// positive whole number
+fn env_days(name: &str) -> Option<u32> {
+    let value = std::env::var(name).ok()?;
+    match value.trim().parse() {
+        Ok(days) if days > 0 => Some(days),
+        _ => {
+            tracing::warn!("Ignoring invalid {}='{}'", name, value);
+            None
+        }
+    }
+}
+
+/// Reads a non-negative whole number from the environment, ignoring invalid values
+fn env_count(name: &str) -> Option<u64> {
+    let value = std::env::var(name).ok()?;
+    value
+        .trim()
+        .parse()
+        .inspect_err(|_| tracing::warn!("Ignoring invalid {}='{}'", name, value))
+        .ok()
+}
+
+/// Reads a base URL override from the environment, falling back to the default
+fn base_url(env_var: &str, default: &str) -> String {
+    let value = std::env::var(env_var).ok();
+    let value = value.as_deref().filter(|value| !value.trim().is_empty());
+    normalize_base_url(value.unwrap_or(default))
+}
+
+/// Trims whitespace and any trailing slash from a base URL so paths can be appended
+/// with a single "/"
+fn normalize_base_url(value: &str) -> String {
+    value.trim().trim_end_matches('/').to_string()
+}
+
+/// The host and port of a URL, which identify an upstream for its circuit breaker
+fn upstream_origin(url: &reqwest::Url) -> Option<String> {
+    Some(format!("{}:{}", url.host_str()?, url.port_or_known_default()?))
+}
+
+/// Rounds a coordinate to 4 decimal places, the precision NWS accepts
+fn round_coordinate(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// The disk cache key for a request URL: the URL with its `latitude` and `longitude`
+/// parameters rounded by `round_coordinate`, so nearby requests for the same point
+/// and parameters share an entry
+fn cache_key(url: &str) -> String {
+    let Ok(mut key) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    let pairs: Vec<(String, String)> = key
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = match name.as_ref() {
+                "latitude" | "longitude" => value
+                    .parse()
+                    .map(|coordinate| round_coordinate(coordinate).to_string())
+                    .unwrap_or_else(|_| value.into_owned()),
+                _ => value.into_owned(),
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    if !pairs.is_empty() {
+        key.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    key.into()
+}
+
+/// Splits current alerts into those not yet seen and the seen IDs that are no longer active
+fn diff_alerts(alerts: AlertResponse, seen_ids: &[String]) -> (AlertResponse, Vec<String>) {
+    let seen: HashSet<&str> = seen_ids.iter().map(String::as_str).collect();
+    let current: HashSet<&str> = alerts
+        .features
+        .iter()
+        .map(|feature| feature.properties.id.as_str())
+        .collect();
+
+    let expired = seen_ids
+        .iter()
+        .filter(|id| !current.contains(id.as_str()))
+        .cloned()
+        .collect();
+
+    let features = alerts
+        .features
+        .into_iter()
+        .filter(|feature| !seen.contains(feature.properties.id.as_str()))
+        .collect();
+
+    (AlertResponse { features }, expired)
+}
+
+/// Resolves an optional `watch_alerts` setting against its (min, max, default) bounds
+fn watch_setting(value: Option<u64>, bounds: (u64, u64, u64), name: &str) -> Result<u64, McpError> {
+    let (min, max, default) = bounds;
+    match value {
+        None => Ok(default),
+        Some(value) if (min..=max).contains(&value) => Ok(value),
+        Some(value) => Err(McpError::invalid_params(
+            format!("{} must be between {} and {}, got {}", name, min, max, value),
+            None,
+        )),
+    }
+}
+
+/// Orders alerts for a point: those whose area contains it first, then by distance
+/// to the center of their nearest area, then alerts without an area, most severe
+/// first
+fn sort_alerts_by_proximity(alerts: &mut AlertResponse, point: (f64, f64)) {
+    let distance = |feature: &AlertFeature| {
+        let rings = feature.geometry.as_ref()?.outer_rings();
+        if rings.iter().any(|ring| point_in_ring(point, ring)) {
+            return Some(0.0);
+        }
+        rings
+            .iter()
+            .filter_map(|ring| ring_center(ring))
+            .map(|center| great_circle_km(point, center))
+            .min_by(f64::total_cmp)
+    };
+    let mut features: Vec<(Option<f64>, AlertFeature)> = alerts
+        .features
+        .drain(..)
+        .map(|feature| (distance(&feature), feature))
+        .collect();
+    features.sort_by(|(a_distance, a), (b_distance, b)| match (a_distance, b_distance) {
+        (Some(a_distance), Some(b_distance)) => a_distance.total_cmp(b_distance),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => b.properties.severity_rank().cmp(&a.properties.severity_rank()),
+    });
+    alerts.features = features.into_iter().map(|(_, feature)| feature).collect();
+}
+
+/// Normalizes a state code and checks it against the supported list
+fn validate_state(state: &str) -> Result<String, McpError> {
+    let code = state.trim().to_ascii_uppercase();
+    if US_STATES.iter().any(|(known, _)| *known == code) {
+        Ok(code)
+    } else {
+        Err(McpError::invalid_params(
+            format!(
+                "Unknown state code '{}'. Use a two-letter US state or territory code (see list_states).",
+                state
+            ),
+            None,
+        ))
+    }
+}
+
+/// Normalizes a county code to its six-digit SAME form, or an uppercase UGC code
+fn validate_county(county: &str) -> Result<String, McpError> {
+    let code = county.trim().to_ascii_uppercase();
+    let bytes = code.as_bytes();
+    let is_ugc = bytes.len() == 6
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && matches!(bytes[2], b'C' | b'Z')
+        && bytes[3..].iter().all(u8::is_ascii_digit);
+
+    match code.len() {
+        5 | 6 if bytes.iter().all(u8::is_ascii_digit) => Ok(format!("{:0>6}", code)),
+        _ if is_ugc => Ok(code),
+        _ => Err(McpError::invalid_params(
+            format!(
+                "Invalid county '{}'. Expected a 5-digit FIPS, 6-digit SAME, or UGC code (e.g., 'CAC037').",
+                county
+            ),
+            None,
+        )),
+    }
+}
+
+/// Parses an optional unit system name, defaulting to metric
+fn parse_units(value: Option<&str>, default: Units) -> Result<Units, McpError> {
+    match value {
+        None => Ok(default),
+        Some(value) => Units::parse(value).ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "Invalid units '{}'. Expected one of: metric, imperial, kelvin",
+                    value
+                ),
+                None,
+            )
+        }),
+    }
+}
+
+/// Parses an optional time format name, defaulting to 24-hour
+fn parse_time_format(value: Option<&str>) -> Result<TimeFormat, McpError> {
+    match value {
+        None => Ok(TimeFormat::default()),
+        Some(value) => TimeFormat::parse(value).ok_or_else(|| {
+            McpError::invalid_params(
+                format!("Invalid time_format '{}'. Expected one of: 24h, 12h", value),
+                None,
+            )
+        }),
+    }
+}
+
+/// Largest bounding box side accepted by `get_area_forecast`, in degrees
+const MAX_AREA_SPAN_DEGREES: f64 = 5.0;
+
+/// Largest number of grid points per side in `get_area_forecast` (25 points in total)
+const MAX_AREA_RESOLUTION: usize = 5;
+
+/// Validates an area request and returns its grid of `(latitude, longitude)` points
+fn area_grid(request: &GetAreaForecastRequest) -> Result<Vec<(f64, f64)>, McpError> {
+    let invalid = |message: String| Err(McpError::invalid_params(message, None));
+    let (south, north) = (request.min_latitude, request.max_latitude);
+    let (west, east) = (request.min_longitude, request.max_longitude);
+
+    if !(-90.0..=90.0).contains(&south) || !(-90.0..=90.0).contains(&north) {
+        return invalid("Latitudes must be between -90 and 90".to_string());
+    }
+    if !(-180.0..=180.0).contains(&west) || !(-180.0..=180.0).contains(&east) {
+        return invalid("Longitudes must be between -180 and 180".to_string());
+    }
+    if south >= north || west >= east {
+        return invalid(
+            "The bounding box is empty: each minimum must be less than its maximum".to_string(),
+        );
+    }
+    if north - south > MAX_AREA_SPAN_DEGREES || east - west > MAX_AREA_SPAN_DEGREES {
+        return invalid(format!(
+            "The bounding box is too large: each side may span at most {} degrees",
+            MAX_AREA_SPAN_DEGREES
+        ));
+    }
+
+    let resolution = request.resolution.unwrap_or(3);
+    if !(2..=MAX_AREA_RESOLUTION).contains(&resolution) {
+        return invalid(format!(
+            "Invalid resolution {}. Expected 2 to {} points per side.",
+            resolution, MAX_AREA_RESOLUTION
+        ));
+    }
+
+    let step = |min: f64, max: f64, i: usize| {
+        min + (max - min) * i as f64 / (resolution - 1) as f64
+    };
+    Ok((0..resolution)
+        .flat_map(|row| {
+            (0..resolution).map(move |col| (step(south, north, row), step(west, east, col)))
+        })
+        .collect())
+}
+
+/// Builds formatter options from a forecast request, rejecting unknown values
+fn format_options(
+    request: &ForecastOptions,
+    default_units: Units,
+) -> Result<FormatOptions, McpError> {
+    let format = match request.format.as_deref() {
+        None => OutputFormat::default(),
+        Some(value) => OutputFormat::parse(value).ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "Invalid format '{}'. Expected one of: text, narrative, arrays, geojson",
+                    value
+                ),
+                None,
+            )
+        })?,
+    };
+
+    let date_format = match request.date_format.as_deref() {
+        None => DateFormat::default(),
+        Some(value) => DateFormat::parse(value).ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "Invalid date_format '{}'. Expected one of: iso, short, weekday",
+                    value
+                ),
+                None,
+            )
+        })?,
+    };
+
+    let units = parse_units(request.units.as_deref(), default_units)?;
+
+    let wind_scale = match request.wind_scale.as_deref() {
+        None => WindScale::default(),
+        Some(value) => WindScale::parse(value).ok_or_else(|| {
+            McpError::invalid_params(
+                format!("Invalid wind_scale '{}'. Expected one of: exact, beaufort", value),
+                None,
+            )
+        })?,
+    };
+
+    Ok(FormatOptions {
+        format,
+        date_format,
+        units,
+        wind_scale,
+        time_format: parse_time_format(request.time_format.as_deref())?,
+        highlight_worst: request.highlight_worst.unwrap_or(false),
+        show_golden_hour: request.show_golden_hour.unwrap_or(false),
+        include_hourly_worst: request.include_hourly_worst.unwrap_or(false),
+        show_zero_precipitation: !request.hide_zero_precip.unwrap_or(true),
+        wind_arrows: request.arrows.unwrap_or(false),
+    })
+}
+
+/// Relative day expressions accepted by `get_forecast_relative`
+const RELATIVE_DAYS: &[&str] = &["today", "tomorrow", "this weekend", "next 3 days"];
+
+/// Maps a relative day expression to an inclusive date range starting from `today`.
+///
+/// "This weekend" is the coming Saturday and Sunday, or what is left of it when
+/// `today` already falls on the weekend.
+fn relative_date_range(when: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    match when.trim().to_lowercase().as_str() {
+        "today" => Some((today, today)),
+        "tomorrow" => {
+            let tomorrow = today + Duration::days(1);
+            Some((tomorrow, tomorrow))
+        }
+        "this weekend" => Some(match today.weekday() {
+            Weekday::Sun => (today, today),
+            weekday => {
+                let saturday = today + Duration::days(5 - weekday.num_days_from_monday() as i64);
+                (saturday, saturday + Duration::days(1))
+            }
+        }),
+        "next 3 days" => Some((today, today + Duration::days(2))),
+        _ => None,
+    }
+}
+
+/// Applies the attribution and size options to a formatted forecast
+fn finish_forecast(
+    mut forecast: FormattedForecast,
+    options: &ForecastOptions,
+    attribution: &str,
+) -> FormattedForecast {
+    if options.show_attribution.unwrap_or(true) {
+        forecast.footer = format!("{}\n", attribution);
+    }
+
+    if let Some(max_chars) = options.max_chars {
+        forecast.truncate(max_chars);
+    }
+    forecast
+}
+
+/// Builds tool content from a forecast summary, splitting text into one block per
+/// day when requested
+fn forecast_contents(
+    summary: ForecastSummary,
+    options: &ForecastOptions,
+) -> Result<Vec<Content>, McpError> {
+    Ok(match summary {
+        ForecastSummary::Text(forecast) if options.split_days.unwrap_or(false) => {
+            forecast.into_blocks().into_iter().map(Content::text).collect()
+        }
+        ForecastSummary::Text(forecast) => vec![Content::text(forecast.into_text())],
+        ForecastSummary::Chart { png, caption } => png
+            .map(|png| Content::image(base64_encode(&png), "image/png"))
+            .into_iter()
+            .chain([Content::text(caption)])
+            .collect(),
+        ForecastSummary::Arrays(arrays) => vec![Content::json(arrays)?],
+        ForecastSummary::GeoJson(feature) => vec![Content::json(feature)?],
+    })
+}
+
+/// The JSON schema the server expects from an upstream endpoint, pretty-printed
+fn format_response_schema(kind: &DebugUrlKind) -> Result<String, McpError> {
+    let schema = match kind {
+        DebugUrlKind::NwsPoints => schema_for!(PointsResponse),
+        DebugUrlKind::NwsForecast => schema_for!(ForecastResponse),
+        DebugUrlKind::OpenMeteo => schema_for!(OpenMeteoResponse),
+        DebugUrlKind::Alerts => schema_for!(AlertResponse),
+    };
+    serde_json::to_string_pretty(&schema)
+        .map_err(|e| McpError::internal_error(format!("Failed to format schema: {}", e), None))
+}
+
+/// Converts a library-layer error back into an MCP error, keeping the original
+/// error code when there is one
+fn mcp_error(error: anyhow::Error) -> McpError {
+    error
+        .downcast::<McpError>()
+        .unwrap_or_else(|e| McpError::internal_error(format!("{:#}", e), None))
+}
+
+/// Settings for the shared HTTP client
+#[derive(Debug, Clone)]
+struct ClientConfig {
+    user_agent: String,
+    /// Overall per-request timeout; `None` waits indefinitely
+    timeout: Option<std::time::Duration>,
+    /// Idle connections kept open per upstream host; 0 disables reuse
+    pool_max_idle_per_host: usize,
+    /// How long an idle connection is kept before it is closed
+    pool_idle_timeout: std::time::Duration,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: USER_AGENT.to_string(),
+            // Generous enough for large archive queries, but a hung upstream still fails
+            timeout: Some(std::time::Duration::from_secs(30)),
+            // Enough for the batch tools' concurrent requests to one host
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout: std::time::Duration::from_secs(90),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// The defaults with any timeout and pool settings overridden from the environment
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            timeout: env_count(HTTP_TIMEOUT_ENV_VAR).map_or(defaults.timeout, |seconds| {
+                (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+            }),
+            pool_max_idle_per_host: env_count(POOL_MAX_IDLE_ENV_VAR)
+                .map_or(defaults.pool_max_idle_per_host, |count| count as usize),
+            pool_idle_timeout: env_count(POOL_IDLE_TIMEOUT_ENV_VAR)
+                .map_or(defaults.pool_idle_timeout, std::time::Duration::from_secs),
+            ..defaults
+        }
+    }
+}
+
+/// Builds the HTTP client used for all upstream requests
+fn build_client(config: &ClientConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(config.user_agent.as_str())
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout);
+    if let Some(timeout) = config.timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    builder
+        .build()
+        .inspect_err(|e| tracing::error!("HTTP client build failed: {:?}", e))
+        .context("failed to build HTTP client")
+}
+
+/// Builds a chart summary: a PNG line chart when one renders, plus text sparklines
+/// that double as the fallback for clients without image support
+fn chart_summary(
+    title: &str,
+    series: &[(&str, Vec<f64>)],
+    describe: impl Fn(f64) -> String,
+) -> ForecastSummary {
+    let mut caption = format!("{}\n", title);
+    for (label, values) in series.iter().filter(|(_, values)| !values.is_empty()) {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        caption.push_str(&format!(
+            "{}: {} ({} to {})\n",
+            label,
+            sparkline(values),
+            describe(min),
+            describe(max)
+        ));
+    }
+
+    let data: Vec<&[f64]> = series.iter().map(|(_, values)| values.as_slice()).collect();
+    ForecastSummary::Chart {
+        png: line_chart_png(&data),
+        caption,
+    }
+}
+
+/// A forecast returned by [`Weather::forecast`], in the shape its options asked for
+#[derive(Debug, Clone)]
+pub enum ForecastSummary {
+    /// A formatted forecast, with attribution and truncation already applied
+    Text(FormattedForecast),
+    /// A temperature trend chart: PNG bytes when one renders, and a text caption
+    /// with sparklines
+    Chart { png: Option<Vec<u8>>, caption: String },
+    /// Daily series as parallel JSON arrays
+    Arrays(serde_json::Value),
+    /// Daily series as a GeoJSON Feature at the forecast point
+    GeoJson(serde_json::Value),
+}
 
-/// Main weather service that handles MCP requests
+/// A forecast request in progress, shareable by identical concurrent requests
+type ForecastFlight = Shared<BoxFuture<'static, Result<ForecastSummary, McpError>>>;
+
+/// Clears a single-flight entry when its waiter finishes or is dropped.
+///
+/// Without this, a forecast whose only caller disconnected would stay in the map,
+/// keeping its half-finished upstream requests alive until someone asked again.
+struct FlightGuard<'a> {
+    in_flight: &'a Mutex<HashMap<String, ForecastFlight>>,
+    key: String,
+    flight: ForecastFlight,
+}
+
+impl Drop for FlightGuard<'_> {
+    fn drop(&mut self) {
+        // Leave the entry alone if a newer request has already replaced it
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(PoisonError::into_inner);
+        if in_flight.get(&self.key).is_some_and(|current| current.ptr_eq(&self.flight)) {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+/// Logs an HTTP request whose future is dropped before it completes, e.g. because
+/// the MCP client disconnected. Dropping the reqwest future aborts the request.
+struct CancelLog<'a> {
+    url: &'a str,
+    completed: bool,
+}
+
+impl Drop for CancelLog<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            tracing::debug!("Request to {} cancelled before completing", self.url);
+        }
+    }
+}
+
+/// Main weather service.
+///
+/// It has two layers. Public methods such as [`Weather::forecast`] are the library
+/// API: they take plain arguments and return domain results, with no MCP types
+/// involved. The `#[tool]` methods are the MCP layer: they parse tool parameters,
+/// call the library API, and turn its results into tool content.
 #[derive(Clone)]
 pub struct Weather {
     client: Arc<Client>,
+    nws_base: String,
+    open_meteo_base: String,
+    air_quality_base: String,
+    ensemble_base: String,
+    geocoding_base: String,
+    nominatim_base: String,
+    archive_base: String,
+    /// When Nominatim was last queried, to keep to its one-request-per-second policy
+    nominatim_last_request: Arc<Mutex<Option<Instant>>>,
+    /// Waits out retry delays
+    sleeper: Arc<dyn Sleeper>,
+    /// Request cap shared by the sub-requests of a batch tool call; set only on the
+    /// clones made by `with_budget`
+    budget: Option<Arc<RequestBudget>>,
+    /// Circuit breakers keyed by upstream host and port, with a display name for errors
+    breakers: Arc<HashMap<String, (&'static str, CircuitBreaker)>>,
+    /// Forecasts in progress, keyed by the serialized request
+    in_flight: Arc<Mutex<HashMap<String, ForecastFlight>>>,
+    /// Last successful NWS forecast per points URL, served when a fresh fetch fails
+    stale_forecasts: Arc<Mutex<HashMap<String, ForecastResponse>>>,
+    /// Forecast responses persisted between runs, when `MCP_WEATHER_CACHE_DIR` is set
+    disk_cache: Option<Arc<DiskCache>>,
+    /// Units used when a request doesn't specify any
+    default_units: Units,
+    /// Furthest day ahead, counting today as day 1, that forecast tools may request
+    max_forecast_days: u32,
+    /// Most days of history archive tools may request; `None` leaves the API's limit
+    max_archive_days: Option<u32>,
+    debug_enabled: bool,
+    /// Set by `over_stdio` when the server is served over stdin and stdout
+    stdio: bool,
     tool_router: ToolRouter<Self>,
 }
 
 impl Weather {
     /// Creates a new Weather service instance
     pub fn new() -> Result<Self> {
-        let client = Client::builder().user_agent(USER_AGENT).build()?;
+        let client = build_client(&ClientConfig::from_env())?;
+
+        let nws_base = base_url(NWS_BASE_ENV_VAR, NWS_API_BASE);
+        let open_meteo_base = base_url(OPEN_METEO_BASE_ENV_VAR, OPEN_METEO_API_BASE);
+        let air_quality_base = base_url(
+            OPEN_METEO_AIR_QUALITY_BASE_ENV_VAR,
+            OPEN_METEO_AIR_QUALITY_API_BASE,
+        );
+        let ensemble_base =
+            base_url(OPEN_METEO_ENSEMBLE_BASE_ENV_VAR, OPEN_METEO_ENSEMBLE_API_BASE);
+        let geocoding_base = base_url(
+            OPEN_METEO_GEOCODING_BASE_ENV_VAR,
+            OPEN_METEO_GEOCODING_API_BASE,
+        );
+        let nominatim_base = base_url(NOMINATIM_BASE_ENV_VAR, NOMINATIM_API_BASE);
+        let archive_base = base_url(OPEN_METEO_ARCHIVE_BASE_ENV_VAR, OPEN_METEO_ARCHIVE_API_BASE);
 
-        Ok(Self {
+        let mut weather = Self {
             client: Arc::new(client),
-            tool_router: Self::tool_router(),
+            nws_base,
+            open_meteo_base,
+            air_quality_base,
+            ensemble_base,
+            geocoding_base,
+            nominatim_base,
+            archive_base,
+            nominatim_last_request: Arc::default(),
+            sleeper: Arc::new(TokioSleeper),
+            budget: None,
+            breakers: Arc::default(),
+            in_flight: Arc::default(),
+            stale_forecasts: Arc::default(),
+            disk_cache: std::env::var_os(CACHE_DIR_ENV_VAR)
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| Arc::new(DiskCache::new(dir.into()))),
+            default_units: env_units(DEFAULT_UNITS_ENV_VAR),
+            max_forecast_days: env_days(MAX_FORECAST_DAYS_ENV_VAR)
+                .unwrap_or(OPEN_METEO_MAX_FORECAST_DAYS),
+            max_archive_days: env_days(MAX_ARCHIVE_DAYS_ENV_VAR),
+            debug_enabled: env_flag(DEBUG_ENV_VAR),
+            stdio: false,
+            tool_router: Self::enabled_tool_router(
+                &std::env::var(ENABLED_TOOLS_ENV_VAR).unwrap_or_default(),
+            ),
+        };
+        weather.breakers = Arc::new(weather.upstream_breakers());
+        Ok(weather)
+    }
+
+    /// Marks the service as served over stdio. A watch there would hold the
+    /// client's only request open for its whole duration, so `watch_alerts`
+    /// returns at once with an explanation instead.
+    pub fn over_stdio(self) -> Self {
+        Self {
+            stdio: true,
+            ..self
+        }
+    }
+
+    /// Builds a circuit breaker per upstream host and port, named for error messages.
+    /// Upstreams configured on the same host and port share a breaker.
+    fn upstream_breakers(&self) -> HashMap<String, (&'static str, CircuitBreaker)> {
+        [
+            ("NWS", &self.nws_base),
+            ("Open-Meteo", &self.open_meteo_base),
+            ("Open-Meteo air quality", &self.air_quality_base),
+            ("Open-Meteo ensemble", &self.ensemble_base),
+            ("Open-Meteo geocoding", &self.geocoding_base),
+            ("Nominatim", &self.nominatim_base),
+            ("Open-Meteo archive", &self.archive_base),
+        ]
+        .into_iter()
+        .filter_map(|(name, base)| {
+            let origin = upstream_origin(&reqwest::Url::parse(base).ok()?)?;
+            Some((origin, (name, CircuitBreaker::default())))
         })
+        .collect()
     }
 
-    /// Makes an HTTP GET request and deserializes the JSON response
-    async fn make_request<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T> {
-        let response = self.client.get(url).send().await?;
+    /// Builds the tool router, keeping only the tools in `enabled_tools`, the
+    /// comma-separated value of `MCP_WEATHER_ENABLED_TOOLS`, when it names any.
+    ///
+    /// Removed tools are neither advertised nor callable; calls to them fail as
+    /// unknown tools.
+    fn enabled_tool_router(enabled_tools: &str) -> ToolRouter<Self> {
+        let mut router = Self::tool_router();
+        let enabled: HashSet<&str> = enabled_tools
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        if enabled.is_empty() {
+            return router;
+        }
 
-        if !response.status().is_success() {
-            anyhow::bail!("Request failed with status: {}", response.status());
+        for name in &enabled {
+            if !router.has_route(name) {
+                tracing::warn!("Ignoring unknown tool '{}' in {}", name, ENABLED_TOOLS_ENV_VAR);
+            }
         }
+        for tool in router.list_all() {
+            if !enabled.contains(tool.name.as_ref()) {
+                router.remove_route(&tool.name);
+            }
+        }
+        tracing::info!("Enabled tools: {}", router.list_all().len());
+        router
+    }
 
-        let data = response.json::<T>().await?;
-        Ok(data)
+    /// A clone whose upstream requests draw on one budget sized for `items`
+    /// sub-requests, for tools that fan out to many locations
+    fn with_budget(&self, items: usize) -> Self {
+        Self {
+            budget: Some(Arc::new(RequestBudget::for_batch(items))),
+            ..self.clone()
+        }
     }
 
-    /// Determines if coordinates are within US coverage area
-    fn is_us_location(latitude: f64, longitude: f64) -> bool {
-        // Continental US, Alaska, Hawaii, and territories
-        latitude >= 24.0 && latitude <= 72.0 && longitude >= -180.0 && longitude <= -60.0
+    /// Rejects a forecast that would reach further ahead than the configured limit
+    fn check_forecast_days(&self, days: i64) -> Result<(), McpError> {
+        if days > self.max_forecast_days as i64 {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Requested {} forecast days, but this server allows at most {} ({}).",
+                    days, self.max_forecast_days, MAX_FORECAST_DAYS_ENV_VAR
+                ),
+                None,
+            ));
+        }
+        Ok(())
     }
 
-    /// Gets forecast using NWS API for US locations
-    async fn get_forecast_nws(
-        &self,
-        request: GetForecastRequest,
-    ) -> Result<CallToolResult, McpError> {
-        tracing::info!("Using NWS API for US location");
+    /// Rejects an archive range longer than the configured limit
+    fn check_archive_days(&self, days: i64) -> Result<(), McpError> {
+        match self.max_archive_days {
+            Some(max) if days > max as i64 => Err(McpError::invalid_params(
+                format!(
+                    "Requested {} days of history, but this server allows at most {} ({}).",
+                    days, max, MAX_ARCHIVE_DAYS_ENV_VAR
+                ),
+                None,
+            )),
+            _ => Ok(()),
+        }
+    }
 
-        let points_url = format!(
-            "{}/points/{},{}",
-            NWS_API_BASE, request.latitude, request.longitude
-        );
+    /// Looks up the circuit breaker for a URL's host and port
+    fn breaker(&self, url: &str) -> Option<&(&'static str, CircuitBreaker)> {
+        let url = reqwest::Url::parse(url).ok()?;
+        self.breakers.get(&upstream_origin(&url)?)
+    }
 
-        let points = self
-            .make_request::<PointsResponse>(&points_url)
-            .await
-            .map_err(|e| {
-                if e.to_string().contains("404") {
-                    McpError::invalid_params(
-                        "Location not found in NWS coverage area. This location may be in US waters not covered by the grid system.",
-                        None,
-                    )
+    /// Makes an HTTP GET request and deserializes the JSON response.
+    ///
+    /// Requests to an upstream whose circuit breaker is open fail immediately.
+    /// Connection errors and 5xx responses count as failures; other responses,
+    /// including 4xx, count as the upstream being healthy. A 429 is retried up to
+    /// `MAX_RATE_LIMIT_RETRIES` times, after its `Retry-After` wait when it gives one
+    /// and with exponential backoff otherwise. Within a batch, every attempt draws
+    /// on the batch's request budget, and none is made once it is spent.
+    ///
+    /// Errors name the upstream, taken from the breaker registered for the URL's
+    /// host and port, and HTML outage pages are reported as the upstream being
+    /// unavailable rather than as a parse error.
+    async fn make_request<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T> {
+        let breaker = self.breaker(url);
+        let upstream = breaker.map_or("Upstream", |(name, _)| *name);
+        if let Some((name, _)) = breaker.filter(|(_, breaker)| breaker.is_open()) {
+            anyhow::bail!("{} temporarily unavailable after repeated failures", name);
+        }
+
+        let mut cancel_log = CancelLog {
+            url,
+            completed: false,
+        };
+        let result = async {
+            let mut retries = 0;
+            let response = loop {
+                let permit = match &self.budget {
+                    Some(budget) => match budget.spend().await {
+                        Some(permit) => Some(permit),
+                        None => {
+                            anyhow::bail!("{} request budget for this batch is spent", upstream)
+                        }
+                    },
+                    None => None,
+                };
+                let response = match self.client.get(url).send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        if let Some((_, breaker)) = breaker {
+                            breaker.record_failure();
+                        }
+                        tracing::warn!("{} request to {} failed: {}", upstream, url, e);
+                        anyhow::bail!("{} request failed: {}", upstream, e);
+                    }
+                };
+                if retries == MAX_RATE_LIMIT_RETRIES
+                    || response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS
+                {
+                    break response;
+                }
+                let delay = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| retry_after_delay(value, Utc::now()))
+                    .unwrap_or_else(|| backoff_delay(retries));
+                tracing::warn!(
+                    "{} rate limited {}, retrying in {}s",
+                    upstream,
+                    url,
+                    delay.as_secs()
+                );
+                retries += 1;
+                // Free the batch's in-flight slot while waiting; the retry takes a new one
+                drop(permit);
+                self.sleeper.sleep(delay).await;
+            };
+            if let Some((_, breaker)) = breaker {
+                if response.status().is_server_error() {
+                    breaker.record_failure();
                 } else {
-                    McpError::internal_error(
-                        format!("Failed to fetch grid points: {}", e),
-                        None,
-                    )
+                    breaker.record_success();
                 }
-            })?;
+            }
 
-        let forecast_url = format!(
-            "{}/gridpoints/{}/{},{}/forecast",
-            NWS_API_BASE,
-            points.properties.grid_id,
-            points.properties.grid_x,
-            points.properties.grid_y
-        );
+            let status = response.status();
+            let is_json = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.contains("json"));
+            if !status.is_success() {
+                tracing::warn!("{} request to {} failed with status {}", upstream, url, status);
+                if !is_json {
+                    anyhow::bail!(
+                        "{} is currently unavailable (returned a non-JSON error page, status {})",
+                        upstream,
+                        status
+                    );
+                }
+                anyhow::bail!("{} request failed with status: {}", upstream, status);
+            }
 
-        let forecast = self
-            .make_request::<ForecastResponse>(&forecast_url)
-            .await
-            .map_err(|e| {
-                McpError::internal_error(format!("Failed to fetch forecast: {}", e), None)
+            let body = response.bytes().await.map_err(|e| {
+                anyhow::anyhow!("{} returned an unexpected response: {}", upstream, e)
             })?;
+            // Outage pages are sometimes served as HTML even with a success status
+            if body.trim_ascii_start().starts_with(b"<") {
+                tracing::warn!("{} request to {} returned an HTML page", upstream, url);
+                anyhow::bail!(
+                    "{} is currently unavailable (returned a non-JSON error page)",
+                    upstream
+                );
+            }
+            let data = serde_json::from_slice::<T>(&body).map_err(|e| {
+                anyhow::anyhow!("{} returned an unexpected response: {}", upstream, e)
+            })?;
+            Ok(data)
+        }
+        .await;
+        cancel_log.completed = true;
+        result
+    }
 
-        let formatted = format_forecast(forecast);
+    /// Like `make_request`, but served from the disk cache when it holds a fresh copy
+    /// of the response for the same rounded coordinates and parameters, and stored
+    /// there after a successful fetch
+    async fn make_cached_request<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T> {
+        let Some(cache) = &self.disk_cache else {
+            return self.make_request(url).await;
+        };
+        let key = cache_key(url);
+        if let Some(body) = cache.get(&key) {
+            match serde_json::from_slice::<T>(&body) {
+                Ok(data) => {
+                    tracing::debug!("Serving {} from the disk cache", url);
+                    return Ok(data);
+                }
+                Err(e) => tracing::warn!("Ignoring unreadable cache entry for {}: {}", url, e),
+            }
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(formatted)]))
+        let value = self.make_request::<serde_json::Value>(url).await?;
+        let data = serde_json::from_value::<T>(value.clone())
+            .context("Upstream returned an unexpected response")?;
+        cache.put(&key, value.to_string().as_bytes());
+        Ok(data)
     }
 
-    /// Gets forecast using Open-Meteo API for non-US locations
-    async fn get_forecast_open_meteo(
-        &self,
-        request: GetForecastRequest,
-    ) -> Result<CallToolResult, McpError> {
-        tracing::info!("Using Open-Meteo API for non-US location");
+    /// Determines if coordinates are within US coverage area
+    fn is_us_location(latitude: f64, longitude: f64) -> bool {
+        // Continental US, Alaska, Hawaii, and territories
+        (24.0..=72.0).contains(&latitude) && (-180.0..=-60.0).contains(&longitude)
+    }
 
-        let url = format!(
-            "{}/forecast?latitude={}&longitude={}&daily=temperature_2m_max,temperature_2m_min,weather_code,wind_speed_10m_max,precipitation_sum&timezone=auto",
-            OPEN_METEO_API_BASE, request.latitude, request.longitude
-        );
+    /// Builds the NWS points lookup URL for coordinates
+    fn points_url(&self, latitude: f64, longitude: f64) -> String {
+        // NWS redirects /points requests with more than 4 decimal places
+        format!(
+            "{}/points/{},{}",
+            self.nws_base,
+            round_coordinate(latitude),
+            round_coordinate(longitude)
+        )
+    }
 
-        let forecast = self
-            .make_request::<OpenMeteoResponse>(&url)
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    format!("Failed to fetch Open-Meteo forecast: {}", e),
-                    None,
-                )
-            })?;
+    /// Builds the NWS gridpoint forecast URL
+    fn gridpoint_forecast_url(&self, grid_id: &str, grid_x: i32, grid_y: i32) -> String {
+        format!(
+            "{}/gridpoints/{}/{},{}/forecast",
+            self.nws_base, grid_id, grid_x, grid_y
+        )
+    }
 
-        let formatted = format_open_meteo_forecast(forecast);
+    /// Builds the NWS URL listing a gridpoint's observation stations, nearest first
+    fn gridpoint_stations_url(&self, grid_id: &str, grid_x: i32, grid_y: i32) -> String {
+        format!(
+            "{}/gridpoints/{}/{},{}/stations",
+            self.nws_base, grid_id, grid_x, grid_y
+        )
+    }
 
-        Ok(CallToolResult::success(vec![Content::text(formatted)]))
+    /// Builds the NWS URL for a station's latest observation
+    fn latest_observation_url(&self, station_id: &str) -> String {
+        format!("{}/stations/{}/observations/latest", self.nws_base, station_id)
     }
-}
 
-#[tool_handler]
-impl ServerHandler for Weather {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            server_info: Implementation {
-                name: "mcp-rust-weather".to_string(),
-                version: env!("CARGO_PKG_VERSION").to_string(),
-                icons: None,
-                title: None,
-                website_url: None,
-            },
-            instructions: Some(
-                "A weather information service powered by the National Weather Service API. \
-                Provides weather alerts and forecasts for US locations."
-                    .to_string(),
-            ),
-        }
+    /// Builds the NWS active alerts URL for a state
+    fn alerts_url(&self, state: &str) -> String {
+        format!("{}/alerts/active?area={}", self.nws_base, state)
     }
-}
 
-#[tool_router]
-impl Weather {
-    /// Gets active weather alerts for a US state
-    #[tool(description = "Get active weather alerts for a US state. Provide a two-letter state code (e.g., 'CA' for California, 'NY' for New York).")]
-    async fn get_alerts(
-        &self,
-        Parameters(request): Parameters<GetAlertsRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        tracing::info!("Getting alerts for state: {}", request.state);
+    /// Builds the NWS active alerts URL for a point
+    fn point_alerts_url(&self, latitude: f64, longitude: f64) -> String {
+        format!(
+            "{}/alerts/active?point={},{}",
+            self.nws_base,
+            round_coordinate(latitude),
+            round_coordinate(longitude)
+        )
+    }
 
-        let url = format!("{}/alerts/active?area={}", NWS_API_BASE, request.state);
+    /// Builds the Open-Meteo daily forecast URL for coordinates
+    fn open_meteo_forecast_url(&self, latitude: f64, longitude: f64, units: Units) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&daily=temperature_2m_max,temperature_2m_min,weather_code,wind_speed_10m_max,wind_direction_10m_dominant,precipitation_sum,precipitation_probability_max,sunrise,sunset,daylight_duration&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            units.open_meteo_params()
+        )
+    }
 
-        let alerts = self
+    /// Builds the Open-Meteo hourly forecast URL for the given variables and horizon
+    fn open_meteo_hourly_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        variables: &str,
+        hours: u32,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&hourly={}&forecast_hours={}&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            variables,
+            hours,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo hourly forecast URL covering whole local days
+    fn open_meteo_hourly_days_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        variables: &str,
+        days: u32,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&hourly={}&forecast_days={}&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            variables,
+            days,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo daily forecast URL for the given variables
+    fn open_meteo_daily_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        variables: &str,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&daily={}&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            variables,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo URL for daily variables over the past days and today
+    fn open_meteo_past_days_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        variables: &str,
+        past_days: u32,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&daily={}&past_days={}&forecast_days=1&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            variables,
+            past_days,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo URL for current conditions with the given variables
+    fn open_meteo_current_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        variables: &str,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&current={}&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            variables,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo URL for current wind speed, gusts, and direction
+    fn wind_url(&self, latitude: f64, longitude: f64, unit: SpeedUnit) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&current=wind_speed_10m,wind_gusts_10m,wind_direction_10m&wind_speed_unit={}&timezone=auto",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            unit.open_meteo_value()
+        )
+    }
+
+    /// Builds the Open-Meteo URL for a single model's two-day daily forecast, in metric
+    fn model_forecast_url(&self, latitude: f64, longitude: f64, model: &str) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&daily=temperature_2m_max,precipitation_sum&forecast_days=2&models={}&timezone=auto",
+            self.open_meteo_base, latitude, longitude, model
+        )
+    }
+
+    /// Builds the Open-Meteo hourly forecast URL for the next 7 days with times in UTC
+    fn open_meteo_hourly_utc_url(&self, latitude: f64, longitude: f64, variables: &str) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&hourly={}&forecast_days=7&timezone=GMT",
+            self.open_meteo_base, latitude, longitude, variables
+        )
+    }
+
+    /// Builds the Open-Meteo ensemble URL for every member's hourly temperature
+    fn ensemble_url(&self, latitude: f64, longitude: f64, model: &str, units: Units) -> String {
+        format!(
+            "{}/ensemble?latitude={}&longitude={}&hourly=temperature_2m&models={}&forecast_days=7&timezone=auto{}",
+            self.ensemble_base,
+            latitude,
+            longitude,
+            model,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo air quality URL for pollen data
+    fn pollen_url(&self, latitude: f64, longitude: f64) -> String {
+        const POLLEN_FIELDS: &str = "alder_pollen,birch_pollen,grass_pollen,ragweed_pollen";
+        format!(
+            "{}/air-quality?latitude={}&longitude={}&current={}&hourly={}&timezone=auto",
+            self.air_quality_base, latitude, longitude, POLLEN_FIELDS, POLLEN_FIELDS
+        )
+    }
+
+    /// Builds the Open-Meteo forecast URL for daily variables over a date range within
+    /// the forecast API's past and future window
+    fn forecast_range_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        (start, end): (NaiveDate, NaiveDate),
+        variables: &str,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/forecast?latitude={}&longitude={}&start_date={}&end_date={}&daily={}&timezone=auto{}",
+            self.open_meteo_base,
+            latitude,
+            longitude,
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d"),
+            variables,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Builds the Open-Meteo archive URL for daily variables over a date range
+    fn archive_url(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        (start, end): (NaiveDate, NaiveDate),
+        variables: &str,
+        units: Units,
+    ) -> String {
+        format!(
+            "{}/archive?latitude={}&longitude={}&start_date={}&end_date={}&daily={}&timezone=auto{}",
+            self.archive_base,
+            latitude,
+            longitude,
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d"),
+            variables,
+            units.open_meteo_params()
+        )
+    }
+
+    /// Fetches daily archive data over a date range in chunks of up to
+    /// `ARCHIVE_CHUNK_DAYS`.
+    ///
+    /// Chunks are requested in date order and each is retried once, so a timeout
+    /// partway through a long range only repeats that chunk.
+    async fn fetch_archive(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        (start, end): (NaiveDate, NaiveDate),
+        variables: &str,
+        units: Units,
+    ) -> Result<ArchiveResponse> {
+        let mut daily = ArchiveDaily::default();
+        let mut chunk_start = start;
+        while chunk_start <= end {
+            let chunk_end = (chunk_start + Duration::days(ARCHIVE_CHUNK_DAYS - 1)).min(end);
+            let url =
+                self.archive_url(latitude, longitude, (chunk_start, chunk_end), variables, units);
+            let chunk = match self.make_request::<ArchiveResponse>(&url).await {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::warn!(
+                        "Archive chunk {} to {} failed, retrying: {}",
+                        chunk_start,
+                        chunk_end,
+                        e
+                    );
+                    self.make_request::<ArchiveResponse>(&url).await?
+                }
+            };
+            daily.append(chunk.daily);
+            chunk_start = chunk_end + Duration::days(1);
+        }
+        Ok(ArchiveResponse { daily })
+    }
+
+    /// Builds the Open-Meteo geocoding search URL, optionally restricted to a country
+    fn geocoding_url(&self, query: &str, country_code: Option<&str>) -> Result<String> {
+        let mut url = reqwest::Url::parse(&format!("{}/search", self.geocoding_base))
+            .context("invalid geocoding base URL")?;
+        url.query_pairs_mut()
+            .append_pair("name", query)
+            .append_pair("count", "5")
+            .append_pair("format", "json");
+        if let Some(country_code) = country_code {
+            url.query_pairs_mut().append_pair("countryCode", country_code);
+        }
+        Ok(url.into())
+    }
+
+    /// Builds the Nominatim search URL, optionally restricted to a country
+    fn nominatim_url(&self, query: &str, country_code: Option<&str>) -> Result<String> {
+        let mut url = reqwest::Url::parse(&format!("{}/search", self.nominatim_base))
+            .context("invalid Nominatim base URL")?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("format", "jsonv2")
+            .append_pair("addressdetails", "1")
+            .append_pair("limit", "5");
+        if let Some(country_code) = country_code {
+            url.query_pairs_mut()
+                .append_pair("countrycodes", &country_code.to_lowercase());
+        }
+        Ok(url.into())
+    }
+
+    /// Looks up places matching a name or postal code.
+    ///
+    /// Tries the Open-Meteo geocoder first and falls back to Nominatim when it
+    /// errors or finds nothing.
+    async fn geocode(
+        &self,
+        query: &str,
+        country_code: Option<&str>,
+    ) -> Result<Vec<GeocodingResult>> {
+        let url = self.geocoding_url(query, country_code)?;
+        let primary = match self.make_request::<GeocodingResponse>(&url).await {
+            Ok(response) if !response.results.is_empty() => return Ok(response.results),
+            Ok(_) => {
+                tracing::info!("No Open-Meteo geocoding results; trying Nominatim");
+                Ok(Vec::new())
+            }
+            Err(e) => {
+                tracing::warn!("Open-Meteo geocoding failed, trying Nominatim: {}", e);
+                Err(e)
+            }
+        };
+
+        // Without fallback results, report the primary geocoder's outcome
+        match self.geocode_nominatim(query, country_code).await {
+            Ok(results) if !results.is_empty() => Ok(results),
+            Ok(_) => primary,
+            Err(e) => {
+                tracing::warn!("Nominatim geocoding failed: {}", e);
+                primary
+            }
+        }
+    }
+
+    /// Looks up places with Nominatim, at most once per second as its usage policy
+    /// asks; a lookup sooner than that is skipped rather than delayed
+    async fn geocode_nominatim(
+        &self,
+        query: &str,
+        country_code: Option<&str>,
+    ) -> Result<Vec<GeocodingResult>> {
+        {
+            let mut last = self
+                .nominatim_last_request
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if last.is_some_and(|last| last.elapsed() < std::time::Duration::from_secs(1)) {
+                anyhow::bail!("Nominatim was queried less than a second ago");
+            }
+            *last = Some(Instant::now());
+        }
+
+        let url = self.nominatim_url(query, country_code)?;
+        let places = self.make_request::<Vec<NominatimPlace>>(&url).await?;
+        Ok(places
+            .into_iter()
+            .filter_map(NominatimPlace::into_geocoding_result)
+            .collect())
+    }
+
+    /// Resolves coordinates to an NWS grid and fetches its forecast
+    async fn fetch_nws_forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<ForecastResponse, McpError> {
+        let points = self.fetch_nws_points(latitude, longitude).await?;
+        let forecast_url =
+            self.gridpoint_forecast_url(&points.grid_id, points.grid_x, points.grid_y);
+
+        self.make_cached_request::<ForecastResponse>(&forecast_url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch forecast: {}", e), None)
+            })
+    }
+
+    /// Fetches an Open-Meteo forecast with its daily series in date order
+    async fn fetch_open_meteo_forecast(&self, url: &str) -> Result<OpenMeteoResponse> {
+        let mut forecast = self.make_cached_request::<OpenMeteoResponse>(url).await?;
+        forecast.normalize_daily();
+        Ok(forecast)
+    }
+
+    /// Fetches the NWS forecast, falling back to the last one fetched for the same
+    /// point when NWS errors. The flag is true when the forecast is a stale copy.
+    async fn fetch_nws_forecast_or_stale(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        serve_stale: bool,
+    ) -> Result<(ForecastResponse, bool), McpError> {
+        let key = self.points_url(latitude, longitude);
+        let forecasts = || self.stale_forecasts.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match self.fetch_nws_forecast(latitude, longitude).await {
+            Ok(forecast) => {
+                let mut forecasts = forecasts();
+                if forecasts.len() >= STALE_FORECAST_CAPACITY && !forecasts.contains_key(&key) {
+                    // Any entry will do; this only bounds memory
+                    if let Some(evicted) = forecasts.keys().next().cloned() {
+                        forecasts.remove(&evicted);
+                    }
+                }
+                forecasts.insert(key, forecast.clone());
+                Ok((forecast, false))
+            }
+            // Only upstream failures; a point outside NWS coverage stays an error
+            Err(e) if serve_stale && e.code == ErrorCode::INTERNAL_ERROR => {
+                let cached = forecasts().get(&key).cloned();
+                match cached {
+                    Some(forecast) => {
+                        tracing::warn!("Serving stale NWS forecast after error: {}", e.message);
+                        Ok((forecast, true))
+                    }
+                    None => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Looks up the NWS grid for coordinates
+    async fn fetch_nws_points(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<PointsProperties, McpError> {
+        let points_url = self.points_url(latitude, longitude);
+
+        self.make_request::<PointsResponse>(&points_url)
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("404") {
+                    McpError::invalid_params(
+                        "Location not found in NWS coverage area. This location may be in US waters not covered by the grid system.",
+                        None,
+                    )
+                } else {
+                    McpError::internal_error(
+                        format!("Failed to fetch grid points: {}", e),
+                        None,
+                    )
+                }
+            })
+            .map(|points| points.properties)
+    }
+
+    /// Routes a forecast request, sharing the result with identical requests already
+    /// in flight instead of repeating the upstream calls
+    async fn single_flight_forecast(
+        &self,
+        request: &GetForecastRequest,
+    ) -> Result<ForecastSummary, McpError> {
+        let key = serde_json::to_string(request).map_err(|e| {
+            McpError::internal_error(format!("Failed to serialize request: {}", e), None)
+        })?;
+
+        let flight = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(PoisonError::into_inner);
+            in_flight
+                .entry(key.clone())
+                .or_insert_with(|| {
+                    let weather = self.clone();
+                    let (latitude, longitude) = (request.latitude, request.longitude);
+                    let options = request.options.clone();
+                    async move {
+                        weather
+                            .forecast(latitude, longitude, options)
+                            .await
+                            .map_err(mcp_error)
+                    }
+                    .boxed()
+                    .shared()
+                })
+                .clone()
+        };
+
+        // Whichever waiter finishes or is cancelled first clears the entry; once every
+        // waiter is gone the shared future is dropped, aborting its requests
+        let guard = FlightGuard {
+            in_flight: &self.in_flight,
+            key,
+            flight: flight.clone(),
+        };
+        let result = flight.await;
+        drop(guard);
+        result
+    }
+
+    /// Gets the forecast for a location.
+    ///
+    /// Picks NWS for US locations and Open-Meteo elsewhere, or while NWS is
+    /// short-circuited. Invalid option values are reported as an [`McpError`] with
+    /// the invalid-params code; upstream failures as plain errors.
+    pub async fn forecast(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        options: ForecastOptions,
+    ) -> Result<ForecastSummary> {
+        if !Self::is_us_location(latitude, longitude) {
+            return self.get_forecast_open_meteo(latitude, longitude, &options).await;
+        }
+
+        // JSON formats are built from Open-Meteo's daily data, which also covers the US
+        let wants_json = matches!(
+            options.format.as_deref().and_then(OutputFormat::parse),
+            Some(OutputFormat::Arrays | OutputFormat::GeoJson)
+        );
+        let nws_open = self
+            .breaker(&self.nws_base)
+            .is_some_and(|(_, breaker)| breaker.is_open());
+        if wants_json {
+            self.get_forecast_open_meteo(latitude, longitude, &options).await
+        } else if nws_open {
+            tracing::warn!("NWS temporarily unavailable; falling back to Open-Meteo");
+            self.get_forecast_open_meteo(latitude, longitude, &options).await
+        } else {
+            self.get_forecast_nws(latitude, longitude, &options).await
+        }
+    }
+
+    /// Gets forecast using NWS API for US locations
+    async fn get_forecast_nws(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        request: &ForecastOptions,
+    ) -> Result<ForecastSummary> {
+        tracing::info!("Using NWS API for US location");
+
+        let options = format_options(request, self.default_units)?;
+
+        // Alerts are best-effort: fetched alongside the forecast, but a failure only
+        // produces a note rather than failing the whole request
+        let serve_stale = request.serve_stale_on_error.unwrap_or(true);
+        let ((mut forecast, stale), alerts) = if request.include_alerts.unwrap_or(false) {
+            let alerts_url = self.point_alerts_url(latitude, longitude);
+            let (forecast, alerts) = tokio::join!(
+                self.fetch_nws_forecast_or_stale(latitude, longitude, serve_stale),
+                self.make_request::<AlertResponse>(&alerts_url)
+            );
+            (forecast?, Some(alerts))
+        } else {
+            let forecast = self
+                .fetch_nws_forecast_or_stale(latitude, longitude, serve_stale)
+                .await?;
+            (forecast, None)
+        };
+
+        if let Some(periods) = request.periods {
+            forecast.properties.periods.truncate(periods);
+        }
+
+        if request.as_chart.unwrap_or(false) {
+            let periods = &forecast.properties.periods;
+            let unit = periods.first().map_or("F", |p| p.temperature_unit());
+            let temperatures = periods.iter().map(|p| p.temperature as f64).collect();
+            return Ok(chart_summary(
+                &format!(
+                    "Temperature Trend (NWS), next {} periods\nLocation: {}",
+                    periods.len(),
+                    format_coords(latitude, longitude)
+                ),
+                &[("Temperature", temperatures)],
+                |value| format!("{:.0}\u{00b0}{}", value, unit),
+            ));
+        }
+
+        let mut formatted = if options.format == OutputFormat::Narrative {
+            format_forecast_narrative(forecast, latitude, longitude)
+        } else if request.merged.unwrap_or(false) {
+            format_forecast_merged(forecast, latitude, longitude)
+        } else {
+            format_forecast(forecast, latitude, longitude, options.time_format, options.wind_arrows)
+        };
+
+        match alerts {
+            Some(Ok(mut alerts)) => {
+                sort_alerts_by_proximity(&mut alerts, (latitude, longitude));
+                formatted.header.push_str(&format_alert_headlines(alerts));
+                formatted.header.push('\n');
+            }
+            Some(Err(e)) => {
+                tracing::warn!("Failed to fetch alerts alongside forecast: {}", e);
+                formatted.header.push_str("(alerts unavailable)\n\n");
+            }
+            None => {}
+        }
+        if stale {
+            formatted.header.push_str("(possibly stale \u{2014} NWS error)\n\n");
+        }
+
+        Ok(ForecastSummary::Text(finish_forecast(
+            formatted,
+            request,
+            NWS_ATTRIBUTION,
+        )))
+    }
+
+    /// Gets forecast using Open-Meteo API for non-US locations
+    async fn get_forecast_open_meteo(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        request: &ForecastOptions,
+    ) -> Result<ForecastSummary> {
+        tracing::info!("Using Open-Meteo API for non-US location");
+
+        let options = format_options(request, self.default_units)?;
+
+        let include_today_hourly = request.include_today_hourly.unwrap_or(false);
+        let mut url = self.open_meteo_forecast_url(latitude, longitude, options.units);
+        if request.include_current.unwrap_or(false) {
+            url.push_str("&current_weather=true");
+        }
+        if include_today_hourly {
+            // Same request: Open-Meteo returns hourly and daily series side by side
+            url.push_str("&hourly=temperature_2m,apparent_temperature,precipitation,weather_code");
+        } else if options.include_hourly_worst {
+            url.push_str("&hourly=weather_code");
+        }
+
+        let forecast = self
+            .fetch_open_meteo_forecast(&url)
+            .await
+            .context("Failed to fetch Open-Meteo forecast")?;
+
+        if request.as_chart.unwrap_or(false) {
+            let daily = &forecast.daily;
+            // Daily vectors can be shorter than `time` when a variable is missing
+            let days = [daily.time.len(), daily.temperature_max.len(), daily.temperature_min.len()]
+                .into_iter()
+                .min()
+                .unwrap_or(0)
+                .min(7);
+            let daily_units = forecast.units_or_default(options.units);
+            return Ok(chart_summary(
+                &format!(
+                    "Temperature Trend (Open-Meteo), next {} days\nLocation: {}",
+                    days,
+                    format_coords(forecast.latitude, forecast.longitude)
+                ),
+                &[
+                    ("High", daily.temperature_max[..days].to_vec()),
+                    ("Low", daily.temperature_min[..days].to_vec()),
+                ],
+                |value| format_temperature(value, &daily_units.temperature_max, options.units, 1),
+            ));
+        }
+
+        match options.format {
+            OutputFormat::Arrays => {
+                return Ok(ForecastSummary::Arrays(open_meteo_arrays(&forecast, options.units)));
+            }
+            OutputFormat::GeoJson => {
+                return Ok(ForecastSummary::GeoJson(open_meteo_geojson(&forecast, options.units)));
+            }
+            OutputFormat::Text | OutputFormat::Narrative => {}
+        }
+
+        let today_hourly = if include_today_hourly {
+            format_today_hourly(&forecast, options)
+        } else {
+            None
+        };
+        let mut formatted = match options.format {
+            OutputFormat::Narrative => format_open_meteo_narrative(forecast, options),
+            OutputFormat::Text | OutputFormat::Arrays | OutputFormat::GeoJson => {
+                format_open_meteo_forecast(forecast, options)
+            }
+        };
+        formatted.entries.extend(today_hourly);
+
+        Ok(ForecastSummary::Text(finish_forecast(
+            formatted,
+            request,
+            OPEN_METEO_ATTRIBUTION,
+        )))
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for Weather {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "mcp-rust-weather".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                icons: None,
+                title: None,
+                website_url: None,
+            },
+            instructions: Some(
+                "A weather information service powered by the National Weather Service API. \
+                Provides weather alerts and forecasts for US locations."
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[tool_router]
+impl Weather {
+    /// Gets active weather alerts for a US state
+    #[tool(description = "Get active weather alerts for a US state. Provide a two-letter state code (e.g., 'CA' for California, 'NY' for New York). Optionally narrow to one county with its FIPS code (e.g., '06037' for Los Angeles County) or a UGC code (e.g., 'CAC037').")]
+    async fn get_alerts(
+        &self,
+        Parameters(request): Parameters<GetAlertsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!("Getting alerts for state: {}", request.state);
+
+        let state = validate_state(&request.state)?;
+        let county = request.county.as_deref().map(validate_county).transpose()?;
+        let url = self.alerts_url(&state);
+
+        let mut alerts = self
             .make_request::<AlertResponse>(&url)
             .await
             .map_err(|e| {
                 McpError::internal_error(format!("Failed to fetch alerts: {}", e), None)
             })?;
 
-        let formatted = format_alerts(alerts);
+        if !request.include_cancelled.unwrap_or(false) {
+            alerts.features.retain(|feature| !feature.properties.is_cancel());
+        }
+
+        if let Some(county) = county {
+            // Alerts without a geocode block can't be matched precisely, so they're dropped
+            alerts.features.retain(|feature| {
+                feature
+                    .properties
+                    .geocode
+                    .as_ref()
+                    .is_some_and(|geocode| {
+                        geocode.same.contains(&county) || geocode.ugc.contains(&county)
+                    })
+            });
+        }
+
+        let event_types = request.event_types.unwrap_or_default();
+        if !event_types.is_empty() {
+            let mut active: Vec<String> = alerts
+                .features
+                .iter()
+                .map(|feature| feature.properties.event.clone())
+                .collect();
+            alerts.features.retain(|feature| {
+                event_types
+                    .iter()
+                    .any(|event| event.trim().eq_ignore_ascii_case(&feature.properties.event))
+            });
+
+            // Listing what is active helps correct a misspelled event type
+            if alerts.features.is_empty() && !active.is_empty() {
+                active.sort();
+                active.dedup();
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "No active alerts match the event types: {}.\nEvent types currently active: {}.",
+                    event_types.join(", "),
+                    active.join(", ")
+                ))]));
+            }
+        }
+
+        let formatted = if request.headlines_only.unwrap_or(false) {
+            format_alert_headlines(alerts)
+        } else {
+            format_alerts(alerts)
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(formatted)]))
+    }
+
+    /// Lists the state codes accepted by the alert tools
+    #[tool(description = "List all valid two-letter US state and territory codes accepted by get_alerts, with their full names.")]
+    async fn list_states(&self) -> Result<CallToolResult, McpError> {
+        Ok(CallToolResult::success(vec![Content::text(format_states(
+            US_STATES,
+        ))]))
+    }
+
+    /// Lists the WMO weather codes and their descriptions
+    #[tool(description = "List the WMO weather codes used by Open-Meteo forecasts with the description this server shows for each, sorted by code.")]
+    async fn get_weather_code_legend(&self) -> Result<CallToolResult, McpError> {
+        Ok(CallToolResult::success(vec![Content::text(
+            format_weather_code_legend(),
+        )]))
+    }
+
+    /// Gets alerts that changed since a previous poll
+    #[tool(description = "Get weather alert changes for a US state since a previous check. Provide a two-letter state code and the alert IDs seen previously (empty on the first call). Returns only new alerts plus the previously seen IDs that have expired.")]
+    async fn get_alert_changes(
+        &self,
+        Parameters(request): Parameters<GetAlertChangesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting alert changes for state: {} ({} seen)",
+            request.state,
+            request.seen_ids.len()
+        );
+
+        let state = validate_state(&request.state)?;
+        let alerts = self
+            .make_request::<AlertResponse>(&self.alerts_url(&state))
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch alerts: {}", e), None)
+            })?;
+
+        let (new_alerts, expired_ids) = diff_alerts(alerts, &request.seen_ids);
+        let formatted = format_alert_changes(new_alerts, &expired_ids);
+
+        Ok(CallToolResult::success(vec![Content::text(formatted)]))
+    }
+
+    /// Polls the alerts for a state and streams each change as a progress notification
+    #[tool(description = "Watch weather alerts for a US state over a period, for monitoring. Provide a two-letter state code; optional interval_seconds between polls (30-900, default 60) and duration_minutes (1-60, default 15). The current alerts are sent first, then only new and expired alerts as they change, as progress notifications when the call carries a progress token. The result lists every update once the watch ends. Over stdio it returns at once with an explanation instead, since the call would stay open for the whole watch.")]
+    async fn watch_alerts(
+        &self,
+        Parameters(request): Parameters<WatchAlertsRequest>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if self.stdio {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "watch_alerts is not available over the stdio transport, where a watch would \
+                 keep the call open for its whole duration. Use get_alerts for the current \
+                 alerts, then get_alert_changes with their IDs to check for changes.",
+            )]));
+        }
+        let state = validate_state(&request.state)?;
+        let interval =
+            watch_setting(request.interval_seconds, WATCH_INTERVAL_SECONDS, "interval_seconds")?;
+        let minutes =
+            watch_setting(request.duration_minutes, WATCH_DURATION_MINUTES, "duration_minutes")?;
+        tracing::info!(
+            "Watching alerts for state: {} every {}s for {} min",
+            state,
+            interval,
+            minutes
+        );
+
+        let interval = std::time::Duration::from_secs(interval);
+        let deadline = Instant::now() + std::time::Duration::from_secs(minutes * 60);
+        let progress_token = meta.get_progress_token();
+        let mut seen_ids: Vec<String> = Vec::new();
+        let mut updates = Vec::new();
+        let mut polls = 0;
 
+        loop {
+            let alerts = self.make_request::<AlertResponse>(&self.alerts_url(&state)).await;
+            polls += 1;
+            match alerts {
+                Ok(alerts) => {
+                    let (new_alerts, expired_ids) = diff_alerts(alerts, &seen_ids);
+                    if polls == 1 || !new_alerts.features.is_empty() || !expired_ids.is_empty() {
+                        seen_ids.retain(|id| !expired_ids.contains(id));
+                        seen_ids.extend(
+                            new_alerts.features.iter().map(|feature| feature.properties.id.clone()),
+                        );
+                        let update = format!(
+                            "[{}]\n{}",
+                            Utc::now().format("%H:%M UTC"),
+                            format_alert_changes(new_alerts, &expired_ids)
+                        );
+                        if let Some(token) = &progress_token {
+                            let notification = ProgressNotificationParam {
+                                progress_token: token.clone(),
+                                progress: polls as f64,
+                                total: None,
+                                message: Some(update.clone()),
+                            };
+                            if let Err(e) = peer.notify_progress(notification).await {
+                                tracing::warn!("Failed to send alert watch update: {}", e);
+                            }
+                        }
+                        updates.push(update);
+                    }
+                }
+                // The first poll has nothing to fall back on; later ones skip a failed poll
+                Err(e) if polls == 1 => {
+                    return Err(McpError::internal_error(
+                        format!("Failed to fetch alerts: {}", e),
+                        None,
+                    ));
+                }
+                Err(e) => tracing::warn!("Alert watch poll for {} failed: {}", state, e),
+            }
+
+            if Instant::now() + interval > deadline {
+                break;
+            }
+            tokio::time::sleep(interval).await;
+        }
+
+        let formatted = format_alert_watch(&state, minutes, polls, &updates);
         Ok(CallToolResult::success(vec![Content::text(formatted)]))
     }
 
@@ -182,15 +1945,3025 @@ impl Weather {
         Parameters(request): Parameters<GetForecastRequest>,
     ) -> Result<CallToolResult, McpError> {
         tracing::info!(
-            "Getting forecast for coordinates: {}, {}",
-            request.latitude,
-            request.longitude
+            "Getting forecast for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
         );
 
-        if Self::is_us_location(request.latitude, request.longitude) {
-            self.get_forecast_nws(request).await
-        } else {
-            self.get_forecast_open_meteo(request).await
+        let summary = self.single_flight_forecast(&request).await?;
+        Ok(CallToolResult::success(forecast_contents(summary, &request.options)?))
+    }
+
+    /// Gets weather forecast for a named landmark
+    #[tool(description = "Get the weather forecast for a landmark or named place (e.g., 'Eiffel Tower', 'Grand Canyon'). The name is geocoded and the resolved location is shown above the forecast. If the landmark can't be found, try a nearby city name instead.")]
+    async fn get_forecast_for_landmark(
+        &self,
+        Parameters(request): Parameters<GetLandmarkForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let landmark = request.landmark.trim();
+        tracing::info!("Getting forecast for landmark: {}", landmark);
+
+        let not_found = || {
+            McpError::invalid_params(
+                format!(
+                    "Could not find a specific place for '{}'. Try the name of a nearby city instead.",
+                    landmark
+                ),
+                None,
+            )
+        };
+
+        let place = self
+            .geocode(landmark, None)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to geocode landmark: {}", e), None)
+            })?
+            .into_iter()
+            .next()
+            .ok_or_else(not_found)?;
+
+        if place
+            .feature_code
+            .as_deref()
+            .is_some_and(|code| NON_PLACE_FEATURE_CODES.iter().any(|c| code.starts_with(c)))
+        {
+            return Err(not_found());
+        }
+
+        let options = ForecastOptions::default();
+        let summary = self
+            .forecast(place.latitude, place.longitude, options.clone())
+            .await
+            .map_err(mcp_error)?;
+        let mut contents = forecast_contents(summary, &options)?;
+        contents.insert(0, Content::text(format!("Resolved: {}", format_place(&place))));
+        Ok(CallToolResult::success(contents))
+    }
+
+    /// Fetches raw upstream JSON for troubleshooting
+    #[tool(description = "Debugging aid: fetch the raw JSON returned by a known upstream endpoint. url_kind is one of 'nws_points', 'nws_forecast', 'open_meteo' (these need latitude and longitude) or 'alerts' (needs state). Only available when the server is started with MCP_WEATHER_ENABLE_DEBUG=1.")]
+    async fn debug_fetch(
+        &self,
+        Parameters(request): Parameters<DebugFetchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.debug_enabled {
+            return Err(McpError::invalid_request(
+                format!("debug_fetch is disabled. Set {}=1 to enable it.", DEBUG_ENV_VAR),
+                None,
+            ));
+        }
+
+        tracing::info!("Debug fetch for {:?}", request.url_kind);
+
+        let coords = || match (request.latitude, request.longitude) {
+            (Some(latitude), Some(longitude)) => Ok((latitude, longitude)),
+            _ => Err(McpError::invalid_params(
+                "latitude and longitude are required for this url_kind",
+                None,
+            )),
+        };
+
+        let url = match request.url_kind {
+            DebugUrlKind::NwsPoints => {
+                let (latitude, longitude) = coords()?;
+                self.points_url(latitude, longitude)
+            }
+            DebugUrlKind::NwsForecast => {
+                let (latitude, longitude) = coords()?;
+                let points = self
+                    .make_request::<PointsResponse>(&self.points_url(latitude, longitude))
+                    .await
+                    .map_err(|e| {
+                        McpError::internal_error(format!("Failed to fetch grid points: {}", e), None)
+                    })?;
+                self.gridpoint_forecast_url(
+                    &points.properties.grid_id,
+                    points.properties.grid_x,
+                    points.properties.grid_y,
+                )
+            }
+            DebugUrlKind::OpenMeteo => {
+                let (latitude, longitude) = coords()?;
+                self.open_meteo_forecast_url(latitude, longitude, Units::default())
+            }
+            DebugUrlKind::Alerts => {
+                let state = request.state.as_deref().ok_or_else(|| {
+                    McpError::invalid_params("state is required for url_kind 'alerts'", None)
+                })?;
+                self.alerts_url(&validate_state(state)?)
+            }
+        };
+
+        let raw = self
+            .make_request::<serde_json::Value>(&url)
+            .await
+            .map_err(|e| McpError::internal_error(format!("Failed to fetch {}: {}", url, e), None))?;
+
+        let pretty = serde_json::to_string_pretty(&raw)
+            .map_err(|e| McpError::internal_error(format!("Failed to format JSON: {}", e), None))?;
+
+        let mut output = format!("GET {}\n\n{}", url, pretty);
+        if request.include_schema.unwrap_or(false) {
+            let schema = format_response_schema(&request.url_kind)?;
+            output.push_str(&format!("\n\nExpected schema:\n{}", schema));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Returns the JSON schema expected from an upstream endpoint
+    #[tool(description = "Get the JSON schema this server expects from an upstream endpoint, for checking API changes. url_kind is one of 'nws_points', 'nws_forecast', 'open_meteo', or 'alerts'.")]
+    async fn get_response_schema(
+        &self,
+        Parameters(request): Parameters<GetResponseSchemaRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        Ok(CallToolResult::success(vec![Content::text(
+            format_response_schema(&request.url_kind)?,
+        )]))
+    }
+
+    /// Gets the NWS forecast for a US ZIP code
+    #[tool(description = "Get the weather forecast for a US ZIP code. Provide a five-digit ZIP code (e.g., '10001' for New York). The ZIP is resolved to coordinates and forecast with the National Weather Service.")]
+    async fn get_forecast_by_zip(
+        &self,
+        Parameters(request): Parameters<GetForecastByZipRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let zip = request.zip.trim();
+        tracing::info!("Getting forecast for ZIP code: {}", zip);
+
+        if zip.len() != 5 || !zip.chars().all(|c| c.is_ascii_digit()) {
+            return Err(McpError::invalid_params(
+                format!("Invalid ZIP code '{}'. Expected five digits, e.g. '10001'.", zip),
+                None,
+            ));
+        }
+
+        let place = self
+            .geocode(zip, Some("US"))
+            .await
+            .map_err(|e| McpError::internal_error(format!("Failed to geocode ZIP: {}", e), None))?
+            .into_iter()
+            .find(|place| place.country_code.as_deref() == Some("US"))
+            .ok_or_else(|| {
+                McpError::invalid_params(format!("ZIP code '{}' was not found in the US.", zip), None)
+            })?;
+
+        let options = ForecastOptions::default();
+        let summary = self
+            .get_forecast_nws(place.latitude, place.longitude, &options)
+            .await
+            .map_err(mcp_error)?;
+        let mut contents = forecast_contents(summary, &options)?;
+        contents.insert(0, Content::text(format!("ZIP {}: {}", zip, format_place(&place))));
+        Ok(CallToolResult::success(contents))
+    }
+
+    /// Gets the forecast for a relative day such as "tomorrow"
+    #[tool(description = "Get the daily forecast for a relative time at any location. Provide latitude, longitude, and when: one of 'today', 'tomorrow', 'this weekend', or 'next 3 days' (relative to the location's local date); optional units ('metric', 'imperial', 'kelvin').")]
+    async fn get_forecast_relative(
+        &self,
+        Parameters(request): Parameters<GetRelativeForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting forecast for '{}' at coordinates: {}",
+            request.when,
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let forecast_options = ForecastOptions {
+            units: request.units.clone(),
+            ..ForecastOptions::default()
+        };
+        let options = format_options(&forecast_options, self.default_units)?;
+        let url = self.open_meteo_forecast_url(request.latitude, request.longitude, options.units);
+        let forecast = self
+            .fetch_open_meteo_forecast(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    format!("Failed to fetch Open-Meteo forecast: {}", e),
+                    None,
+                )
+            })?;
+
+        // Daily dates are local to the location, so the first one is its "today"
+        let dates: Vec<Option<NaiveDate>> = forecast
+            .daily
+            .time
+            .iter()
+            .map(|raw| NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok())
+            .collect();
+        let today = dates.first().copied().flatten().ok_or_else(|| {
+            McpError::internal_error("Open-Meteo returned no forecast days".to_string(), None)
+        })?;
+        let (start, end) = relative_date_range(&request.when, today).ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "Unrecognized when '{}'. Expected one of: {}",
+                    request.when,
+                    RELATIVE_DAYS.join(", ")
+                ),
+                None,
+            )
+        })?;
+
+        let mut formatted = format_open_meteo_forecast(forecast, options);
+        formatted.entries = formatted
+            .entries
+            .into_iter()
+            .zip(&dates)
+            .filter(|(_, date)| date.is_some_and(|date| (start..=end).contains(&date)))
+            .map(|(entry, _)| entry)
+            .collect();
+        if formatted.entries.is_empty() {
+            formatted
+                .entries
+                .push("No forecast data is available for that period.\n".to_string());
+        }
+
+        let formatted = finish_forecast(formatted, &forecast_options, OPEN_METEO_ATTRIBUTION);
+        Ok(CallToolResult::success(vec![Content::text(formatted.into_text())]))
+    }
+
+    /// Compares tomorrow's forecast across several weather models
+    #[tool(description = "Compare tomorrow's forecast high and precipitation across several weather models (ECMWF, GFS, ICON) for any location. Provide latitude and longitude. Reports each model's values, their spread, and an agreement score from 0 to 100. Values are metric.")]
+    async fn get_forecast_ensemble(
+        &self,
+        Parameters(request): Parameters<LocationRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        const MODELS: [(&str, &str); 3] = [
+            ("ecmwf_ifs025", "ECMWF IFS"),
+            ("gfs_seamless", "NOAA GFS"),
+            ("icon_seamless", "DWD ICON"),
+        ];
+        // Spreads at which the models count as fully disagreeing
+        const HIGH_TOLERANCE_C: f64 = 5.0;
+        const PRECIPITATION_TOLERANCE_MM: f64 = 10.0;
+
+        tracing::info!(
+            "Getting model agreement for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let urls = MODELS
+            .map(|(model, _)| self.model_forecast_url(request.latitude, request.longitude, model));
+        let (first, second, third) = tokio::join!(
+            self.make_request::<ModelForecastResponse>(&urls[0]),
+            self.make_request::<ModelForecastResponse>(&urls[1]),
+            self.make_request::<ModelForecastResponse>(&urls[2])
+        );
+
+        // Models are compared best-effort; one failing upstream shouldn't hide the rest
+        let mut date = None;
+        let mut outlooks = Vec::new();
+        for ((_, name), response) in MODELS.iter().zip([first, second, third]) {
+            let forecast = match response {
+                Ok(forecast) => forecast,
+                Err(e) => {
+                    tracing::warn!("Model {} unavailable: {}", name, e);
+                    continue;
+                }
+            };
+            // Index 1 is tomorrow in the location's local time
+            date = date.or_else(|| forecast.daily.time.get(1).cloned());
+            outlooks.push(ModelOutlook {
+                name,
+                high: forecast.daily.temperature_max.get(1).copied().flatten(),
+                precipitation: forecast.daily.precipitation_sum.get(1).copied().flatten(),
+            });
+        }
+
+        if outlooks.len() < 2 {
+            return Err(McpError::internal_error(
+                "Fewer than two weather models returned a forecast; try again later",
+                None,
+            ));
+        }
+
+        let highs: Vec<f64> = outlooks.iter().filter_map(|o| o.high).collect();
+        let precipitation: Vec<f64> = outlooks.iter().filter_map(|o| o.precipitation).collect();
+        let high_spread = if highs.len() >= 2 { spread(&highs) } else { None };
+        let precipitation_spread = if precipitation.len() >= 2 {
+            spread(&precipitation)
+        } else {
+            None
+        };
+        let spreads: Vec<(f64, f64)> = [
+            high_spread.map(|s| (s, HIGH_TOLERANCE_C)),
+            precipitation_spread.map(|s| (s, PRECIPITATION_TOLERANCE_MM)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Ok(CallToolResult::success(vec![Content::text(
+            format_model_agreement(
+                request.latitude,
+                request.longitude,
+                date.as_deref().unwrap_or("tomorrow"),
+                &outlooks,
+                high_spread,
+                precipitation_spread,
+                agreement_score(&spreads),
+            ),
+        )]))
+    }
+
+    /// Summarizes today's weather across a grid of points in a bounding box
+    #[tool(description = "Summarize today's weather across an area. Provide a bounding box (min_latitude, max_latitude, min_longitude, max_longitude; each side at most 5 degrees) and optionally resolution (2-5 points per side, default 3) and units. Returns the min, mean, and max of high and low temperature and precipitation across the grid.")]
+    async fn get_area_forecast(
+        &self,
+        Parameters(request): Parameters<GetAreaForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let points = area_grid(&request)?;
+        let units = parse_units(request.units.as_deref(), self.default_units)?;
+        tracing::info!("Getting area forecast for {} grid points", points.len());
+
+        let batch = self.with_budget(points.len());
+        let mut tasks = tokio::task::JoinSet::new();
+        for (latitude, longitude) in points.iter().copied() {
+            let weather = batch.clone();
+            let url = self.open_meteo_forecast_url(latitude, longitude, units);
+            tasks.spawn(async move { weather.fetch_open_meteo_forecast(&url).await });
+        }
+
+        // Points are sampled best-effort; the summary notes how many responded
+        let mut forecasts = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok(Ok(forecast)) => forecasts.push(forecast),
+                Ok(Err(e)) => tracing::warn!("Area grid point failed: {}", e),
+                Err(e) => tracing::warn!("Area grid task failed: {}", e),
+            }
+        }
+        if forecasts.is_empty() {
+            return Err(McpError::internal_error(
+                "Failed to fetch Open-Meteo forecasts for the area",
+                None,
+            ));
+        }
+
+        let today = |values: fn(&OpenMeteoResponse) -> &Vec<f64>| -> Vec<f64> {
+            forecasts.iter().filter_map(|f| values(f).first().copied()).collect()
+        };
+        let daily_units = forecasts[0].units_or_default(units);
+        let summaries = [
+            ("High", today(|f| &f.daily.temperature_max), true),
+            ("Low", today(|f| &f.daily.temperature_min), true),
+            ("Precipitation", today(|f| &f.daily.precipitation_sum), false),
+        ]
+        .into_iter()
+        .filter_map(|(label, values, is_temperature)| {
+            let (min, mean, max) = summarize(&values)?;
+            let describe = |value: f64| {
+                if is_temperature {
+                    format_temperature(value, &daily_units.temperature_max, units, 1)
+                } else {
+                    format!("{:.1} {}", value, daily_units.precipitation_sum)
+                }
+            };
+            Some(AreaSummary {
+                label,
+                min: describe(min),
+                mean: describe(mean),
+                max: describe(max),
+            })
+        })
+        .collect::<Vec<_>>();
+
+        Ok(CallToolResult::success(vec![Content::text(format_area_forecast(
+            &request,
+            forecasts[0].daily.time.first().map(String::as_str),
+            forecasts.len(),
+            points.len(),
+            &summaries,
+        ))]))
+    }
+
+    /// Assesses how the weather at both ends of a journey will affect travel
+    #[tool(description = "Estimate the weather impact on a road journey. Provide origin and destination coordinates, departure_time in UTC (YYYY-MM-DDTHH:MM, within 7 days), and optionally duration_hours. Samples the forecast at the origin around departure and at the destination around arrival, and rates each as clear, rain-slowed, or snow hazard.")]
+    async fn get_travel_impact(
+        &self,
+        Parameters(request): Parameters<GetTravelImpactRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // Average road speed used when no duration is given
+        const ESTIMATED_SPEED_KMH: f64 = 80.0;
+
+        let departure =
+            NaiveDateTime::parse_from_str(request.departure_time.trim(), "%Y-%m-%dT%H:%M")
+                .map_err(|_| {
+                    McpError::invalid_params(
+                        format!(
+                            "Invalid departure_time '{}'. Expected YYYY-MM-DDTHH:MM in UTC.",
+                            request.departure_time
+                        ),
+                        None,
+                    )
+                })?;
+        let origin = (request.origin_latitude, request.origin_longitude);
+        let destination = (request.destination_latitude, request.destination_longitude);
+        let (duration_hours, estimated) = match request.duration_hours {
+            Some(hours) if hours.is_finite() && hours >= 0.0 => (hours, false),
+            Some(hours) => {
+                return Err(McpError::invalid_params(
+                    format!("Invalid duration_hours {}. Expected a non-negative number.", hours),
+                    None,
+                ))
+            }
+            None => (great_circle_km(origin, destination) / ESTIMATED_SPEED_KMH, true),
+        };
+        let arrival = departure + Duration::minutes((duration_hours * 60.0).round() as i64);
+        tracing::info!(
+            "Assessing travel impact from {} to {} departing {}",
+            format_coords(origin.0, origin.1),
+            format_coords(destination.0, destination.1),
+            departure
+        );
+
+        let variables = "precipitation,weather_code";
+        let origin_url = self.open_meteo_hourly_utc_url(origin.0, origin.1, variables);
+        let destination_url =
+            self.open_meteo_hourly_utc_url(destination.0, destination.1, variables);
+        let (origin_forecast, destination_forecast) = tokio::join!(
+            self.make_request::<OpenMeteoHourlyResponse>(&origin_url),
+            self.make_request::<OpenMeteoHourlyResponse>(&destination_url)
+        );
+
+        let mut legs = Vec::new();
+        for (label, (latitude, longitude), time, forecast) in [
+            ("Origin", origin, departure, origin_forecast),
+            ("Destination", destination, arrival, destination_forecast),
+        ] {
+            let forecast = forecast.map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch hourly forecast: {}", e), None)
+            })?;
+            let hourly = &forecast.hourly;
+
+            // The hour before through the hour after, to absorb timing uncertainty
+            let samples: Vec<(Option<i32>, Option<f64>)> = hourly
+                .time
+                .iter()
+                .enumerate()
+                .filter(|(_, raw)| {
+                    NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M")
+                        .is_ok_and(|t| (t - time).num_minutes().abs() <= 90)
+                })
+                .map(|(i, _)| {
+                    (
+                        hourly.weather_code.get(i).copied().flatten(),
+                        hourly.precipitation.get(i).copied().flatten(),
+                    )
+                })
+                .collect();
+            if samples.is_empty() {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "No forecast is available for {} UTC; use a time within 7 days",
+                        time.format("%Y-%m-%d %H:%M")
+                    ),
+                    None,
+                ));
+            }
+
+            legs.push(TravelLeg {
+                label,
+                latitude,
+                longitude,
+                time,
+                impact: travel_impact(&samples),
+                weather_code: samples
+                    .iter()
+                    .filter_map(|(code, _)| *code)
+                    .max_by_key(|&code| weather_code_severity(code)),
+            });
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(format_travel_impact(
+            &legs,
+            duration_hours,
+            estimated,
+        ))]))
+    }
+
+    /// Gets a compact 48-hour precipitation timeline
+    #[tool(description = "Get a 48-hour rain timeline for any location. Provide latitude and longitude; optional units ('metric' or 'imperial'). Lists only the periods with meaningful precipitation, with likelihood, totals, and the heaviest hour.")]
+    async fn get_rain_timeline(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting rain timeline for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.open_meteo_hourly_url(
+            request.latitude,
+            request.longitude,
+            "precipitation,precipitation_probability",
+            48,
+            options.units,
+        );
+
+        let forecast = self
+            .make_request::<OpenMeteoHourlyResponse>(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch hourly forecast: {}", e), None)
+            })?;
+
+        // 0.1 mm/h is the smallest amount Open-Meteo reports; 50% is a coin flip
+        let blocks = precipitation_blocks(
+            &forecast.hourly.precipitation,
+            &forecast.hourly.precipitation_probability,
+            0.1,
+            50.0,
+        );
+
+        Ok(CallToolResult::success(vec![Content::text(
+            format_rain_timeline(&forecast, &blocks, options.time_format),
+        )]))
+    }
+
+    /// Gets the warmest and coldest hour of each forecast day
+    #[tool(description = "Get the warmest and coldest hour of each of the next 7 days at any location, e.g. to time heating or cooling. Provide latitude and longitude; optional units ('metric', 'imperial', 'kelvin'), date_format, and time_format ('24h' or '12h').")]
+    async fn get_daily_temperature_profile(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting daily temperature profile for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.open_meteo_hourly_days_url(
+            request.latitude,
+            request.longitude,
+            "temperature_2m",
+            7,
+            options.units,
+        );
+
+        let forecast = self
+            .make_request::<OpenMeteoHourlyResponse>(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch hourly forecast: {}", e), None)
+            })?;
+
+        // Local timestamps start with the calendar date
+        let days: Vec<&str> = forecast
+            .hourly
+            .time
+            .iter()
+            .map(|raw| raw.get(..10).unwrap_or(raw))
+            .collect();
+        let extremes = daily_extremes(&days, &forecast.hourly.temperature);
+
+        Ok(CallToolResult::success(vec![Content::text(
+            format_temperature_profile(&forecast, &extremes, options),
+        )]))
+    }
+
+    /// Answers whether it is raining at a location right now
+    #[tool(description = "Check whether it is raining right now at any location. Provide latitude and longitude. Returns a direct yes or no with the current conditions.")]
+    async fn is_it_raining(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Checking current rain for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.open_meteo_current_url(
+            request.latitude,
+            request.longitude,
+            "precipitation,weather_code,is_day",
+            options.units,
+        );
+
+        let current = self
+            .make_request::<OpenMeteoCurrentResponse>(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    format!("Failed to fetch current conditions: {}", e),
+                    None,
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            format_is_it_raining(&current),
+        )]))
+    }
+
+    /// Ranks several locations by their mean forecast high
+    #[tool(description = "Rank up to 10 named locations from warmest to coolest by mean daily high over a date window, e.g. to choose a travel destination. Provide locations (name, latitude, longitude); optional start_date and end_date (YYYY-MM-DD, within the next 7 days; default today), units ('metric', 'imperial', 'kelvin'), and coolest_first. Locations whose forecast can't be fetched are listed as unavailable.")]
+    async fn rank_locations_by_temperature(
+        &self,
+        Parameters(request): Parameters<RankLocationsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        check_location_count(request.locations.len())?;
+        let units = parse_units(request.units.as_deref(), self.default_units)?;
+        let parse_date = |raw: &str| {
+            NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").map_err(|_| {
+                McpError::invalid_params(
+                    format!("Invalid date '{}'. Expected YYYY-MM-DD.", raw),
+                    None,
+                )
+            })
+        };
+        let start = request.start_date.as_deref().map(parse_date).transpose()?;
+        let end = request.end_date.as_deref().map(parse_date).transpose()?.or(start);
+        if let (Some(start), Some(end)) = (start, end) {
+            if end < start {
+                return Err(McpError::invalid_params(
+                    "end_date must not be before start_date".to_string(),
+                    None,
+                ));
+            }
+        }
+        if let Some(end) = end {
+            self.check_forecast_days((end - Utc::now().date_naive()).num_days() + 1)?;
+        }
+        tracing::info!("Ranking {} locations by temperature", request.locations.len());
+
+        let batch = self.with_budget(request.locations.len());
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, location) in request.locations.iter().enumerate() {
+            let weather = batch.clone();
+            let url = self.open_meteo_forecast_url(location.latitude, location.longitude, units);
+            tasks.spawn(async move { (index, weather.fetch_open_meteo_forecast(&url).await) });
+        }
+        let mut results = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok(result) => results.push(result),
+                Err(e) => tracing::warn!("Ranking task failed: {}", e),
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+
+        let mut ranked = Vec::new();
+        let mut unavailable = Vec::new();
+        let mut unit = None;
+        for (index, result) in results {
+            let name = request.locations[index].name.clone();
+            let forecast = match result {
+                Ok(forecast) => forecast,
+                Err(e) => {
+                    tracing::warn!("Forecast for '{}' failed: {}", name, e);
+                    unavailable.push((name, "forecast unavailable".to_string()));
+                    continue;
+                }
+            };
+
+            // Without explicit dates the window is each location's own "today"
+            let highs: Vec<f64> = forecast
+                .daily
+                .time
+                .iter()
+                .zip(&forecast.daily.temperature_max)
+                .enumerate()
+                .filter(|(i, (raw, _))| {
+                    let date = NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok();
+                    match (start, end) {
+                        (Some(start), Some(end)) => {
+                            date.is_some_and(|date| (start..=end).contains(&date))
+                        }
+                        _ => *i == 0,
+                    }
+                })
+                .map(|(_, (_, high))| *high)
+                .collect();
+            match mean(&highs) {
+                Some(mean_high) => {
+                    unit.get_or_insert_with(|| forecast.units_or_default(units).temperature_max);
+                    ranked.push(RankedLocation {
+                        name,
+                        mean_high,
+                        days: highs.len(),
+                    });
+                }
+                None => unavailable.push((name, "no forecast for those dates".to_string())),
+            }
+        }
+        // Stable sort, so equal means keep the order they were given in
+        if request.coolest_first.unwrap_or(false) {
+            ranked.sort_by(|a, b| a.mean_high.total_cmp(&b.mean_high));
+        } else {
+            ranked.sort_by(|a, b| b.mean_high.total_cmp(&a.mean_high));
+        }
+
+        let window = match (start, end) {
+            (Some(start), Some(end)) if start == end => start.to_string(),
+            (Some(start), Some(end)) => format!("{} to {}", start, end),
+            _ => "today".to_string(),
+        };
+        let unit = unit.unwrap_or_else(|| DailyUnits::defaults(units).temperature_max);
+        Ok(CallToolResult::success(vec![Content::text(format_location_ranking(
+            &window,
+            &ranked,
+            &unavailable,
+            &unit,
+            units,
+        ))]))
+    }
+
+    /// Gets current conditions as one short sentence
+    #[tool(description = "Get the current weather at any location as exactly one short line for status bars and widgets, e.g. 'Berlin: 14°C, partly cloudy, light wind.' Provide latitude and longitude; optional name to lead the line with and units ('metric', 'imperial', 'kelvin').")]
+    async fn get_weather_oneliner(
+        &self,
+        Parameters(request): Parameters<GetOnelinerRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting weather one-liner for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let units = parse_units(request.units.as_deref(), self.default_units)?;
+        let url = self.open_meteo_current_url(
+            request.latitude,
+            request.longitude,
+            "temperature_2m,weather_code,wind_speed_10m",
+            units,
+        );
+        let current = self
+            .make_request::<OpenMeteoCurrentResponse>(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    format!("Failed to fetch current conditions: {}", e),
+                    None,
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_oneliner(
+            &current,
+            request.name.as_deref(),
+            units,
+        ))]))
+    }
+
+    /// Checks current conditions against "nice weather" limits
+    #[tool(description = "Check whether the weather right now is 'nice' by your own limits, and if not, find the first forecast day that is. Provide latitude and longitude; optional min_temperature, max_temperature, max_precipitation_probability (percent), max_wind_speed, and units ('metric', 'imperial', 'kelvin'), which also sets the units of the limits. Defaults: 18-28°C, 20% precipitation chance, 25 km/h wind. Rain, snow, and other adverse weather never count as nice.")]
+    async fn check_nice_weather(
+        &self,
+        Parameters(request): Parameters<CheckNiceWeatherRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Checking nice weather for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let units = parse_units(request.units.as_deref(), self.default_units)?;
+        let (min_temperature, max_temperature, max_wind_speed) = match units {
+            Units::Imperial => (64.0, 82.0, 16.0),
+            Units::Metric | Units::Kelvin => (18.0, 28.0, 25.0),
+        };
+        // Kelvin output is converted from Celsius data, so compare limits in Celsius
+        let temperature_limit = |value: f64| match units {
+            Units::Kelvin => value - 273.15,
+            Units::Metric | Units::Imperial => value,
+        };
+        let limits = NiceWeather {
+            min_temperature: request
+                .min_temperature
+                .map_or(min_temperature, temperature_limit),
+            max_temperature: request
+                .max_temperature
+                .map_or(max_temperature, temperature_limit),
+            max_precipitation_probability: request.max_precipitation_probability.unwrap_or(20.0),
+            max_wind_speed: request.max_wind_speed.unwrap_or(max_wind_speed),
+        };
+        if limits.min_temperature > limits.max_temperature {
+            return Err(McpError::invalid_params(
+                "min_temperature must not be above max_temperature".to_string(),
+                None,
+            ));
+        }
+
+        let current_url = self.open_meteo_current_url(
+            request.latitude,
+            request.longitude,
+            "temperature_2m,weather_code,wind_speed_10m",
+            units,
+        );
+        let forecast_url = self.open_meteo_forecast_url(request.latitude, request.longitude, units);
+        let (current, forecast) = tokio::join!(
+            self.make_request::<OpenMeteoCurrentResponse>(&current_url),
+            self.fetch_open_meteo_forecast(&forecast_url)
+        );
+        let current = current.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch current conditions: {}", e), None)
+        })?;
+        let forecast = forecast.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch Open-Meteo forecast: {}", e), None)
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_nice_weather(
+            &current, &forecast, &limits, units,
+        ))]))
+    }
+
+    /// Rates tonight's stargazing conditions from cloud cover and moonlight
+    #[tool(description = "Get tonight's stargazing conditions for any location. Provide latitude and longitude. Returns hourly cloud cover from 8pm to 6am, the clearest window, moon illumination, and an overall Good/Fair/Poor rating.")]
+    async fn get_stargazing_conditions(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        // Hours at or below this cloud cover count as clear
+        const CLEAR_SKY_MAX_COVER: f64 = 30.0;
+
+        tracing::info!(
+            "Getting stargazing conditions for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+
+        let url = self.open_meteo_hourly_url(
+            request.latitude,
+            request.longitude,
+            "cloud_cover",
+            36,
+            Units::default(),
+        );
+        let forecast = self
+            .make_request::<OpenMeteoHourlyResponse>(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch hourly forecast: {}", e), None)
+            })?;
+
+        // Tonight is the first run of hours from 20:00 to 05:00 local time, which is
+        // the current night when called after dark
+        let times: Vec<Option<NaiveDateTime>> = forecast
+            .hourly
+            .time
+            .iter()
+            .map(|raw| NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M").ok())
+            .collect();
+        let is_night = |i: usize| times[i].is_some_and(|t| t.hour() >= 20 || t.hour() < 6);
+        let night_start = (0..times.len()).find(|&i| is_night(i)).ok_or_else(|| {
+            McpError::internal_error("Open-Meteo returned no night-time hours", None)
+        })?;
+        let night_end = (night_start..times.len())
+            .take_while(|&i| is_night(i))
+            .last()
+            .unwrap_or(night_start);
+
+        let night_cover = forecast
+            .hourly
+            .cloud_cover
+            .get(night_start..=night_end)
+            .unwrap_or(&[]);
+        let clear_window = longest_run_at_most(night_cover, CLEAR_SKY_MAX_COVER)
+            .map(|(start, end)| (start + night_start, end + night_start));
+
+        let night_start_utc = times[night_start]
+            .map(|t| t - Duration::seconds(forecast.utc_offset_seconds))
+            .unwrap_or_else(|| Utc::now().naive_utc());
+        let illumination = moon_illumination(night_start_utc);
+        let clear_hours = clear_window.map_or(0, |(start, end)| end - start + 1);
+        let rating = stargazing_rating(clear_hours, illumination);
+
+        Ok(CallToolResult::success(vec![Content::text(format_stargazing(
+            &forecast,
+            (night_start, night_end),
+            clear_window,
+            illumination,
+            rating,
+            options.time_format,
+        ))]))
+    }
+
+    /// Calculates sun times for any date without an API call
+    #[tool(description = "Calculate sunrise, sunset, solar noon, and day length for any location and date, including dates beyond the forecast window. Provide latitude, longitude, date (YYYY-MM-DD), and optionally utc_offset in hours for local times (default UTC).")]
+    async fn get_sun_times(
+        &self,
+        Parameters(request): Parameters<GetSunTimesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Calculating sun times for {} at coordinates: {}",
+            request.date,
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let date = NaiveDate::parse_from_str(request.date.trim(), "%Y-%m-%d").map_err(|_| {
+            McpError::invalid_params(
+                format!("Invalid date '{}'. Expected YYYY-MM-DD.", request.date),
+                None,
+            )
+        })?;
+        let utc_offset = request.utc_offset.unwrap_or(0.0);
+        if !(-12.0..=14.0).contains(&utc_offset) {
+            return Err(McpError::invalid_params(
+                format!("Invalid utc_offset {}. Expected hours between -12 and 14.", utc_offset),
+                None,
+            ));
+        }
+
+        let time_format = parse_time_format(request.time_format.as_deref())?;
+        let times = sun_times(request.latitude, request.longitude, date);
+        Ok(CallToolResult::success(vec![Content::text(format_sun_times(
+            request.latitude,
+            request.longitude,
+            date,
+            utc_offset,
+            &times,
+            time_format,
+        ))]))
+    }
+
+    /// Gets the pollen forecast for a European location
+    #[tool(description = "Get the pollen forecast (alder, birch, grass, ragweed) for a location in Europe. Provide latitude and longitude. Reports current and peak levels with a Low/Moderate/High category. Pollen data is only available for Europe.")]
+    async fn get_pollen_forecast(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting pollen forecast for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let pollen = self
+            .make_request::<PollenResponse>(&self.pollen_url(request.latitude, request.longitude))
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch pollen forecast: {}", e), None)
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_pollen(
+            pollen,
+            options.time_format,
+        ))]))
+    }
+
+    /// Compares the current NWS forecast period with the nearest station's observation
+    #[tool(description = "Compare the NWS forecast temperature for the current period with the latest observation from the nearest weather station (US only). Provide latitude and longitude. Reports both temperatures in °F and how far apart they are.")]
+    async fn get_forecast_accuracy(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting forecast accuracy for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        if !Self::is_us_location(request.latitude, request.longitude) {
+            return Err(McpError::invalid_params(
+                "Forecast accuracy needs NWS observations, which only cover US locations.",
+                None,
+            ));
+        }
+
+        let points = self
+            .fetch_nws_points(request.latitude, request.longitude)
+            .await?;
+        let forecast_url =
+            self.gridpoint_forecast_url(&points.grid_id, points.grid_x, points.grid_y);
+        let stations_url =
+            self.gridpoint_stations_url(&points.grid_id, points.grid_x, points.grid_y);
+        let (forecast, stations) = tokio::join!(
+            self.make_request::<ForecastResponse>(&forecast_url),
+            self.make_request::<StationsResponse>(&stations_url)
+        );
+        let forecast = forecast.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch forecast: {}", e), None)
+        })?;
+        let stations = stations.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch observation stations: {}", e), None)
+        })?;
+
+        let period = forecast.properties.periods.first().ok_or_else(|| {
+            McpError::internal_error("NWS returned no forecast periods".to_string(), None)
+        })?;
+        let station = stations
+            .features
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                McpError::internal_error(
+                    "No observation stations found near this location".to_string(),
+                    None,
+                )
+            })?
+            .properties;
+
+        let observation_url = self.latest_observation_url(&station.station_identifier);
+        let observation = self
+            .make_request::<ObservationResponse>(&observation_url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch latest observation: {}", e), None)
+            })?
+            .properties;
+
+        Ok(CallToolResult::success(vec![Content::text(format_forecast_accuracy(
+            period,
+            &station,
+            &observation,
+            request.latitude,
+            request.longitude,
+        ))]))
+    }
+
+    /// Compares today's temperatures with the same date in previous years
+    #[tool(description = "Report how today's forecast high and low compare to the historical average for the same date over the last 10 years (e.g. '+4.2°C above normal'). Provide latitude and longitude; optional units ('metric', 'imperial', 'kelvin').")]
+    async fn get_temperature_anomaly(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        const YEARS: u32 = 10;
+
+        tracing::info!(
+            "Getting temperature anomaly for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+
+        // The local date isn't known until the forecast returns, so pad the range by a
+        // day on each side to cover locations on the other side of the date line
+        let today = Utc::now().date_naive();
+        let start = today - Months::new(12 * YEARS) - Duration::days(1);
+        let end = today - Months::new(12) + Duration::days(1);
+        self.check_archive_days((end - start).num_days() + 1)?;
+
+        let forecast_url =
+            self.open_meteo_forecast_url(request.latitude, request.longitude, options.units);
+        let (forecast, archive) = tokio::join!(
+            self.fetch_open_meteo_forecast(&forecast_url),
+            self.fetch_archive(
+                request.latitude,
+                request.longitude,
+                (start, end),
+                DAILY_TEMPERATURE_FIELDS,
+                options.units,
+            )
+        );
+        let forecast = forecast.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch Open-Meteo forecast: {}", e), None)
+        })?;
+        let archive = archive.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch historical weather: {}", e), None)
+        })?;
+
+        let (Some(date), Some(&high), Some(&low)) = (
+            forecast.daily.time.first(),
+            forecast.daily.temperature_max.first(),
+            forecast.daily.temperature_min.first(),
+        ) else {
+            return Err(McpError::internal_error(
+                "Open-Meteo returned no forecast for today",
+                None,
+            ));
+        };
+        let local_date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+            McpError::internal_error(format!("Unexpected forecast date '{}': {}", date, e), None)
+        })?;
+
+        let same_day = |values: &[Option<f64>]| -> Vec<f64> {
+            archive
+                .daily
+                .time
+                .iter()
+                .zip(values)
+                .filter_map(|(time, value)| {
+                    let day = NaiveDate::parse_from_str(time, "%Y-%m-%d").ok()?;
+                    if day.month() == local_date.month() && day.day() == local_date.day() {
+                        *value
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        };
+        let history_high = same_day(&archive.daily.temperature_max);
+        let history_low = same_day(&archive.daily.temperature_min);
+
+        let anomalies: Vec<TemperatureAnomaly> = [
+            ("High", high, &history_high),
+            ("Low", low, &history_low),
+        ]
+        .into_iter()
+        .filter_map(|(label, value, history)| {
+            let (normal, anomaly) = anomaly(value, history)?;
+            Some(TemperatureAnomaly {
+                label,
+                value,
+                normal,
+                anomaly,
+            })
+        })
+        .collect();
+
+        if anomalies.is_empty() {
+            return Err(McpError::internal_error(
+                "No historical data is available for this location and date",
+                None,
+            ));
+        }
+
+        let formatted = format_temperature_anomaly(
+            request.latitude,
+            request.longitude,
+            date,
+            history_high.len(),
+            &forecast.units_or_default(options.units).temperature_max,
+            options.units,
+            &anomalies,
+        );
+
+        Ok(CallToolResult::success(vec![Content::text(formatted)]))
+    }
+
+    /// Estimates how much snow clearing the coming week needs
+    #[tool(description = "Get the snow outlook for the coming week at any location: daily snowfall, the total, and whether the heaviest day calls for shoveling (e.g. 'Significant snow Thu Jan 15 — plan to shovel'). Provide latitude and longitude; optional units ('metric' for cm, 'imperial' for inches) and date_format.")]
+    async fn get_snow_outlook(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting snow outlook for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.open_meteo_daily_url(
+            request.latitude,
+            request.longitude,
+            "snowfall_sum",
+            options.units,
+        );
+        let forecast = self.make_request::<SnowfallResponse>(&url).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch snowfall forecast: {}", e), None)
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_snow_outlook(
+            &forecast, options,
+        ))]))
+    }
+
+    /// Reports current wind for a kite or sail sport
+    #[tool(description = "Get the current wind for kite and sail sports at any location: sustained speed, gusts, direction, the gust factor (gust / sustained), and whether it suits the sport. Provide latitude, longitude, and sport ('kitesurfing' 12-25 kn, 'windsurfing' 15-30 kn, or 'sailing' 8-20 kn); optional wind_unit ('kn' default, 'kmh', 'mph', 'ms'). A gust factor above 1.4 counts as too gusty.")]
+    async fn get_wind_conditions(
+        &self,
+        Parameters(request): Parameters<GetWindConditionsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting {} wind conditions for coordinates: {}",
+            request.sport,
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let sport = WindSport::parse(&request.sport).ok_or_else(|| {
+            let names: Vec<&str> = WindSport::ALL.iter().map(|sport| sport.name()).collect();
+            McpError::invalid_params(
+                format!(
+                    "Unknown sport '{}'. Expected one of: {}.",
+                    request.sport,
+                    names.join(", ")
+                ),
+                None,
+            )
+        })?;
+        let unit = match request.wind_unit.as_deref() {
+            None => SpeedUnit::default(),
+            Some(value) => SpeedUnit::parse(value.trim()).ok_or_else(|| {
+                McpError::invalid_params(
+                    format!("Invalid wind_unit '{}'. Expected 'kn', 'kmh', 'mph', or 'ms'.", value),
+                    None,
+                )
+            })?,
+        };
+
+        let url = self.wind_url(request.latitude, request.longitude, unit);
+        let current = self
+            .make_request::<OpenMeteoCurrentResponse>(&url)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to fetch current wind: {}", e), None)
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_wind_conditions(
+            &current, sport, unit,
+        ))]))
+    }
+
+    /// Combines the alerts at a US point with its near-term forecast hazards
+    #[tool(description = "Get a prioritized severe weather safety briefing for a US location. Provide latitude and longitude. Lists the active NWS alerts for the point, most severe first, with their recommended actions, then hazards in the next two days of forecast (high wind, thunderstorms, heavy rain or snow, freezing rain, sleet). Reports 'No active hazards' when there are none.")]
+    async fn get_safety_briefing(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting safety briefing for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        if !Self::is_us_location(request.latitude, request.longitude) {
+            return Err(McpError::invalid_params(
+                "Safety briefings use NWS alerts and are only available for US locations."
+                    .to_string(),
+                None,
+            ));
+        }
+
+        let alerts_url = self.point_alerts_url(request.latitude, request.longitude);
+        let (forecast, alerts) = tokio::join!(
+            self.fetch_nws_forecast(request.latitude, request.longitude),
+            self.make_request::<AlertResponse>(&alerts_url)
+        );
+        let forecast = forecast?;
+        let alerts = alerts.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch alerts: {}", e), None)
+        })?;
+
+        let periods = &forecast.properties.periods;
+        let periods = &periods[..periods.len().min(SAFETY_BRIEFING_PERIODS)];
+        Ok(CallToolResult::success(vec![Content::text(format_safety_briefing(
+            request.latitude,
+            request.longitude,
+            alerts,
+            periods,
+        ))]))
+    }
+
+    /// Compares today's forecast high with yesterday's high
+    #[tool(description = "Answer 'is it warmer than yesterday?' for any location: today's forecast high against yesterday's high, e.g. '2.0°C warmer than yesterday.' Provide latitude and longitude; optional units ('metric', 'imperial', 'kelvin'). Yesterday's high is provisional, from recent model data, since the historical archive lags a few days.")]
+    async fn compare_to_yesterday(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Comparing today to yesterday for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        // One request returns both local days: yesterday first, then today
+        let url = self.open_meteo_past_days_url(
+            request.latitude,
+            request.longitude,
+            "temperature_2m_max",
+            1,
+            options.units,
+        );
+        let response = self.make_request::<ArchiveResponse>(&url).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch daily highs: {}", e), None)
+        })?;
+
+        let highs = &response.daily.temperature_max;
+        let high = |i: usize| highs.get(i).copied().flatten();
+        let unit = match options.units {
+            Units::Imperial => "°F",
+            Units::Metric | Units::Kelvin => "°C",
+        };
+        Ok(CallToolResult::success(vec![Content::text(format_yesterday_comparison(
+            request.latitude,
+            request.longitude,
+            (high(0), high(1)),
+            unit,
+            options.units,
+        ))]))
+    }
+
+    /// Scores the coming week for an outdoor activity
+    #[tool(description = "Get the coming week's forecast scored for an outdoor activity: running, cycling, beach, skiing, or gardening. Each day gets a 0-100 score from the activity's own preferences (e.g. skiing wants fresh snow and cold, beach wants sun and warmth), the conditions that matter for it, and what holds it back, followed by the best day. Provide latitude, longitude, and activity; optional units ('metric', 'imperial', 'kelvin') and date_format.")]
+    async fn get_activity_forecast(
+        &self,
+        Parameters(request): Parameters<GetActivityForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting {} forecast for coordinates: {}",
+            request.activity,
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let activity = Activity::parse(&request.activity).ok_or_else(|| {
+            let names: Vec<&str> = Activity::ALL.iter().map(|activity| activity.name()).collect();
+            McpError::invalid_params(
+                format!(
+                    "Unknown activity '{}'. Expected one of: {}.",
+                    request.activity,
+                    names.join(", ")
+                ),
+                None,
+            )
+        })?;
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.open_meteo_daily_url(
+            request.latitude,
+            request.longitude,
+            ACTIVITY_DAILY_FIELDS,
+            options.units,
+        );
+        let forecast = self.make_request::<ActivityResponse>(&url).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch forecast: {}", e), None)
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_activity_forecast(
+            &forecast, activity, options,
+        ))]))
+    }
+
+    /// Summarizes whether the coming days are warming or cooling, and wetter or drier
+    #[tool(description = "Get the at-a-glance weather trend for the coming week at any location: whether daily highs are warming, cooling, or steady (e.g. 'warming ~2.0°C/day'), and whether precipitation is getting wetter or drier. Provide latitude and longitude; optional units ('metric', 'imperial', 'kelvin') and date_format.")]
+    async fn get_weather_trend(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting weather trend for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.open_meteo_forecast_url(request.latitude, request.longitude, options.units);
+        let forecast = self.fetch_open_meteo_forecast(&url).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch Open-Meteo forecast: {}", e), None)
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_weather_trend(
+            &forecast, options,
+        ))]))
+    }
+
+    /// Reports each day's likely temperature range from an ensemble forecast
+    #[tool(description = "Get a 7-day forecast with uncertainty ranges at any location: each day's median high and low across the ECMWF ensemble members, plus the 25th-75th percentile range, e.g. 'High 18°C, likely 15–21°C'. Provide latitude and longitude; optional units ('metric', 'imperial', 'kelvin') and date_format.")]
+    async fn get_forecast_percentiles(
+        &self,
+        Parameters(request): Parameters<GetForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        const MODEL: (&str, &str) = ("ecmwf_ifs025", "ECMWF IFS");
+
+        tracing::info!(
+            "Getting forecast percentiles for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let options = format_options(&request.options, self.default_units)?;
+        let url = self.ensemble_url(request.latitude, request.longitude, MODEL.0, options.units);
+        let ensemble = self.make_request::<EnsembleResponse>(&url).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch ensemble forecast: {}", e), None)
+        })?;
+
+        // Each member's daily high and low, gathered per local date
+        let hourly = &ensemble.hourly;
+        let dates: Vec<&str> = hourly.time.iter().map(|raw| raw.get(..10).unwrap_or(raw)).collect();
+        let mut by_date: BTreeMap<&str, (Vec<f64>, Vec<f64>)> = BTreeMap::new();
+        for (name, values) in &hourly.members {
+            if !name.starts_with("temperature_2m") {
+                continue;
+            }
+            for day in daily_extremes(&dates, values) {
+                let (highs, lows) = by_date.entry(dates[day.max]).or_default();
+                highs.extend(values[day.max]);
+                lows.extend(values[day.min]);
+            }
+        }
+        let days: Vec<DayQuartiles> = by_date
+            .into_iter()
+            .filter_map(|(date, (highs, lows))| {
+                Some(DayQuartiles {
+                    date: date.to_string(),
+                    high: quartiles(&highs)?,
+                    low: quartiles(&lows)?,
+                    members: highs.len(),
+                })
+            })
+            .collect();
+
+        let unit = ensemble
+            .hourly_units
+            .get("temperature_2m")
+            .map(String::as_str)
+            .unwrap_or("\u{00b0}C");
+        Ok(CallToolResult::success(vec![Content::text(format_forecast_quartiles(
+            ensemble.latitude,
+            ensemble.longitude,
+            MODEL.1,
+            unit,
+            &days,
+            options,
+        ))]))
+    }
+
+    /// Reports how the forecast has changed since an earlier snapshot
+    #[tool(description = "Show how the forecast has changed since an earlier check, e.g. 'high 18.0°C → 21.0°C (revised up 3.0)'. Provide previous: the JSON returned by get_forecast with format 'arrays', unchanged. The current forecast is fetched for the same location and units, and each day both cover is compared for high, low, and precipitation.")]
+    async fn diff_forecast(
+        &self,
+        Parameters(request): Parameters<DiffForecastRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let previous = request.previous;
+        tracing::info!(
+            "Diffing forecast for coordinates: {}",
+            format_coords(previous.latitude, previous.longitude)
+        );
+
+        let units = previous.units.units();
+        let url = self.open_meteo_forecast_url(previous.latitude, previous.longitude, units);
+        let forecast = self.fetch_open_meteo_forecast(&url).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch Open-Meteo forecast: {}", e), None)
+        })?;
+        let current: ForecastArrays = serde_json::from_value(open_meteo_arrays(&forecast, units))
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to build forecast arrays: {}", e), None)
+            })?;
+
+        let changes = diff_forecast_arrays(&previous, &current);
+        Ok(CallToolResult::success(vec![Content::text(format_forecast_diff(
+            &previous,
+            &current,
+            &changes,
+            DateFormat::default(),
+        ))]))
+    }
+
+    /// Scores a date's weather for an outdoor event
+    #[tool(description = "Score how good the weather looks for an outdoor event (e.g. a wedding) on a given date, 0-100, with a breakdown: temperature (40 points, best 18-26°C), precipitation (40, best dry), and wind (20, best under 15 km/h). Provide latitude, longitude, and date (YYYY-MM-DD, today or later); optional units ('metric', 'imperial', 'kelvin'). Dates within the forecast use it; later dates use the average of the same week over the past 10 years, and the source is labeled.")]
+    async fn get_event_weather_score(
+        &self,
+        Parameters(request): Parameters<GetEventScoreRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Scoring event weather for {} at coordinates: {}",
+            request.date,
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let units = parse_units(request.units.as_deref(), self.default_units)?;
+        let date = NaiveDate::parse_from_str(request.date.trim(), "%Y-%m-%d").map_err(|_| {
+            McpError::invalid_params(
+                format!("Invalid date '{}'. Expected YYYY-MM-DD.", request.date),
+                None,
+            )
+        })?;
+        let today = Utc::now().date_naive();
+        if date < today {
+            return Err(McpError::invalid_params(
+                "date must be today or later".to_string(),
+                None,
+            ));
+        }
+
+        let forecast_days = OPEN_METEO_MAX_FORECAST_DAYS.min(self.max_forecast_days) as i64;
+        let near = (date - today).num_days() < forecast_days;
+        // The date's anniversary in another year, moving February 29 to the 28th
+        let anniversary = |year: i32| {
+            date.with_year(year)
+                .or_else(|| date.pred_opt()?.with_year(year))
+                .unwrap_or(date)
+        };
+        let response = if near {
+            let url = self.forecast_range_url(
+                request.latitude,
+                request.longitude,
+                (date, date),
+                EVENT_DAILY_FIELDS,
+                units,
+            );
+            self.make_request::<ArchiveResponse>(&url).await
+        } else {
+            let window = Duration::days(EVENT_CLIMATOLOGY_WINDOW_DAYS);
+            let start = anniversary(date.year() - EVENT_CLIMATOLOGY_YEARS) - window;
+            let end = (anniversary(date.year() - 1) + window)
+                .min(today - Duration::days(ARCHIVE_DELAY_DAYS));
+            self.check_archive_days((end - start).num_days() + 1)?;
+            self.fetch_archive(
+                request.latitude,
+                request.longitude,
+                (start, end),
+                EVENT_DAILY_FIELDS,
+                units,
+            )
+            .await
+        }
+        .map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch daily weather: {}", e), None)
+        })?;
+        let daily = &response.daily;
+
+        // For climatology, keep days within the window around the date in each year,
+        // including windows that cross New Year
+        let in_window = |raw: &str| {
+            if near {
+                return true;
+            }
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d").is_ok_and(|day| {
+                (day.year() - 1..=day.year() + 1).any(|year| {
+                    (day - anniversary(year)).num_days().abs() <= EVENT_CLIMATOLOGY_WINDOW_DAYS
+                })
+            })
+        };
+        let to_metric = |high: f64, precipitation: f64, wind: f64| match units {
+            Units::Imperial => {
+                (fahrenheit_to_celsius(high), precipitation * 25.4, mph_to_kmh(wind))
+            }
+            Units::Metric | Units::Kelvin => (high, precipitation, wind),
+        };
+        let samples: Vec<(EventConditions, EventScore)> = (0..daily.time.len())
+            .filter(|&i| in_window(&daily.time[i]))
+            .filter_map(|i| {
+                let conditions = EventConditions {
+                    high: daily.temperature_max.get(i).copied().flatten()?,
+                    precipitation: daily.precipitation_sum.get(i).copied().flatten()?,
+                    wind_speed: daily.wind_speed_max.get(i).copied().flatten()?,
+                };
+                let (high, precipitation, wind) = to_metric(
+                    conditions.high,
+                    conditions.precipitation,
+                    conditions.wind_speed,
+                );
+                Some((conditions, event_score(high, precipitation, wind)))
+            })
+            .collect();
+        if samples.is_empty() {
+            return Err(McpError::internal_error(
+                "No weather data is available for this location and date",
+                None,
+            ));
+        }
+
+        // A forecast has a single sample, so averaging only changes climatology
+        let average = |value: fn(&(EventConditions, EventScore)) -> f64| {
+            samples.iter().map(value).sum::<f64>() / samples.len() as f64
+        };
+        let conditions = EventConditions {
+            high: average(|(c, _)| c.high),
+            precipitation: average(|(c, _)| c.precipitation),
+            wind_speed: average(|(c, _)| c.wind_speed),
+        };
+        let score = EventScore {
+            temperature: average(|(_, s)| s.temperature),
+            precipitation: average(|(_, s)| s.precipitation),
+            wind: average(|(_, s)| s.wind),
+        };
+        let source = if near {
+            "Forecast (Open-Meteo)".to_string()
+        } else {
+            format!(
+                "Climatology \u{2014} average of {} days within {} days of this date over the past {} years (Open-Meteo archive); not a forecast",
+                samples.len(),
+                EVENT_CLIMATOLOGY_WINDOW_DAYS,
+                EVENT_CLIMATOLOGY_YEARS
+            )
+        };
+
+        let labels = DailyUnits::defaults(units);
+        Ok(CallToolResult::success(vec![Content::text(format_event_score(
+            request.latitude,
+            request.longitude,
+            &date.format("%Y-%m-%d").to_string(),
+            &source,
+            &score,
+            &conditions,
+            (
+                &labels.temperature_max,
+                &labels.precipitation_sum,
+                &labels.wind_speed_max,
+            ),
+            units,
+        ))]))
+    }
+
+    /// Computes heating and cooling degree days over a date range
+    #[tool(description = "Compute heating degree days (HDD) and cooling degree days (CDD) per day and cumulatively over a date range, e.g. to track building energy use. Provide latitude, longitude, start_date and end_date (YYYY-MM-DD, up to 366 days, past or within the forecast); optional base_temperature (default 18°C, 65°F, or 291.15 K) and units ('metric', 'imperial', 'kelvin'). Recent and future days come from the forecast, older ones from the historical archive.")]
+    async fn get_degree_days(
+        &self,
+        Parameters(request): Parameters<GetDegreeDaysRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "Getting degree days for coordinates: {}",
+            format_coords(request.latitude, request.longitude)
+        );
+
+        let units = parse_units(request.units.as_deref(), self.default_units)?;
+        let parse_date = |raw: &str| {
+            NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d").map_err(|_| {
+                McpError::invalid_params(
+                    format!("Invalid date '{}'. Expected YYYY-MM-DD.", raw),
+                    None,
+                )
+            })
+        };
+        let start = parse_date(&request.start_date)?;
+        let end = parse_date(&request.end_date)?;
+        let days = (end - start).num_days() + 1;
+        if days < 1 {
+            return Err(McpError::invalid_params(
+                "end_date must not be before start_date".to_string(),
+                None,
+            ));
+        }
+        if days > MAX_DEGREE_DAY_RANGE {
+            return Err(McpError::invalid_params(
+                format!("The range may span at most {} days.", MAX_DEGREE_DAY_RANGE),
+                None,
+            ));
+        }
+
+        // The base arrives in the requested units, but Kelvin data comes back in Celsius
+        let base = match (units, request.base_temperature) {
+            (_, Some(base)) if !base.is_finite() => {
+                return Err(McpError::invalid_params(
+                    "base_temperature must be a number".to_string(),
+                    None,
+                ));
+            }
+            (Units::Metric, base) => base.unwrap_or(18.0),
+            (Units::Imperial, base) => base.unwrap_or(65.0),
+            (Units::Kelvin, base) => base.map(|k| k - 273.15).unwrap_or(18.0),
+        };
+        let base_celsius = match units {
+            Units::Imperial => fahrenheit_to_celsius(base),
+            Units::Metric | Units::Kelvin => base,
+        };
+        if !(-30.0..=40.0).contains(&base_celsius) {
+            return Err(McpError::invalid_params(
+                "base_temperature must be between -30°C and 40°C (-22°F to 104°F)".to_string(),
+                None,
+            ));
+        }
+
+        // The archive lags a few days and the forecast API only reaches back about three
+        // months, so a range must fit entirely within one of them
+        let today = Utc::now().date_naive();
+        let response = if end <= today - Duration::days(ARCHIVE_DELAY_DAYS) {
+            self.check_archive_days(days)?;
+            self.fetch_archive(
+                request.latitude,
+                request.longitude,
+                (start, end),
+                DAILY_TEMPERATURE_FIELDS,
+                units,
+            )
+            .await
+        } else if start >= today - Duration::days(FORECAST_PAST_DAYS) {
+            self.check_forecast_days((end - today).num_days() + 1)?;
+            let url = self.forecast_range_url(
+                request.latitude,
+                request.longitude,
+                (start, end),
+                DAILY_TEMPERATURE_FIELDS,
+                units,
+            );
+            self.make_request::<ArchiveResponse>(&url).await
+        } else {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Ranges starting more than {} days ago must end at least {} days ago; split the request.",
+                    FORECAST_PAST_DAYS, ARCHIVE_DELAY_DAYS
+                ),
+                None,
+            ));
+        }
+        .map_err(|e| {
+            McpError::internal_error(format!("Failed to fetch daily temperatures: {}", e), None)
+        })?;
+        let daily = &response.daily;
+        let days: Vec<(String, Option<DegreeDays>)> = daily
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, date)| {
+                let high = daily.temperature_max.get(i).copied().flatten();
+                let low = daily.temperature_min.get(i).copied().flatten();
+                let day = high.zip(low).map(|(high, low)| degree_days(high, low, base));
+                (date.clone(), day)
+            })
+            .collect();
+
+        let unit = if units == Units::Imperial { "\u{00b0}F" } else { "\u{00b0}C" };
+        Ok(CallToolResult::success(vec![Content::text(format_degree_days(
+            request.latitude,
+            request.longitude,
+            base,
+            unit,
+            units,
+            &days,
+        ))]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::{BATCH_CONCURRENCY, BATCH_REQUESTS_PER_ITEM};
+    use crate::test_support::{MockResponse, MockServer, RecordingSleeper};
+
+    /// A service with every upstream pointed at the mock server. The upstreams share
+    /// one circuit breaker, named for the last of them; use `isolated_weather` when a
+    /// test checks which upstream an error or breaker belongs to.
+    fn mock_weather(server: &MockServer) -> Weather {
+        let base = server.url().to_string();
+        let mut weather = Weather {
+            nws_base: base.clone(),
+            open_meteo_base: base.clone(),
+            air_quality_base: base.clone(),
+            ensemble_base: base.clone(),
+            geocoding_base: base.clone(),
+            nominatim_base: base.clone(),
+            archive_base: base,
+            sleeper: Arc::new(RecordingSleeper::default()),
+            ..Weather::new().unwrap()
+        };
+        weather.breakers = Arc::new(weather.upstream_breakers());
+        weather
+    }
+
+    /// A weather service with each upstream on its own mock server, so that errors
+    /// and circuit breakers can be told apart; servers are paired with the upstream
+    /// name their errors carry
+    async fn isolated_weather() -> (Weather, Vec<(&'static str, MockServer)>) {
+        let mut upstreams = Vec::new();
+        for name in [
+            "NWS",
+            "Open-Meteo",
+            "Open-Meteo air quality",
+            "Open-Meteo ensemble",
+            "Open-Meteo geocoding",
+            "Nominatim",
+            "Open-Meteo archive",
+        ] {
+            upstreams.push((name, MockServer::start().await));
+        }
+        let base = |index: usize| upstreams[index].1.url().to_string();
+        let mut weather = Weather {
+            nws_base: base(0),
+            open_meteo_base: base(1),
+            air_quality_base: base(2),
+            ensemble_base: base(3),
+            geocoding_base: base(4),
+            nominatim_base: base(5),
+            archive_base: base(6),
+            sleeper: Arc::new(RecordingSleeper::default()),
+            ..Weather::new().unwrap()
+        };
+        weather.breakers = Arc::new(weather.upstream_breakers());
+        assert_eq!(weather.breakers.len(), upstreams.len());
+        (weather, upstreams)
+    }
+
+    /// Routes the NWS points lookup and gridpoint forecast for any US point to a
+    /// one-period forecast
+    fn route_nws_forecast(server: &MockServer) {
+        server.route(
+            "/points/",
+            vec![MockResponse::json(serde_json::json!({
+                "properties": { "gridId": "TOP", "gridX": 31, "gridY": 80 }
+            }))],
+        );
+        server.route(
+            "/gridpoints/TOP/31,80/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "properties": { "periods": [{
+                    "name": "Tonight",
+                    "startTime": "2024-06-15T18:00:00-05:00",
+                    "endTime": "2024-06-16T06:00:00-05:00",
+                    "isDaytime": false,
+                    "temperature": 64,
+                    "temperatureUnit": "F",
+                    "windSpeed": "5 mph",
+                    "windDirection": "S",
+                    "shortForecast": "Mostly Clear",
+                    "detailedForecast": "Mostly clear, with a low around 64."
+                }] }
+            }))],
+        );
+    }
+
+    /// Routes the Open-Meteo daily forecast for any point to a one-day Berlin forecast
+    fn route_open_meteo_forecast(server: &MockServer) {
+        server.route(
+            "/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.41,
+                "timezone": "Europe/Berlin",
+                "daily": {
+                    "time": ["2024-06-15"],
+                    "temperature_2m_max": [21.0],
+                    "temperature_2m_min": [11.0],
+                    "weather_code": [0],
+                    "wind_speed_10m_max": [10.0],
+                    "precipitation_sum": [0.0]
+                }
+            }))],
+        );
+    }
+
+    /// The text of a forecast summary, failing the test for other shapes
+    fn summary_text(summary: ForecastSummary) -> String {
+        match summary {
+            ForecastSummary::Text(formatted) => formatted.into_text(),
+            other => panic!("expected a text forecast, got {:?}", other),
+        }
+    }
+
+    /// The text blocks of a tool result, joined
+    fn result_text(result: CallToolResult) -> String {
+        result
+            .content
+            .iter()
+            .filter_map(|content| content.as_text())
+            .map(|text| text.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn points_url_rounds_coordinates_to_four_decimals() {
+        let weather = Weather::new().unwrap();
+        let url = weather.points_url(40.712812345, -74.006012345);
+        assert_eq!(url, format!("{}/points/40.7128,-74.006", weather.nws_base));
+    }
+
+    #[test]
+    fn invalid_client_config_reports_context() {
+        let config = ClientConfig {
+            user_agent: "bad\nagent".to_string(),
+            ..ClientConfig::default()
+        };
+        let error = build_client(&config).unwrap_err();
+        assert_eq!(error.to_string(), "failed to build HTTP client");
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn trailing_slash_in_base_url_is_ignored() {
+        let urls = |base: &str| {
+            let weather = Weather {
+                nws_base: normalize_base_url(base),
+                open_meteo_base: normalize_base_url(base),
+                ..Weather::new().unwrap()
+            };
+            [
+                weather.points_url(39.7456, -97.0892),
+                weather.alerts_url("KS"),
+                weather.open_meteo_forecast_url(52.52, 13.41, Units::Metric),
+            ]
+        };
+        assert_eq!(urls("https://example.com/api/"), urls("https://example.com/api"));
+        assert_eq!(
+            urls(" https://example.com/api// ")[1],
+            "https://example.com/api/alerts/active?area=KS"
+        );
+    }
+
+    #[test]
+    fn response_schemas_generate_for_every_endpoint() {
+        for (kind, field) in [
+            (DebugUrlKind::NwsPoints, "properties"),
+            (DebugUrlKind::NwsForecast, "properties"),
+            (DebugUrlKind::OpenMeteo, "daily"),
+            (DebugUrlKind::Alerts, "features"),
+        ] {
+            let schema: serde_json::Value =
+                serde_json::from_str(&format_response_schema(&kind).unwrap()).unwrap();
+            assert_eq!(schema["type"], "object", "{:?}", kind);
+            assert!(schema["properties"].get(field).is_some(), "{:?} lacks {}", kind, field);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_uses_configured_timeout_and_user_agent() {
+        let server = MockServer::start().await;
+        server.route("/fast", vec![MockResponse::json(serde_json::json!({}))]);
+        let slow = MockResponse::json(serde_json::json!({}));
+        server.route("/slow", vec![slow.delay(std::time::Duration::from_secs(5))]);
+        let config = ClientConfig {
+            user_agent: "weather-test/1.0".to_string(),
+            timeout: Some(std::time::Duration::from_millis(200)),
+            ..ClientConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        client.get(format!("{}/fast", server.url())).send().await.unwrap();
+        assert_eq!(server.last_header("User-Agent").as_deref(), Some("weather-test/1.0"));
+
+        let started = Instant::now();
+        let error = client.get(format!("{}/slow", server.url())).send().await.unwrap_err();
+        assert!(error.is_timeout(), "{}", error);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn default_client_config_has_a_timeout() {
+        assert_eq!(ClientConfig::default().timeout, Some(std::time::Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn forecast_survives_an_alerts_failure() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        server.route(
+            "/alerts/active",
+            vec![MockResponse::status(500, "application/json", "{}")],
+        );
+        let options = ForecastOptions {
+            include_alerts: Some(true),
+            ..ForecastOptions::default()
+        };
+
+        let summary = mock_weather(&server).forecast(39.7456, -97.0892, options).await;
+        let text = summary_text(summary.unwrap());
+        assert!(text.contains("(alerts unavailable)"), "{}", text);
+        assert!(text.contains("Mostly Clear"), "{}", text);
+        assert_eq!(server.request_count("/alerts/active?point=39.7456,-97.0892"), 1);
+    }
+
+    #[test]
+    fn relative_days_map_to_date_ranges() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        // 2024-06-12 is a Wednesday
+        let wednesday = date(12);
+        assert_eq!(relative_date_range("today", wednesday), Some((date(12), date(12))));
+        assert_eq!(relative_date_range(" Tomorrow ", wednesday), Some((date(13), date(13))));
+        assert_eq!(relative_date_range("this weekend", wednesday), Some((date(15), date(16))));
+        assert_eq!(relative_date_range("next 3 days", wednesday), Some((date(12), date(14))));
+        assert_eq!(relative_date_range("next week", wednesday), None);
+    }
+
+    #[test]
+    fn this_weekend_on_a_weekend_keeps_what_is_left() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        assert_eq!(relative_date_range("this weekend", date(15)), Some((date(15), date(16))));
+        assert_eq!(relative_date_range("this weekend", date(16)), Some((date(16), date(16))));
+    }
+
+    #[tokio::test]
+    async fn tripped_breaker_fails_fast_without_a_request() {
+        let (weather, upstreams) = isolated_weather().await;
+        for (name, server) in &upstreams {
+            server.route("/", vec![MockResponse::status(500, "application/json", "{}")]);
+            let url = format!("{}/forecast?latitude=52.52&longitude=13.41", server.url());
+
+            let failed = format!("{} request failed with status: 500 Internal Server Error", name);
+            for _ in 0..5 {
+                let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+                assert_eq!(error.to_string(), failed);
+            }
+            let requests = server.requests().len();
+            let started = Instant::now();
+            let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("{} temporarily unavailable after repeated failures", name)
+            );
+            assert!(started.elapsed() < std::time::Duration::from_millis(50));
+            assert_eq!(server.requests().len(), requests);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_tripped_breaker_leaves_other_upstreams_reachable() {
+        let (weather, upstreams) = isolated_weather().await;
+        let (_, open_meteo) = &upstreams[1];
+        open_meteo.route("/", vec![MockResponse::status(500, "application/json", "{}")]);
+        let url = format!("{}/forecast", open_meteo.url());
+        for _ in 0..5 {
+            assert!(weather.make_request::<serde_json::Value>(&url).await.is_err());
+        }
+
+        let (_, archive) = &upstreams[6];
+        archive.route("/", vec![MockResponse::json(serde_json::json!({ "ok": true }))]);
+        let url = format!("{}/archive", archive.url());
+        let value = weather.make_request::<serde_json::Value>(&url).await.unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(archive.request_count("/archive"), 1);
+    }
+
+    #[tokio::test]
+    async fn chart_tolerates_short_daily_arrays() {
+        let server = MockServer::start().await;
+        server.route(
+            "/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.41,
+                "timezone": "Europe/Berlin",
+                "daily": {
+                    "time": ["2024-06-15", "2024-06-16", "2024-06-17"],
+                    "temperature_2m_max": [20.0, 22.0, 25.0],
+                    "temperature_2m_min": [10.0, 11.0],
+                    "weather_code": [0, 0, 61],
+                    "wind_speed_10m_max": [10.0, 12.0, 20.0],
+                    "precipitation_sum": [0.0, 0.0, 5.2]
+                }
+            }))],
+        );
+        let options = ForecastOptions {
+            as_chart: Some(true),
+            ..ForecastOptions::default()
+        };
+
+        let summary = mock_weather(&server).forecast(52.52, 13.41, options).await.unwrap();
+        let ForecastSummary::Chart { png, caption } = summary else {
+            panic!("expected a chart, got {:?}", summary);
+        };
+        assert!(png.is_some());
+        assert!(caption.contains("next 2 days"), "{}", caption);
+    }
+
+    #[tokio::test]
+    async fn identical_concurrent_forecasts_share_one_upstream_call() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        server.route(
+            "/points/",
+            vec![MockResponse::json(serde_json::json!({
+                "properties": { "gridId": "TOP", "gridX": 31, "gridY": 80 }
+            }))
+            .delay(std::time::Duration::from_millis(200))],
+        );
+        let weather = mock_weather(&server);
+        let request = GetForecastRequest {
+            latitude: 39.7456,
+            longitude: -97.0892,
+            ..GetForecastRequest::default()
+        };
+
+        let (first, second) = tokio::join!(
+            weather.single_flight_forecast(&request),
+            weather.single_flight_forecast(&request)
+        );
+        assert_eq!(summary_text(first.unwrap()), summary_text(second.unwrap()));
+        assert_eq!(server.request_count("/points/"), 1);
+        assert_eq!(server.request_count("/gridpoints/"), 1);
+        assert!(weather.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_back_off_exponentially() {
+        let server = MockServer::start().await;
+        server.route("/forecast", vec![MockResponse::status(429, "application/json", "{}")]);
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let weather = Weather {
+            sleeper: sleeper.clone(),
+            ..mock_weather(&server)
+        };
+        let url = format!("{}/forecast?latitude=52.52&longitude=13.41", server.url());
+
+        let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+        assert!(error.to_string().contains("429"), "{}", error);
+        assert_eq!(sleeper.delays(), [1, 2, 4].map(std::time::Duration::from_secs));
+        assert_eq!(server.request_count("/forecast"), 4);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_retries_stop_at_the_first_success() {
+        let server = MockServer::start().await;
+        let limited = MockResponse::status(429, "application/json", "{}");
+        server.route(
+            "/forecast",
+            vec![limited.clone(), limited, MockResponse::json(serde_json::json!({"ok": true}))],
+        );
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let weather = Weather {
+            sleeper: sleeper.clone(),
+            ..mock_weather(&server)
+        };
+        let url = format!("{}/forecast?latitude=52.52&longitude=13.41", server.url());
+
+        let value = weather.make_request::<serde_json::Value>(&url).await.unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(sleeper.delays(), [1, 2].map(std::time::Duration::from_secs));
+    }
+
+    #[tokio::test]
+    async fn batch_requests_stay_within_the_shared_budget() {
+        let server = MockServer::start().await;
+        server.route("/forecast", vec![MockResponse::status(429, "application/json", "{}")]);
+        let locations: Vec<serde_json::Value> = (0..10)
+            .map(|i| {
+                serde_json::json!({
+                    "name": format!("City {}", i),
+                    "latitude": 48.0 + i as f64,
+                    "longitude": 11.0
+                })
+            })
+            .collect();
+        let request = serde_json::from_value(serde_json::json!({ "locations": locations }));
+
+        let result = mock_weather(&server)
+            .rank_locations_by_temperature(Parameters(request.unwrap()))
+            .await;
+        // Every location fails, and retries stop once the batch's budget is spent
+        assert!(result.is_ok());
+        assert_eq!(server.requests().len(), 10 * BATCH_REQUESTS_PER_ITEM as usize);
+    }
+
+    #[tokio::test]
+    async fn nws_error_serves_the_stale_forecast() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        let weather = mock_weather(&server);
+        let fresh = weather.forecast(39.7456, -97.0892, ForecastOptions::default()).await;
+        assert!(!summary_text(fresh.unwrap()).contains("possibly stale"));
+
+        server.route(
+            "/gridpoints/TOP/31,80/forecast",
+            vec![MockResponse::status(500, "application/problem+json", "{}")],
+        );
+        let stale = weather.forecast(39.7456, -97.0892, ForecastOptions::default()).await;
+        let text = summary_text(stale.unwrap());
+        assert!(text.contains("(possibly stale \u{2014} NWS error)"), "{}", text);
+        assert!(text.contains("Mostly Clear"), "{}", text);
+
+        let options = ForecastOptions {
+            serve_stale_on_error: Some(false),
+            ..ForecastOptions::default()
+        };
+        assert!(weather.forecast(39.7456, -97.0892, options).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn geocoding_falls_back_to_nominatim() {
+        let open_meteo = MockServer::start().await;
+        open_meteo.route("/search", vec![MockResponse::status(500, "application/json", "{}")]);
+        let nominatim = MockServer::start().await;
+        nominatim.route(
+            "/search",
+            vec![MockResponse::json(serde_json::json!([{
+                "lat": "52.5170365",
+                "lon": "13.3888599",
+                "name": "Berlin",
+                "display_name": "Berlin, Deutschland",
+                "addresstype": "city",
+                "address": { "country_code": "de", "country": "Deutschland", "state": "Berlin" }
+            }]))],
+        );
+        let mut weather = Weather {
+            nominatim_base: nominatim.url().to_string(),
+            ..mock_weather(&open_meteo)
+        };
+        weather.breakers = Arc::new(weather.upstream_breakers());
+
+        let results = weather.geocode("Berlin", None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Berlin");
+        assert_eq!((results[0].latitude, results[0].longitude), (52.5170365, 13.3888599));
+        assert_eq!(open_meteo.request_count("/search?name=Berlin"), 1);
+        assert_eq!(nominatim.request_count("/search?q=Berlin"), 1);
+    }
+
+    #[tokio::test]
+    async fn request_errors_name_the_upstream() {
+        let nws = MockServer::start().await;
+        nws.route("/points/", vec![MockResponse::status(200, "application/json", "[1, 2")]);
+        let archive = MockServer::start().await;
+        archive.route("/v1/archive", vec![MockResponse::status(503, "application/json", "{}")]);
+        let others = MockServer::start().await;
+        let mut weather = Weather {
+            nws_base: nws.url().to_string(),
+            archive_base: archive.url().to_string(),
+            ..mock_weather(&others)
+        };
+        weather.breakers = Arc::new(weather.upstream_breakers());
+
+        let url = format!("{}/v1/archive?latitude=52.52&longitude=13.41", weather.archive_base);
+        let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Open-Meteo archive request failed with status: 503 Service Unavailable"
+        );
+
+        let url = format!("{}/points/39.7456,-97.0892", weather.nws_base);
+        let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+        assert!(error.to_string().starts_with("NWS returned an unexpected response"));
+    }
+
+    #[test]
+    fn disabled_tools_are_neither_listed_nor_routed() {
+        let router = Weather::enabled_tool_router(" get_forecast, get_alerts ,no_such_tool");
+        let mut listed: Vec<String> =
+            router.list_all().into_iter().map(|tool| tool.name.to_string()).collect();
+        listed.sort();
+        assert_eq!(listed, ["get_alerts", "get_forecast"]);
+        // Calls are dispatched through the same routes, so a removed tool is rejected
+        // as "tool not found"
+        assert!(router.has_route("get_forecast"));
+        assert!(!router.has_route("get_weather_code_legend"));
+        assert!(!router.has_route("no_such_tool"));
+
+        let all = Weather::enabled_tool_router("");
+        assert_eq!(all.list_all().len(), Weather::tool_router().list_all().len());
+        assert!(all.has_route("get_weather_code_legend"));
+    }
+
+    #[tokio::test]
+    async fn watch_alerts_returns_at_once_over_stdio() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let server = MockServer::start().await;
+        let weather = mock_weather(&server).over_stdio();
+        let (client, transport) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            if let Ok(running) = rmcp::ServiceExt::serve(weather, transport).await {
+                let _ = running.waiting().await;
+            }
+        });
+
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+        let messages = [
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "0" }
+                }
+            }),
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "watch_alerts", "arguments": { "state": "KS" } }
+            }),
+        ];
+        for message in messages {
+            writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+        }
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                let line = lines.next_line().await.unwrap().unwrap();
+                let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+                if message["id"] == 2 {
+                    return message;
+                }
+            }
+        })
+        .await
+        .expect("watch_alerts should return at once over stdio");
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("not available over the stdio transport"), "{}", text);
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn html_outage_pages_report_the_upstream_unavailable() {
+        let (weather, upstreams) = isolated_weather().await;
+        let page = "<html><body><h1>503 Service Temporarily Unavailable</h1></body></html>";
+        for (name, server) in &upstreams {
+            server.route("/down", vec![MockResponse::status(503, "text/html", page)]);
+            server.route("/ok", vec![MockResponse::status(200, "text/html", page)]);
+
+            let url = format!("{}/down", server.url());
+            let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "{} is currently unavailable (returned a non-JSON error page, status 503 \
+                     Service Unavailable)",
+                    name
+                )
+            );
+
+            let url = format!("{}/ok", server.url());
+            let error = weather.make_request::<serde_json::Value>(&url).await.unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("{} is currently unavailable (returned a non-JSON error page)", name)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn archive_ranges_are_fetched_in_chunks_with_a_retry() {
+        let server = MockServer::start().await;
+        let chunk = |date: &str, high: f64| {
+            MockResponse::json(serde_json::json!({
+                "daily": { "time": [date], "temperature_2m_max": [high] }
+            }))
+        };
+        let chunk_url =
+            |start: &str| format!("/archive?latitude=52.52&longitude=13.41&start_date={}", start);
+        server.route(&chunk_url("2023-01-01"), vec![chunk("2023-01-01", 2.0)]);
+        server.route(
+            &chunk_url("2023-04-01"),
+            vec![
+                MockResponse::status(500, "application/json", "{}"),
+                chunk("2023-04-01", 14.0),
+            ],
+        );
+        server.route(&chunk_url("2023-06-30"), vec![chunk("2023-06-30", 25.0)]);
+        let weather = mock_weather(&server);
+
+        let range = (
+            NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 7, 31).unwrap(),
+        );
+        let archive = weather
+            .fetch_archive(52.52, 13.41, range, "temperature_2m_max", Units::Metric)
+            .await
+            .unwrap();
+        assert_eq!(archive.daily.time, ["2023-01-01", "2023-04-01", "2023-06-30"]);
+        assert_eq!(archive.daily.temperature_max, [Some(2.0), Some(14.0), Some(25.0)]);
+
+        let ranges: Vec<String> = server
+            .requests()
+            .iter()
+            .map(|path| {
+                let start = path.split("start_date=").nth(1).unwrap();
+                start[..30].replace("&end_date=", " to ")
+            })
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                "2023-01-01 to 2023-03-31",
+                "2023-04-01 to 2023-06-29",
+                "2023-04-01 to 2023-06-29",
+                "2023-06-30 to 2023-07-31",
+            ]
+        );
+    }
+
+    #[test]
+    fn cache_keys_round_coordinates() {
+        let key = |latitude: &str| {
+            let query = format!("latitude={}&longitude=13.41&daily=weather_code", latitude);
+            cache_key(&format!("https://api.open-meteo.com/v1/forecast?{}", query))
+        };
+        assert_eq!(key("52.520001"), key("52.52"));
+        assert_ne!(key("52.5201"), key("52.52"));
+        assert!(key("52.520001").contains("latitude=52.52&longitude=13.41&daily=weather_code"));
+    }
+
+    #[tokio::test]
+    async fn disk_cache_serves_a_new_instance() {
+        let dir = std::env::temp_dir().join(format!("mcp-weather-cache-{}", std::process::id()));
+        let server = MockServer::start().await;
+        server.route(
+            "/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.41,
+                "timezone": "Europe/Berlin",
+                "daily": {
+                    "time": ["2024-06-15"],
+                    "temperature_2m_max": [21.0],
+                    "temperature_2m_min": [11.0],
+                    "weather_code": [3],
+                    "wind_speed_10m_max": [12.0],
+                    "precipitation_sum": [0.0],
+                }
+            }))],
+        );
+        let cached_weather = |server: &MockServer| Weather {
+            disk_cache: Some(Arc::new(DiskCache::new(dir.clone()))),
+            ..mock_weather(server)
+        };
+
+        let first = cached_weather(&server);
+        let url = first.open_meteo_forecast_url(52.520001, 13.41, Units::Metric);
+        first.fetch_open_meteo_forecast(&url).await.unwrap();
+
+        // A restarted server, with the upstream now down, answers from the cache
+        server.route("/forecast", vec![MockResponse::status(500, "application/json", "{}")]);
+        let second = cached_weather(&server);
+        let url = second.open_meteo_forecast_url(52.52, 13.41, Units::Metric);
+        let forecast = second.fetch_open_meteo_forecast(&url).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(forecast.unwrap().daily.temperature_max, [21.0]);
+        assert_eq!(server.request_count("/forecast"), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_wait_out_retry_after() {
+        let server = MockServer::start().await;
+        let in_20s = (Utc::now() + Duration::seconds(20)).format("%a, %d %b %Y %H:%M:%S GMT");
+        let limited = MockResponse::status(429, "application/json", "{}");
+        server.route(
+            "/forecast",
+            vec![
+                limited.clone().header("Retry-After", "30"),
+                limited.header("Retry-After", &in_20s.to_string()),
+                MockResponse::json(serde_json::json!({"ok": true})),
+            ],
+        );
+        let sleeper = Arc::new(RecordingSleeper::default());
+        let weather = Weather {
+            sleeper: sleeper.clone(),
+            ..mock_weather(&server)
+        };
+        let url = format!("{}/forecast?latitude=52.52&longitude=13.41", server.url());
+
+        weather.make_request::<serde_json::Value>(&url).await.unwrap();
+        let delays = sleeper.delays();
+        assert_eq!(delays.len(), 2);
+        assert_eq!(delays[0], std::time::Duration::from_secs(30));
+        // The date is whole seconds, so a second or two may pass before it is read
+        let date_delay = delays[1].as_secs();
+        assert!((18..=20).contains(&date_delay), "{:?}", delays);
+    }
+
+    /// An alert feature, with a square area of `half` degrees either side of a
+    /// center when one is given
+    fn alert(id: &str, severity: &str, area: Option<((f64, f64), f64)>) -> serde_json::Value {
+        let geometry = area.map(|((latitude, longitude), half)| {
+            let (south, north) = (latitude - half, latitude + half);
+            let (west, east) = (longitude - half, longitude + half);
+            serde_json::json!({
+                "type": "Polygon",
+                "coordinates": [[
+                    [west, south], [east, south], [east, north], [west, north], [west, south]
+                ]]
+            })
+        });
+        serde_json::json!({
+            "properties": {
+                "id": id,
+                "event": "Test Alert",
+                "severity": severity,
+                "areaDesc": "Test County",
+            },
+            "geometry": geometry,
+        })
+    }
+
+    #[test]
+    fn alerts_are_ordered_by_proximity() {
+        let mut alerts: AlertResponse = serde_json::from_value(serde_json::json!({
+            "features": [
+                alert("no-area-minor", "Minor", None),
+                alert("far", "Extreme", Some(((41.0, -95.0), 0.2))),
+                alert("no-area-severe", "Severe", None),
+                alert("near", "Moderate", Some(((39.4, -97.4), 0.2))),
+                alert("contains", "Minor", Some(((39.1, -97.1), 0.5))),
+            ]
+        }))
+        .unwrap();
+        sort_alerts_by_proximity(&mut alerts, (39.0, -97.0));
+
+        let ids: Vec<&str> =
+            alerts.features.iter().map(|feature| feature.properties.id.as_str()).collect();
+        assert_eq!(ids, ["contains", "near", "far", "no-area-severe", "no-area-minor"]);
+    }
+
+    #[tokio::test]
+    async fn debug_fetch_rejects_unknown_states() {
+        let server = MockServer::start().await;
+        let weather = Weather {
+            debug_enabled: true,
+            ..mock_weather(&server)
+        };
+        let request = serde_json::from_value(serde_json::json!({
+            "url_kind": "alerts",
+            "state": "CA&limit=500"
+        }));
+
+        let error = weather.debug_fetch(Parameters(request.unwrap())).await.unwrap_err();
+        let message = error.message.as_ref();
+        assert!(message.starts_with("Unknown state code 'CA&limit=500'"), "{}", message);
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn alert_diff_reports_new_and_expired_ids() {
+        let alerts: AlertResponse = serde_json::from_value(serde_json::json!({
+            "features": [
+                alert("kept", "Minor", None),
+                alert("new-1", "Severe", None),
+                alert("new-2", "Moderate", None),
+            ]
+        }))
+        .unwrap();
+        let seen = ["expired".to_string(), "kept".to_string()];
+
+        let (new_alerts, expired) = diff_alerts(alerts, &seen);
+        let new_ids: Vec<&str> =
+            new_alerts.features.iter().map(|feature| feature.properties.id.as_str()).collect();
+        assert_eq!(new_ids, ["new-1", "new-2"]);
+        assert_eq!(expired, ["expired"]);
+    }
+
+    /// Records, at each retry wait, whether every in-flight slot of a budget is free
+    struct SlotCheckingSleeper {
+        budget: Arc<RequestBudget>,
+        all_free: std::sync::Mutex<Vec<bool>>,
+    }
+
+    impl Sleeper for SlotCheckingSleeper {
+        fn sleep(&self, _: std::time::Duration) -> BoxFuture<'static, ()> {
+            let permits: Vec<_> = (0..BATCH_CONCURRENCY)
+                .map(|_| self.budget.spend().now_or_never().flatten())
+                .collect();
+            self.all_free.lock().unwrap().push(permits.iter().all(Option::is_some));
+            futures::future::ready(()).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_waits_release_the_batch_slot() {
+        let server = MockServer::start().await;
+        server.route(
+            "/forecast",
+            vec![
+                MockResponse::status(429, "application/json", "{}"),
+                MockResponse::json(serde_json::json!({"ok": true})),
+            ],
+        );
+        let budget = Arc::new(RequestBudget::for_batch(20));
+        let sleeper = Arc::new(SlotCheckingSleeper {
+            budget: budget.clone(),
+            all_free: Default::default(),
+        });
+        let weather = Weather {
+            budget: Some(budget),
+            sleeper: sleeper.clone(),
+            ..mock_weather(&server)
+        };
+        let url = format!("{}/forecast?latitude=52.52&longitude=13.41", server.url());
+
+        weather.make_request::<serde_json::Value>(&url).await.unwrap();
+        assert_eq!(*sleeper.all_free.lock().unwrap(), [true]);
+    }
+
+    #[tokio::test]
+    async fn only_the_requested_number_of_periods_is_rendered() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        let period = |name: &str, temperature: i32| {
+            serde_json::json!({
+                "name": name,
+                "startTime": "2024-06-15T18:00:00-05:00",
+                "endTime": "2024-06-16T06:00:00-05:00",
+                "isDaytime": false,
+                "temperature": temperature,
+                "temperatureUnit": "F",
+                "windSpeed": "5 mph",
+                "windDirection": "S",
+                "shortForecast": "Mostly Clear",
+                "detailedForecast": "Mostly clear."
+            })
+        };
+        server.route(
+            "/gridpoints/TOP/31,80/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "properties": { "periods": [
+                    period("Tonight", 64),
+                    period("Sunday", 88),
+                    period("Sunday Night", 66),
+                ] }
+            }))],
+        );
+        let options = ForecastOptions {
+            periods: Some(2),
+            ..ForecastOptions::default()
+        };
+
+        let summary = mock_weather(&server).forecast(39.7456, -97.0892, options).await;
+        let text = summary_text(summary.unwrap());
+        assert!(text.contains("Tonight:\n"), "{}", text);
+        assert!(text.contains("Sunday:\n"), "{}", text);
+        assert!(!text.contains("Sunday Night"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn each_source_appends_its_attribution_unless_disabled() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        route_open_meteo_forecast(&server);
+        let weather = mock_weather(&server);
+        let hidden = || ForecastOptions {
+            show_attribution: Some(false),
+            ..ForecastOptions::default()
+        };
+
+        for (latitude, longitude, attribution) in [
+            (39.7456, -97.0892, NWS_ATTRIBUTION),
+            (52.52, 13.41, OPEN_METEO_ATTRIBUTION),
+        ] {
+            let shown = weather.forecast(latitude, longitude, ForecastOptions::default()).await;
+            let text = summary_text(shown.unwrap());
+            assert!(text.ends_with(&format!("{}\n", attribution)), "{}", text);
+
+            let hidden = weather.forecast(latitude, longitude, hidden()).await;
+            let text = summary_text(hidden.unwrap());
+            assert!(!text.contains("Data: "), "{}", text);
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_current_weather_is_requested_only_when_asked_for() {
+        let server = MockServer::start().await;
+        server.route(
+            "/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.41,
+                "timezone": "Europe/Berlin",
+                "current_weather": {
+                    "temperature": 14.0,
+                    "windspeed": 9.0,
+                    "winddirection": 270.0,
+                    "weathercode": 0,
+                    "is_day": 1
+                },
+                "daily": {
+                    "time": ["2024-06-15"],
+                    "temperature_2m_max": [21.0],
+                    "temperature_2m_min": [11.0],
+                    "weather_code": [0],
+                    "wind_speed_10m_max": [10.0],
+                    "precipitation_sum": [0.0]
+                }
+            }))],
+        );
+        let weather = mock_weather(&server);
+
+        weather.forecast(52.52, 13.41, ForecastOptions::default()).await.unwrap();
+        assert!(!server.requests()[0].contains("current_weather"), "{:?}", server.requests());
+
+        let options = ForecastOptions {
+            include_current: Some(true),
+            ..ForecastOptions::default()
+        };
+        let summary = weather.forecast(52.52, 13.41, options).await;
+        let text = summary_text(summary.unwrap());
+        assert!(server.requests()[1].contains("&current_weather=true"), "{:?}", server.requests());
+        assert!(text.contains("Current: 14.0°C, Clear sky (day), wind 9.0 km/h"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn county_filter_matches_alerts_covering_several_counties() {
+        let server = MockServer::start().await;
+        let covering = |event: &str, geocode: serde_json::Value| {
+            let mut alert = alert(event, "Moderate", None);
+            alert["properties"]["event"] = serde_json::json!(event);
+            alert["properties"]["geocode"] = geocode;
+            alert
+        };
+        server.route(
+            "/alerts/active",
+            vec![MockResponse::json(serde_json::json!({
+                "features": [
+                    covering(
+                        "Heat Advisory",
+                        serde_json::json!({
+                            "SAME": ["006037", "006059"],
+                            "UGC": ["CAZ041", "CAZ087"]
+                        }),
+                    ),
+                    covering(
+                        "Wind Advisory",
+                        serde_json::json!({
+                            "SAME": ["006059", "006065"],
+                            "UGC": ["CAC059", "CAC065"]
+                        }),
+                    ),
+                    covering("Unlocated Alert", serde_json::Value::Null),
+                ]
+            }))],
+        );
+        let weather = mock_weather(&server);
+        let events = |county: Option<&str>| {
+            let request = serde_json::json!({ "state": "CA", "county": county });
+            let weather = &weather;
+            async move {
+                let request = serde_json::from_value(request).unwrap();
+                let text = result_text(weather.get_alerts(Parameters(request)).await.unwrap());
+                ["Heat Advisory", "Wind Advisory", "Unlocated Alert"]
+                    .into_iter()
+                    .filter(|event| text.contains(event))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(events(None).await, ["Heat Advisory", "Wind Advisory", "Unlocated Alert"]);
+        // A FIPS code is matched against each alert's SAME list, a UGC code against its UGC list
+        assert_eq!(events(Some("06037")).await, ["Heat Advisory"]);
+        assert_eq!(events(Some("06059")).await, ["Heat Advisory", "Wind Advisory"]);
+        assert_eq!(events(Some("cac065")).await, ["Wind Advisory"]);
+        assert_eq!(events(Some("CAZ041")).await, ["Heat Advisory"]);
+        assert!(events(Some("06001")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn today_hourly_comes_from_the_same_request_as_the_daily_forecast() {
+        let server = MockServer::start().await;
+        server.route(
+            "/forecast",
+            vec![MockResponse::json(serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.41,
+                "timezone": "Europe/Berlin",
+                "daily": {
+                    "time": ["2024-06-15", "2024-06-16"],
+                    "temperature_2m_max": [21.0, 23.0],
+                    "temperature_2m_min": [11.0, 12.0],
+                    "weather_code": [61, 0],
+                    "wind_speed_10m_max": [10.0, 8.0],
+                    "precipitation_sum": [1.2, 0.0]
+                },
+                "hourly": {
+                    "time": ["2024-06-15T14:00", "2024-06-15T15:00", "2024-06-16T14:00"],
+                    "temperature_2m": [20.5, 19.0, 22.0],
+                    "apparent_temperature": [19.8, 17.5, 22.4],
+                    "precipitation": [0.0, 1.2, 0.0],
+                    "weather_code": [3, 61, 0]
+                }
+            }))],
+        );
+        let options = ForecastOptions {
+            include_today_hourly: Some(true),
+            ..ForecastOptions::default()
+        };
+
+        let summary = mock_weather(&server).forecast(52.52, 13.41, options).await;
+        let text = summary_text(summary.unwrap());
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("daily=temperature_2m_max,"), "{}", requests[0]);
+        assert!(
+            requests[0]
+                .contains("&hourly=temperature_2m,apparent_temperature,precipitation,weather_code"),
+            "{}",
+            requests[0]
+        );
+        let first_hour = "Hourly for 2024-06-15:\n  14:00  20.5°C (feels 19.8°C)  Overcast\n";
+        assert!(text.contains(first_hour), "{}", text);
+        assert!(text.contains("\n  15:00  19.0°C (feels 17.5°C)  Rain, 1.2 mm\n\n"), "{}", text);
+        // Later days' hours are left out
+        assert!(!text.contains("22.0°C (feels"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn cancelled_alerts_are_dropped_unless_requested() {
+        let server = MockServer::start().await;
+        let message = |id: &str, message_type: &str| {
+            let mut alert = alert(id, "Moderate", None);
+            alert["properties"]["messageType"] = serde_json::json!(message_type);
+            alert
+        };
+        server.route(
+            "/alerts/active",
+            vec![MockResponse::json(serde_json::json!({
+                "features": [
+                    message("urn:new", "Alert"),
+                    message("urn:updated", "Update"),
+                    message("urn:cancelled", "Cancel"),
+                ]
+            }))],
+        );
+        let weather = mock_weather(&server);
+        let alerts = |include_cancelled: Option<bool>| {
+            let request = serde_json::json!({
+                "state": "KS",
+                "include_cancelled": include_cancelled,
+            });
+            let weather = &weather;
+            async move {
+                let request = serde_json::from_value(request).unwrap();
+                result_text(weather.get_alerts(Parameters(request)).await.unwrap())
+            }
+        };
+
+        let text = alerts(None).await;
+        assert!(text.contains("ID: urn:new") && text.contains("ID: urn:updated"), "{}", text);
+        assert!(!text.contains("urn:cancelled"), "{}", text);
+        assert_eq!(alerts(Some(false)).await, text);
+
+        let text = alerts(Some(true)).await;
+        assert!(text.contains("Alert 3 (CANCEL):\n  ID: urn:cancelled\n"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn forecast_accuracy_compares_the_period_with_the_nearest_observation() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        server.route(
+            "/gridpoints/TOP/31,80/stations",
+            vec![MockResponse::json(serde_json::json!({
+                "features": [
+                    { "properties": { "stationIdentifier": "KTOP", "name": "Topeka Billard" } },
+                    { "properties": { "stationIdentifier": "KFOE", "name": "Topeka Forbes" } }
+                ]
+            }))],
+        );
+        server.route(
+            "/stations/KTOP/observations/latest",
+            vec![MockResponse::json(serde_json::json!({
+                "properties": {
+                    "timestamp": "2024-06-15T18:53:00+00:00",
+                    "textDescription": "Clear",
+                    "temperature": { "value": 20.0, "unitCode": "wmoUnit:degC" }
+                }
+            }))],
+        );
+        let weather = mock_weather(&server);
+        let request = |latitude: f64, longitude: f64| {
+            let request = serde_json::json!({ "latitude": latitude, "longitude": longitude });
+            Parameters(serde_json::from_value(request).unwrap())
+        };
+
+        let result = weather.get_forecast_accuracy(request(39.7456, -97.0892)).await;
+        let text = result_text(result.unwrap());
+        assert!(
+            text.contains("Station: KTOP (Topeka Billard)\nObserved at: 2024-06-15T18:53:00+00:00"),
+            "{}",
+            text
+        );
+        // 20°C is 68°F against a forecast of 64°F
+        assert!(text.ends_with("Tonight: forecast 64°F, observed 68°F, off by 4°\n"), "{}", text);
+        assert_eq!(server.request_count("/stations/KFOE"), 0);
+
+        let error = weather.get_forecast_accuracy(request(52.52, 13.41)).await.unwrap_err();
+        assert!(error.message.contains("only cover US locations"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn event_type_filter_lists_active_events_when_nothing_matches() {
+        let server = MockServer::start().await;
+        let event = |id: &str, event: &str| {
+            let mut alert = alert(id, "Severe", None);
+            alert["properties"]["event"] = serde_json::json!(event);
+            alert
+        };
+        server.route(
+            "/alerts/active",
+            vec![MockResponse::json(serde_json::json!({
+                "features": [
+                    event("urn:1", "Tornado Warning"),
+                    event("urn:2", "Flood Watch"),
+                    event("urn:3", "Tornado Warning"),
+                ]
+            }))],
+        );
+        let weather = mock_weather(&server);
+        let alerts = |event_types: &[&str]| {
+            let request = serde_json::json!({ "state": "KS", "event_types": event_types });
+            let weather = &weather;
+            async move {
+                let request = serde_json::from_value(request).unwrap();
+                result_text(weather.get_alerts(Parameters(request)).await.unwrap())
+            }
+        };
+
+        let text = alerts(&[" tornado warning "]).await;
+        assert!(text.contains("ID: urn:1") && text.contains("ID: urn:3"), "{}", text);
+        assert!(!text.contains("Flood Watch"), "{}", text);
+
+        assert_eq!(
+            alerts(&["Tornado Watch", "Flood Warning"]).await,
+            "No active alerts match the event types: Tornado Watch, Flood Warning.\n\
+             Event types currently active: Flood Watch, Tornado Warning."
+        );
+    }
+
+    #[tokio::test]
+    async fn forecast_horizon_over_the_cap_names_the_limit() {
+        let weather = Weather {
+            max_forecast_days: 7,
+            ..Weather::new().unwrap()
+        };
+        assert!(weather.check_forecast_days(7).is_ok());
+        let error = weather.check_forecast_days(8).unwrap_err();
+        assert_eq!(
+            error.message,
+            "Requested 8 forecast days, but this server allows at most 7 \
+             (MCP_WEATHER_MAX_FORECAST_DAYS)."
+        );
+
+        // Tools reject the range before fetching anything
+        let server = MockServer::start().await;
+        let weather = Weather {
+            max_forecast_days: 7,
+            ..mock_weather(&server)
+        };
+        let end = Utc::now().date_naive() + chrono::Duration::days(9);
+        let request = serde_json::from_value(serde_json::json!({
+            "locations": [{ "name": "Berlin", "latitude": 52.52, "longitude": 13.41 }],
+            "end_date": end.format("%Y-%m-%d").to_string(),
+        }));
+        let error = weather
+            .rank_locations_by_temperature(Parameters(request.unwrap()))
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("allows at most 7"), "{}", error.message);
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn location_list_tools_reject_more_than_the_batch_limit() {
+        // A new tool taking a list of locations must be added to the calls below
+        let list_tools: Vec<String> = Weather::tool_router()
+            .list_all()
+            .into_iter()
+            .filter(|tool| {
+                let properties = tool.input_schema.get("properties");
+                properties.and_then(|properties| properties.get("locations")).is_some()
+            })
+            .map(|tool| tool.name.to_string())
+            .collect();
+        assert_eq!(list_tools, ["rank_locations_by_temperature"]);
+
+        let server = MockServer::start().await;
+        let weather = mock_weather(&server);
+        let ulm = serde_json::json!({ "name": "Ulm", "latitude": 48.4, "longitude": 10.0 });
+        let locations = |count: usize| serde_json::json!({ "locations": vec![ulm.clone(); count] });
+
+        let request = serde_json::from_value(locations(MAX_BATCH_LOCATIONS + 1)).unwrap();
+        let error = weather.rank_locations_by_temperature(Parameters(request)).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(error.message, "At most 10 locations per request; got 11.");
+
+        let request = serde_json::from_value(locations(0)).unwrap();
+        let error = weather.rank_locations_by_temperature(Parameters(request)).await.unwrap_err();
+        assert_eq!(error.message, "Provide at least one location.");
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dropping_a_forecast_aborts_the_upstream_request() {
+        let server = MockServer::start().await;
+        route_nws_forecast(&server);
+        server.route(
+            "/gridpoints/TOP/31,80/forecast",
+            vec![MockResponse::json(serde_json::json!({ "properties": { "periods": [] } }))
+                .delay(std::time::Duration::from_secs(30))],
+        );
+        let weather = mock_weather(&server);
+
+        let forecast = weather.forecast(39.7456, -97.0892, ForecastOptions::default());
+        let timed_out = tokio::time::timeout(std::time::Duration::from_millis(300), forecast).await;
+        assert!(timed_out.is_err());
+        assert_eq!(server.request_count("/gridpoints/"), 1);
+
+        // The dropped future closes its connection, which the server sees while it
+        // is still holding the response back
+        let started = Instant::now();
+        while server.hung_up_count("/gridpoints/") == 0 {
+            assert!(started.elapsed() < std::time::Duration::from_secs(5), "request not aborted");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(server.answered_count("/gridpoints/"), 0);
+        assert_eq!(server.answered_count("/points/"), 1);
+    }
+
+    #[tokio::test]
+    async fn relative_forecast_uses_the_default_units_unless_overridden() {
+        let server = MockServer::start().await;
+        route_open_meteo_forecast(&server);
+        let weather = Weather {
+            default_units: Units::Kelvin,
+            ..mock_weather(&server)
+        };
+        let forecast = |units: Option<&str>| {
+            let request = serde_json::json!({
+                "latitude": 52.52,
+                "longitude": 13.41,
+                "when": "today",
+                "units": units,
+            });
+            let weather = &weather;
+            async move {
+                let request = serde_json::from_value(request).unwrap();
+                weather.get_forecast_relative(Parameters(request)).await.map(result_text)
+            }
+        };
+
+        let text = forecast(None).await.unwrap();
+        assert!(text.contains("Temperature: 284.2 K - 294.2 K"), "{}", text);
+
+        let text = forecast(Some("imperial")).await.unwrap();
+        let requests = server.requests();
+        assert!(requests[1].contains("&temperature_unit=fahrenheit"), "{}", requests[1]);
+        assert!(text.contains("Temperature: 11.0°F - 21.0°F"), "{}", text);
+
+        let error = forecast(Some("furlongs")).await.unwrap_err();
+        assert!(error.message.starts_with("Invalid units 'furlongs'"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn client_builds_with_custom_pool_settings() {
+        let server = MockServer::start().await;
+        server.route("/ok", vec![MockResponse::json(serde_json::json!({ "ok": true }))]);
+
+        for (max_idle, idle_timeout, timeout) in [(0, 1, None), (64, 600, Some(5))] {
+            let config = ClientConfig {
+                pool_max_idle_per_host: max_idle,
+                pool_idle_timeout: std::time::Duration::from_secs(idle_timeout),
+                timeout: timeout.map(std::time::Duration::from_secs),
+                ..ClientConfig::default()
+            };
+            let client = build_client(&config).unwrap();
+            let response = client.get(format!("{}/ok", server.url())).send().await.unwrap();
+            assert!(response.status().is_success());
         }
+        assert_eq!(server.last_header("user-agent").as_deref(), Some(USER_AGENT));
     }
 }
@@ -0,0 +1,764 @@
+/// Returns the arithmetic mean, or `None` for an empty slice
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Returns `(min, mean, max)`, or `None` for an empty slice
+pub fn summarize(values: &[f64]) -> Option<(f64, f64, f64)> {
+    let min = values.iter().copied().reduce(f64::min)?;
+    let max = values.iter().copied().reduce(f64::max)?;
+    Some((min, mean(values)?, max))
+}
+
+/// Compares a value against the mean of historical values.
+///
+/// Returns `(normal, anomaly)` where `anomaly` is positive when `value` is above normal.
+pub fn anomaly(value: f64, history: &[f64]) -> Option<(f64, f64)> {
+    let normal = mean(history)?;
+    Some((normal, value - normal))
+}
+
+/// Returns the difference between the largest and smallest value, or `None` for an
+/// empty slice
+pub fn spread(values: &[f64]) -> Option<f64> {
+    let max = values.iter().copied().reduce(f64::max)?;
+    let min = values.iter().copied().reduce(f64::min)?;
+    Some(max - min)
+}
+
+/// Scores agreement between forecasts from 0 (complete disagreement) to 100 (identical).
+///
+/// Each `(spread, tolerance)` pair contributes equally; a spread at or beyond its
+/// tolerance counts as complete disagreement on that variable.
+pub fn agreement_score(spreads: &[(f64, f64)]) -> Option<u8> {
+    let parts: Vec<f64> = spreads
+        .iter()
+        .map(|&(spread, tolerance)| (1.0 - spread / tolerance).clamp(0.0, 1.0))
+        .collect();
+    mean(&parts).map(|agreement| (agreement * 100.0).round() as u8)
+}
+
+/// A run of consecutive hours with meaningful precipitation, as inclusive indices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrecipitationBlock {
+    pub start: usize,
+    pub end: usize,
+    /// Hour with the largest precipitation amount in the block
+    pub peak: usize,
+}
+
+/// Groups hours into blocks where precipitation reaches `min_amount` or its
+/// probability reaches `min_probability` percent.
+pub fn precipitation_blocks(
+    amounts: &[Option<f64>],
+    probabilities: &[Option<f64>],
+    min_amount: f64,
+    min_probability: f64,
+) -> Vec<PrecipitationBlock> {
+    let hours = amounts.len().max(probabilities.len());
+    let amount = |i: usize| amounts.get(i).copied().flatten().unwrap_or(0.0);
+    let is_wet = |i: usize| {
+        amount(i) >= min_amount
+            || probabilities.get(i).copied().flatten().unwrap_or(0.0) >= min_probability
+    };
+
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < hours {
+        if !is_wet(i) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i + 1 < hours && is_wet(i + 1) {
+            i += 1;
+        }
+        let peak = (start..=i)
+            .max_by(|&a, &b| amount(a).total_cmp(&amount(b)).then(b.cmp(&a)))
+            .unwrap_or(start);
+        blocks.push(PrecipitationBlock {
+            start,
+            end: i,
+            peak,
+        });
+        i += 1;
+    }
+    blocks
+}
+
+/// Indices of the coldest and warmest values within one group of hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyExtremes {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Finds the minimum and maximum value for each run of equal consecutive keys,
+/// such as the calendar day of each hour.
+///
+/// Missing values are skipped and a group without any values is left out. The
+/// earliest index wins ties.
+pub fn daily_extremes<K: PartialEq>(keys: &[K], values: &[Option<f64>]) -> Vec<DailyExtremes> {
+    let value = |i: usize| values.get(i).copied().flatten();
+
+    let mut extremes = Vec::new();
+    let mut start = 0;
+    while start < keys.len() {
+        let end = (start..keys.len())
+            .find(|&i| keys[i] != keys[start])
+            .unwrap_or(keys.len());
+        let present = || (start..end).filter_map(|i| Some((i, value(i)?)));
+        let min = present().reduce(|best, item| if item.1 < best.1 { item } else { best });
+        let max = present().reduce(|best, item| if item.1 > best.1 { item } else { best });
+        if let (Some((min, _)), Some((max, _))) = (min, max) {
+            extremes.push(DailyExtremes { min, max });
+        }
+        start = end;
+    }
+    extremes
+}
+
+/// Finds the longest run of consecutive values at or below `max`, as inclusive indices.
+///
+/// Missing values break a run. The earliest run wins ties.
+pub fn longest_run_at_most(values: &[Option<f64>], max: f64) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = None;
+    for (i, value) in values.iter().enumerate() {
+        if value.is_some_and(|v| v <= max) {
+            let run_start = *start.get_or_insert(i);
+            if best.is_none_or(|(s, e)| i - run_start > e - s) {
+                best = Some((run_start, i));
+            }
+        } else {
+            start = None;
+        }
+    }
+    best
+}
+
+/// Overall stargazing quality
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StargazingRating {
+    Good,
+    Fair,
+    Poor,
+}
+
+/// Rates a night from its clearest stretch and the moon's brightness.
+///
+/// Three or more clear hours make a good night unless the moon is more than half
+/// lit; any clear hour is at least fair.
+pub fn stargazing_rating(clear_hours: usize, moon_illumination: f64) -> StargazingRating {
+    match clear_hours {
+        0 => StargazingRating::Poor,
+        3.. if moon_illumination <= 0.5 => StargazingRating::Good,
+        _ => StargazingRating::Fair,
+    }
+}
+
+/// How much clearing a day's snowfall calls for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SnowImpact {
+    None,
+    Light,
+    Moderate,
+    Significant,
+}
+
+/// Classifies a day's snowfall in centimetres.
+///
+/// Under 0.5 cm is nothing to clear, under 2.5 cm a sweep, under 10 cm worth
+/// shoveling, and anything deeper significant.
+pub fn snow_impact(snowfall_cm: f64) -> SnowImpact {
+    match snowfall_cm {
+        s if s < 0.5 => SnowImpact::None,
+        s if s < 2.5 => SnowImpact::Light,
+        s if s < 10.0 => SnowImpact::Moderate,
+        _ => SnowImpact::Significant,
+    }
+}
+
+/// Points an event day earns for each factor, out of its weight in
+/// `EVENT_SCORE_WEIGHTS`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventScore {
+    pub temperature: f64,
+    pub precipitation: f64,
+    pub wind: f64,
+}
+
+impl EventScore {
+    /// The overall 0-100 score
+    pub fn total(&self) -> f64 {
+        self.temperature + self.precipitation + self.wind
+    }
+}
+
+/// Maximum points for temperature, precipitation, and wind; they sum to 100
+pub const EVENT_SCORE_WEIGHTS: (f64, f64, f64) = (40.0, 40.0, 20.0);
+
+/// Scores a day for an outdoor event from its high (°C), precipitation (mm), and
+/// peak wind (km/h).
+///
+/// Each factor earns its full weight inside a comfortable range and loses it
+/// linearly beyond: highs of 18-26°C, falling to nothing 10°C outside; no
+/// precipitation, falling to nothing at 10 mm; wind up to 15 km/h, falling to
+/// nothing at 50 km/h.
+pub fn event_score(high_c: f64, precipitation_mm: f64, wind_kmh: f64) -> EventScore {
+    let (temperature_weight, precipitation_weight, wind_weight) = EVENT_SCORE_WEIGHTS;
+    let fraction = |excess: f64, span: f64| (1.0 - excess.max(0.0) / span).clamp(0.0, 1.0);
+    let temperature_excess = (18.0 - high_c).max(high_c - 26.0);
+    EventScore {
+        temperature: temperature_weight * fraction(temperature_excess, 10.0),
+        precipitation: precipitation_weight * fraction(precipitation_mm, 10.0),
+        wind: wind_weight * fraction(wind_kmh - 15.0, 35.0),
+    }
+}
+
+/// Outdoor activities `activity_score` knows the weather preferences of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    Running,
+    Cycling,
+    Beach,
+    Skiing,
+    Gardening,
+}
+
+impl Activity {
+    pub const ALL: [Self; 5] =
+        [Self::Running, Self::Cycling, Self::Beach, Self::Skiing, Self::Gardening];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Cycling => "cycling",
+            Self::Beach => "beach",
+            Self::Skiing => "skiing",
+            Self::Gardening => "gardening",
+        }
+    }
+
+    /// Parses an activity name case-insensitively, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|activity| activity.name().eq_ignore_ascii_case(value.trim()))
+    }
+}
+
+/// A day's weather in metric units, as scored by `activity_score`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivityWeather {
+    pub high_c: f64,
+    pub precipitation_mm: f64,
+    pub wind_kmh: f64,
+    pub snowfall_cm: f64,
+    pub sunshine_hours: f64,
+}
+
+/// Points one factor of an activity score earned, out of its weight
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivityFactor {
+    pub name: &'static str,
+    pub points: f64,
+    pub weight: f64,
+}
+
+/// Scores a day for an activity; the factors' weights sum to 100.
+///
+/// Like `event_score`, each factor earns its full weight inside a comfortable
+/// range and loses it linearly beyond, over a per-activity span:
+///
+/// - running: highs of 5-18°C, dry, wind up to 25 km/h
+/// - cycling: highs of 12-25°C, dry, wind up to 15 km/h
+/// - beach: highs of 25-32°C, at most 1 mm of rain, wind up to 20 km/h, 8+ hours of sun
+/// - skiing: highs from -10 to 2°C, 5+ cm of fresh snow, wind up to 25 km/h
+/// - gardening: highs of 12-26°C, up to 2 mm of rain, wind up to 25 km/h
+pub fn activity_score(activity: Activity, weather: &ActivityWeather) -> Vec<ActivityFactor> {
+    let factor = |name, weight: f64, excess: f64, span: f64| ActivityFactor {
+        name,
+        points: weight * (1.0 - excess.max(0.0) / span).clamp(0.0, 1.0),
+        weight,
+    };
+    let temperature = |weight, (min, max): (f64, f64), span| {
+        factor("temperature", weight, (min - weather.high_c).max(weather.high_c - max), span)
+    };
+    let precipitation = |weight, dry_mm: f64, span| {
+        factor("precipitation", weight, weather.precipitation_mm - dry_mm, span)
+    };
+    let wind =
+        |weight, calm_kmh: f64, span| factor("wind", weight, weather.wind_kmh - calm_kmh, span);
+
+    match activity {
+        Activity::Running => vec![
+            temperature(40.0, (5.0, 18.0), 12.0),
+            precipitation(40.0, 0.0, 5.0),
+            wind(20.0, 25.0, 30.0),
+        ],
+        Activity::Cycling => vec![
+            temperature(30.0, (12.0, 25.0), 10.0),
+            precipitation(40.0, 0.0, 3.0),
+            wind(30.0, 15.0, 30.0),
+        ],
+        Activity::Beach => vec![
+            temperature(35.0, (25.0, 32.0), 8.0),
+            precipitation(25.0, 1.0, 2.0),
+            wind(10.0, 20.0, 25.0),
+            factor("sunshine", 30.0, 8.0 - weather.sunshine_hours, 8.0),
+        ],
+        Activity::Skiing => vec![
+            temperature(35.0, (-10.0, 2.0), 8.0),
+            factor("fresh snow", 35.0, 5.0 - weather.snowfall_cm, 5.0),
+            wind(30.0, 25.0, 35.0),
+        ],
+        Activity::Gardening => vec![
+            temperature(40.0, (12.0, 26.0), 10.0),
+            precipitation(30.0, 2.0, 8.0),
+            wind(30.0, 25.0, 25.0),
+        ],
+    }
+}
+
+/// Wind sports `wind_verdict` knows the wind preferences of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindSport {
+    Kitesurfing,
+    Windsurfing,
+    Sailing,
+}
+
+impl WindSport {
+    pub const ALL: [Self; 3] = [Self::Kitesurfing, Self::Windsurfing, Self::Sailing];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Kitesurfing => "kitesurfing",
+            Self::Windsurfing => "windsurfing",
+            Self::Sailing => "sailing",
+        }
+    }
+
+    /// Parses a sport name case-insensitively, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|sport| sport.name().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Sustained wind the sport is best in, in knots
+    pub fn range_knots(self) -> (f64, f64) {
+        match self {
+            Self::Kitesurfing => (12.0, 25.0),
+            Self::Windsurfing => (15.0, 30.0),
+            Self::Sailing => (8.0, 20.0),
+        }
+    }
+}
+
+/// Gust factor (gust / sustained) above which wind is too gusty for any wind sport
+pub const MAX_GUST_FACTOR: f64 = 1.4;
+
+/// Whether the wind suits a wind sport
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindVerdict {
+    TooLight,
+    Good,
+    /// In range, but gusting well above the sustained speed
+    Gusty,
+    TooStrong,
+}
+
+/// Returns the gust factor, gust over sustained speed, or `None` in calm air
+pub fn gust_factor(sustained: f64, gust: f64) -> Option<f64> {
+    (sustained > 0.0).then(|| gust / sustained)
+}
+
+/// Judges sustained wind and gusts, in knots, for a sport.
+///
+/// Wind is too light below the sport's range and too strong above it or when
+/// gusts pass the top of the range by more than a fifth; otherwise it is gusty when
+/// the gust factor exceeds `MAX_GUST_FACTOR`.
+pub fn wind_verdict(sport: WindSport, sustained_knots: f64, gust_knots: f64) -> WindVerdict {
+    let (min, max) = sport.range_knots();
+    if sustained_knots < min {
+        WindVerdict::TooLight
+    } else if sustained_knots > max || gust_knots > max * 1.2 {
+        WindVerdict::TooStrong
+    } else if gust_factor(sustained_knots, gust_knots).is_some_and(|f| f > MAX_GUST_FACTOR) {
+        WindVerdict::Gusty
+    } else {
+        WindVerdict::Good
+    }
+}
+
+/// Whether a (latitude, longitude) point lies inside a polygon ring of (latitude,
+/// longitude) vertices, by ray casting; treats the ring as flat, which is fine at
+/// the scale of alert areas
+pub fn point_in_ring(point: (f64, f64), ring: &[(f64, f64)]) -> bool {
+    let (y, x) = point;
+    let mut inside = false;
+    for (i, &(y1, x1)) in ring.iter().enumerate() {
+        let (y2, x2) = ring[(i + 1) % ring.len()];
+        if (y1 > y) != (y2 > y) && x < x1 + (y - y1) / (y2 - y1) * (x2 - x1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// The mean of a ring's vertices, or `None` for an empty ring; close enough to the
+/// centroid to rank nearby areas
+pub fn ring_center(ring: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let latitudes: Vec<f64> = ring.iter().map(|&(latitude, _)| latitude).collect();
+    let longitudes: Vec<f64> = ring.iter().map(|&(_, longitude)| longitude).collect();
+    Some((mean(&latitudes)?, mean(&longitudes)?))
+}
+
+/// Great-circle distance between two coordinates in kilometres (haversine formula)
+pub fn great_circle_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Expected effect of the weather on a road journey
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TravelImpact {
+    Clear,
+    RainSlowed,
+    SnowHazard,
+}
+
+/// Classifies travel impact from hourly `(weather_code, precipitation_mm)` samples,
+/// taking the worst hour.
+///
+/// Snow and freezing precipitation are hazards; rain, drizzle, showers, storms, or at
+/// least 0.5 mm/h of precipitation slow traffic.
+pub fn travel_impact(samples: &[(Option<i32>, Option<f64>)]) -> TravelImpact {
+    samples
+        .iter()
+        .map(|&(code, precipitation)| match code {
+            Some(56 | 57 | 66 | 67 | 71..=77 | 85 | 86) => TravelImpact::SnowHazard,
+            Some(51..=55 | 61..=65 | 80..=82 | 95..=99) => TravelImpact::RainSlowed,
+            _ if precipitation.is_some_and(|mm| mm >= 0.5) => TravelImpact::RainSlowed,
+            _ => TravelImpact::Clear,
+        })
+        .max()
+        .unwrap_or(TravelImpact::Clear)
+}
+
+/// Heating and cooling degree days for one day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DegreeDays {
+    pub heating: f64,
+    pub cooling: f64,
+}
+
+/// Computes a day's degree days against `base`, taking the mean of its high and low
+/// as the day's temperature
+pub fn degree_days(high: f64, low: f64, base: f64) -> DegreeDays {
+    let mean = (high + low) / 2.0;
+    DegreeDays {
+        heating: (base - mean).max(0.0),
+        cooling: (mean - base).max(0.0),
+    }
+}
+
+/// Returns the `p`th percentile (0-100), interpolating linearly between the closest
+/// ranks, or `None` for an empty slice
+pub fn percentile(values: &[f64], p: f64) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let last = sorted.len().checked_sub(1)?;
+    let rank = p.clamp(0.0, 100.0) / 100.0 * last as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// The median and interquartile range of a set of values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quartiles {
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+}
+
+/// Returns the 25th, 50th, and 75th percentiles, or `None` for an empty slice
+pub fn quartiles(values: &[f64]) -> Option<Quartiles> {
+    Some(Quartiles {
+        p25: percentile(values, 25.0)?,
+        median: percentile(values, 50.0)?,
+        p75: percentile(values, 75.0)?,
+    })
+}
+
+/// How one day's high compares with the previous day's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HighChange {
+    /// Warmer by this many degrees
+    Warmer(f64),
+    /// Cooler by this many degrees
+    Cooler(f64),
+    Similar,
+}
+
+/// Compares two highs in the same unit; differences smaller than `threshold` are
+/// similar
+pub fn compare_highs(today: f64, yesterday: f64, threshold: f64) -> HighChange {
+    let difference = today - yesterday;
+    if difference.abs() < threshold {
+        HighChange::Similar
+    } else if difference > 0.0 {
+        HighChange::Warmer(difference)
+    } else {
+        HighChange::Cooler(-difference)
+    }
+}
+
+/// Returns the least-squares slope of evenly spaced values, in units per step, or
+/// `None` with fewer than two values
+pub fn linear_slope(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = mean(values)?;
+    let mut covariance = 0.0;
+    let mut variance = 0.0;
+    for (i, y) in values.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+    Some(covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(start: usize, end: usize, peak: usize) -> PrecipitationBlock {
+        PrecipitationBlock { start, end, peak }
+    }
+
+    #[test]
+    fn precipitation_blocks_group_wet_hours() {
+        let amounts = [Some(0.0), Some(0.4), Some(1.2), None, Some(0.0), Some(0.3)];
+        let probabilities = [Some(10.0), Some(20.0), Some(80.0), Some(60.0), Some(5.0), None];
+        assert_eq!(
+            precipitation_blocks(&amounts, &probabilities, 0.2, 50.0),
+            vec![block(1, 3, 2), block(5, 5, 5)]
+        );
+    }
+
+    #[test]
+    fn precipitation_blocks_are_empty_for_dry_hours() {
+        let amounts = [Some(0.0), Some(0.1), None];
+        assert!(precipitation_blocks(&amounts, &[], 0.2, 50.0).is_empty());
+    }
+
+    #[test]
+    fn spread_is_the_range_of_values() {
+        assert_eq!(spread(&[21.5, 19.0, 23.0]), Some(4.0));
+        assert_eq!(spread(&[]), None);
+    }
+
+    #[test]
+    fn agreement_score_falls_as_models_diverge() {
+        assert_eq!(agreement_score(&[(0.0, 5.0), (0.0, 10.0)]), Some(100));
+        assert_eq!(agreement_score(&[(2.5, 5.0), (5.0, 10.0)]), Some(50));
+        assert_eq!(agreement_score(&[(0.0, 5.0), (10.0, 10.0)]), Some(50));
+        assert_eq!(agreement_score(&[(8.0, 5.0), (25.0, 10.0)]), Some(0));
+        assert_eq!(agreement_score(&[]), None);
+    }
+
+    #[test]
+    fn longest_clear_run_breaks_on_cloud_and_gaps() {
+        let cloud = [Some(80.0), Some(10.0), Some(20.0), None, Some(5.0), Some(0.0), Some(15.0)];
+        assert_eq!(longest_run_at_most(&cloud, 20.0), Some((4, 6)));
+        assert_eq!(longest_run_at_most(&cloud[..3], 20.0), Some((1, 2)));
+        assert_eq!(longest_run_at_most(&[Some(90.0)], 20.0), None);
+    }
+
+    #[test]
+    fn stargazing_needs_clear_hours_and_a_dim_moon() {
+        assert_eq!(stargazing_rating(4, 0.2), StargazingRating::Good);
+        assert_eq!(stargazing_rating(4, 0.9), StargazingRating::Fair);
+        assert_eq!(stargazing_rating(1, 0.0), StargazingRating::Fair);
+        assert_eq!(stargazing_rating(0, 0.0), StargazingRating::Poor);
+    }
+
+    #[test]
+    fn travel_impact_takes_the_worst_sample() {
+        assert_eq!(travel_impact(&[(Some(0), Some(0.0)), (Some(3), None)]), TravelImpact::Clear);
+        assert_eq!(travel_impact(&[(Some(0), None), (Some(61), None)]), TravelImpact::RainSlowed);
+        assert_eq!(
+            travel_impact(&[(Some(61), Some(4.0)), (Some(73), None), (Some(0), None)]),
+            TravelImpact::SnowHazard
+        );
+        assert_eq!(travel_impact(&[]), TravelImpact::Clear);
+    }
+
+    #[test]
+    fn travel_impact_counts_precipitation_without_a_wet_code() {
+        assert_eq!(travel_impact(&[(None, Some(0.5))]), TravelImpact::RainSlowed);
+        assert_eq!(travel_impact(&[(Some(2), Some(0.4))]), TravelImpact::Clear);
+    }
+
+    #[test]
+    fn daily_extremes_group_hours_by_day() {
+        let days = ["06-15", "06-15", "06-15", "06-16", "06-16", "06-17"];
+        let temperatures = [Some(12.0), Some(18.0), Some(15.0), None, Some(9.0), None];
+        let extremes = daily_extremes(&days, &temperatures);
+        let pairs: Vec<(usize, usize)> = extremes.iter().map(|e| (e.min, e.max)).collect();
+        // The last day has no readings and is skipped
+        assert_eq!(pairs, [(0, 1), (4, 4)]);
+    }
+
+    #[test]
+    fn daily_extremes_keep_the_first_of_equal_values() {
+        let extremes = daily_extremes(&[1, 1, 1], &[Some(5.0), Some(5.0), Some(7.0)]);
+        assert_eq!(extremes, [DailyExtremes { min: 0, max: 2 }]);
+    }
+
+    #[test]
+    fn degree_days_use_the_mean_of_high_and_low() {
+        let days = |heating, cooling| DegreeDays { heating, cooling };
+        assert_eq!(degree_days(14.0, 6.0, 18.0), days(8.0, 0.0));
+        assert_eq!(degree_days(32.0, 18.0, 18.0), days(0.0, 7.0));
+        assert_eq!(degree_days(65.0, 65.0, 65.0), days(0.0, 0.0));
+    }
+
+    #[test]
+    fn percentile_interpolates_between_ranks() {
+        let values = [15.0, 20.0, 10.0, 30.0, 25.0];
+        assert_eq!(percentile(&values, 0.0), Some(10.0));
+        assert_eq!(percentile(&values, 50.0), Some(20.0));
+        assert_eq!(percentile(&values, 100.0), Some(30.0));
+        assert_eq!(percentile(&values, 90.0), Some(28.0));
+        assert_eq!(percentile(&values, 150.0), Some(30.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn quartiles_of_an_even_count() {
+        let even = quartiles(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!((even.p25, even.median, even.p75), (1.75, 2.5, 3.25));
+        assert_eq!(quartiles(&[7.0]).map(|single| single.median), Some(7.0));
+        assert_eq!(quartiles(&[]), None);
+    }
+
+    #[test]
+    fn linear_slope_fits_a_least_squares_line() {
+        assert_eq!(linear_slope(&[10.0, 12.0, 14.0, 16.0]), Some(2.0));
+        assert_eq!(linear_slope(&[20.0, 20.0, 20.0]), Some(0.0));
+        assert_eq!(linear_slope(&[5.0, 1.0, 3.0, 0.0]), Some(-1.3));
+        assert_eq!(linear_slope(&[12.0]), None);
+    }
+
+    #[test]
+    fn snow_impact_grows_with_depth() {
+        assert_eq!(snow_impact(0.0), SnowImpact::None);
+        assert_eq!(snow_impact(0.4), SnowImpact::None);
+        assert_eq!(snow_impact(0.5), SnowImpact::Light);
+        assert_eq!(snow_impact(2.5), SnowImpact::Moderate);
+        assert_eq!(snow_impact(9.9), SnowImpact::Moderate);
+        assert_eq!(snow_impact(10.0), SnowImpact::Significant);
+    }
+
+    #[test]
+    fn event_score_is_full_in_comfortable_weather() {
+        assert_eq!(event_score(22.0, 0.0, 10.0).total(), 100.0);
+        assert_eq!(event_score(26.0, 0.0, 15.0).total(), 100.0);
+    }
+
+    #[test]
+    fn event_score_falls_off_linearly_outside_the_comfortable_range() {
+        let score = event_score(13.0, 2.5, 32.5);
+        assert_eq!(score.temperature, 20.0);
+        assert_eq!(score.precipitation, 30.0);
+        assert_eq!(score.wind, 10.0);
+        assert_eq!(score.total(), 60.0);
+
+        let washout = event_score(40.0, 25.0, 80.0);
+        assert_eq!(washout.total(), 0.0);
+    }
+
+    fn activity_day(high_c: f64, precipitation_mm: f64, wind_kmh: f64) -> ActivityWeather {
+        ActivityWeather {
+            high_c,
+            precipitation_mm,
+            wind_kmh,
+            snowfall_cm: 0.0,
+            sunshine_hours: 10.0,
+        }
+    }
+
+    fn points(factors: &[ActivityFactor]) -> f64 {
+        factors.iter().map(|factor| factor.points).sum()
+    }
+
+    #[test]
+    fn activity_weights_sum_to_one_hundred() {
+        for activity in Activity::ALL {
+            let factors = activity_score(activity, &activity_day(15.0, 0.0, 10.0));
+            let weights: f64 = factors.iter().map(|factor| factor.weight).sum();
+            assert_eq!(weights, 100.0, "{}", activity.name());
+        }
+    }
+
+    #[test]
+    fn activity_score_loses_points_outside_comfortable_ranges() {
+        let run = activity_score(Activity::Running, &activity_day(12.0, 0.0, 10.0));
+        assert_eq!(points(&run), 100.0);
+        let beach = activity_score(Activity::Beach, &activity_day(28.0, 0.5, 10.0));
+        assert_eq!(points(&beach), 100.0);
+
+        // 6°C too warm for running loses half of the 40 temperature points over a 12°C span
+        let warm = activity_score(Activity::Running, &activity_day(24.0, 0.0, 10.0));
+        assert_eq!(warm[0].name, "temperature");
+        assert_eq!(warm[0].points, 20.0);
+
+        let mut powder = activity_day(-5.0, 0.0, 10.0);
+        powder.snowfall_cm = 2.5;
+        let skiing = activity_score(Activity::Skiing, &powder);
+        assert_eq!(skiing[1].name, "fresh snow");
+        assert_eq!(skiing[1].points, 17.5);
+        assert_eq!(points(&skiing), 82.5);
+    }
+
+    #[test]
+    fn compare_highs_treats_small_differences_as_similar() {
+        assert_eq!(compare_highs(22.0, 20.0, 0.5), HighChange::Warmer(2.0));
+        assert_eq!(compare_highs(18.0, 20.5, 0.5), HighChange::Cooler(2.5));
+        assert_eq!(compare_highs(20.4, 20.0, 0.5), HighChange::Similar);
+    }
+
+    #[test]
+    fn gust_factor_is_undefined_in_calm_air() {
+        assert_eq!(gust_factor(10.0, 15.0), Some(1.5));
+        assert_eq!(gust_factor(0.0, 5.0), None);
+    }
+
+    #[test]
+    fn wind_verdict_checks_the_range_and_gusts() {
+        let verdict = |sustained, gust| wind_verdict(WindSport::Kitesurfing, sustained, gust);
+        assert_eq!(verdict(8.0, 10.0), WindVerdict::TooLight);
+        assert_eq!(verdict(18.0, 22.0), WindVerdict::Good);
+        assert_eq!(verdict(15.0, 24.0), WindVerdict::Gusty);
+        assert_eq!(verdict(28.0, 30.0), WindVerdict::TooStrong);
+        // Gusts past the top of the range by more than a fifth are too strong
+        assert_eq!(verdict(24.0, 31.0), WindVerdict::TooStrong);
+        assert_eq!(wind_verdict(WindSport::Sailing, 8.0, 10.0), WindVerdict::Good);
+    }
+}
@@ -0,0 +1,227 @@
+//! A scripted HTTP server standing in for the upstream APIs in tests
+
+use futures::future::{BoxFuture, FutureExt};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::retry::Sleeper;
+
+/// A sleeper that returns at once, recording each requested delay
+#[derive(Default)]
+pub struct RecordingSleeper {
+    delays: Mutex<Vec<Duration>>,
+}
+
+impl RecordingSleeper {
+    pub fn delays(&self) -> Vec<Duration> {
+        self.delays.lock().unwrap().clone()
+    }
+}
+
+impl Sleeper for RecordingSleeper {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.delays.lock().unwrap().push(duration);
+        futures::future::ready(()).boxed()
+    }
+}
+
+/// A canned response served by [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+    delay: Duration,
+}
+
+impl MockResponse {
+    /// A 200 response with a JSON body
+    pub fn json(body: serde_json::Value) -> Self {
+        Self::status(200, "application/json", &body.to_string())
+    }
+
+    /// A response with any status, content type, and body
+    pub fn status(status: u16, content_type: &str, body: &str) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", content_type.to_string())],
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Adds a response header
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    /// Holds the response back for a while, to keep a request in flight
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by [`MockServer`]
+#[derive(Debug, Clone)]
+struct MockRequest {
+    /// Path and query
+    path: String,
+    /// Header names, lowercased, with their values
+    headers: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Path prefixes with the responses still queued for them
+    routes: Vec<(String, VecDeque<MockResponse>)>,
+    /// Every request received, in order
+    requests: Vec<MockRequest>,
+    /// Paths whose response was written in full
+    answered: Vec<String>,
+    /// Paths whose client hung up while the response was held back
+    hung_up: Vec<String>,
+}
+
+/// An HTTP server on a local port that answers each request from the responses
+/// queued for the longest matching path prefix; the last response repeats.
+/// Unrouted paths get a 404.
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(respond(stream, server_state.clone()));
+            }
+        });
+        Self { url, state }
+    }
+
+    /// The base URL, without a trailing slash
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Serves `responses` in order to requests whose path and query start with `prefix`
+    pub fn route(&self, prefix: &str, responses: Vec<MockResponse>) {
+        assert!(!responses.is_empty(), "a route needs at least one response");
+        let mut state = self.state.lock().unwrap();
+        state.routes.retain(|(existing, _)| existing != prefix);
+        state.routes.push((prefix.to_string(), responses.into()));
+    }
+
+    /// The path and query of every request received so far
+    pub fn requests(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.requests.iter().map(|request| request.path.clone()).collect()
+    }
+
+    /// A header of the most recent request, looked up case-insensitively
+    pub fn last_header(&self, name: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        let request = state.requests.last()?;
+        request
+            .headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+
+    /// How many requests were received for paths starting with `prefix`
+    pub fn request_count(&self, prefix: &str) -> usize {
+        self.requests().iter().filter(|path| path.starts_with(prefix)).count()
+    }
+
+    /// How many responses were written in full for paths starting with `prefix`
+    pub fn answered_count(&self, prefix: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state.answered.iter().filter(|path| path.starts_with(prefix)).count()
+    }
+
+    /// How many clients hung up on a delayed response for paths starting with `prefix`
+    pub fn hung_up_count(&self, prefix: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state.hung_up.iter().filter(|path| path.starts_with(prefix)).count()
+    }
+}
+
+async fn respond(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut lines = request.lines();
+    let path = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/")
+        .to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(MockRequest {
+            path: path.clone(),
+            headers,
+        });
+        state
+            .routes
+            .iter_mut()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, responses)| match responses.len() {
+                1 => responses[0].clone(),
+                _ => responses.pop_front().unwrap(),
+            })
+            .unwrap_or_else(|| MockResponse::status(404, "application/json", "{}"))
+    };
+
+    // A client that drops the request closes the connection; the only other read
+    // would be an unexpected pipelined request, so either way stop waiting
+    let hung_up = tokio::select! {
+        _ = tokio::time::sleep(response.delay) => false,
+        _ = stream.read(&mut buffer) => true,
+    };
+    if hung_up {
+        state.lock().unwrap().hung_up.push(path);
+        return;
+    }
+
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let written = stream.write_all(head.as_bytes()).await.is_ok()
+        && stream.write_all(response.body.as_bytes()).await.is_ok();
+    let _ = stream.shutdown().await;
+    if written {
+        state.lock().unwrap().answered.push(path);
+    }
+}
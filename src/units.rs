@@ -0,0 +1,239 @@
+/// Unit system for Open-Meteo forecasts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Units {
+    /// Celsius, km/h, mm
+    #[default]
+    Metric,
+    /// Fahrenheit, mph, inches
+    Imperial,
+    /// Metric with temperatures converted to Kelvin
+    Kelvin,
+}
+
+impl Units {
+    /// Parses a user-supplied unit system name, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "metric" => Some(Self::Metric),
+            "imperial" => Some(Self::Imperial),
+            "kelvin" => Some(Self::Kelvin),
+            _ => None,
+        }
+    }
+
+    /// Open-Meteo query parameters selecting this unit system.
+    ///
+    /// Open-Meteo has no Kelvin option, so Kelvin requests Celsius and converts locally.
+    pub fn open_meteo_params(self) -> &'static str {
+        match self {
+            Self::Metric | Self::Kelvin => "",
+            Self::Imperial => "&temperature_unit=fahrenheit&wind_speed_unit=mph&precipitation_unit=inch",
+        }
+    }
+}
+
+/// How wind speeds are presented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindScale {
+    /// Speed in the response's unit, e.g. "24.5 km/h"
+    #[default]
+    Exact,
+    /// Beaufort force number and descriptor
+    Beaufort,
+}
+
+impl WindScale {
+    /// Parses a user-supplied wind scale name, returning `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "exact" => Some(Self::Exact),
+            "beaufort" => Some(Self::Beaufort),
+            _ => None,
+        }
+    }
+}
+
+/// Upper bounds in km/h (exclusive) for Beaufort forces 0 through 11, with descriptors
+const BEAUFORT: [(f64, &str); 12] = [
+    (1.0, "Calm"),
+    (6.0, "Light air"),
+    (12.0, "Light breeze"),
+    (20.0, "Gentle breeze"),
+    (29.0, "Moderate breeze"),
+    (39.0, "Fresh breeze"),
+    (50.0, "Strong breeze"),
+    (62.0, "Near gale"),
+    (75.0, "Gale"),
+    (89.0, "Strong gale"),
+    (103.0, "Storm"),
+    (118.0, "Violent storm"),
+];
+
+/// Converts a wind speed in km/h to its Beaufort force and descriptor
+pub fn to_beaufort(speed_kmh: f64) -> (u8, &'static str) {
+    BEAUFORT
+        .iter()
+        .zip(0u8..)
+        .find(|((upper, _), _)| speed_kmh < *upper)
+        .map(|((_, name), force)| (force, *name))
+        .unwrap_or((12, "Hurricane force"))
+}
+
+/// Units for wind-sport speeds, which are usually quoted in knots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpeedUnit {
+    #[default]
+    Knots,
+    Kmh,
+    Mph,
+    MetresPerSecond,
+}
+
+impl SpeedUnit {
+    /// Parses a user-supplied speed unit ("kn", "kmh", "mph", or "ms"), returning
+    /// `None` if unrecognized
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "kn" | "kt" | "knots" => Some(Self::Knots),
+            "kmh" | "km/h" => Some(Self::Kmh),
+            "mph" => Some(Self::Mph),
+            "ms" | "m/s" => Some(Self::MetresPerSecond),
+            _ => None,
+        }
+    }
+
+    /// Open-Meteo's `wind_speed_unit` value for this unit
+    pub fn open_meteo_value(self) -> &'static str {
+        match self {
+            Self::Knots => "kn",
+            Self::Kmh => "kmh",
+            Self::Mph => "mph",
+            Self::MetresPerSecond => "ms",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Knots => "kn",
+            Self::Kmh => "km/h",
+            Self::Mph => "mph",
+            Self::MetresPerSecond => "m/s",
+        }
+    }
+
+    /// Converts a speed in this unit to knots
+    pub fn to_knots(self, speed: f64) -> f64 {
+        match self {
+            Self::Knots => speed,
+            Self::Kmh => speed / 1.852,
+            Self::Mph => speed * 1.609_344 / 1.852,
+            Self::MetresPerSecond => speed * 3.6 / 1.852,
+        }
+    }
+}
+
+/// Converts miles per hour to kilometres per hour
+pub fn mph_to_kmh(mph: f64) -> f64 {
+    mph * 1.609_344
+}
+
+/// Converts Celsius to Fahrenheit
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+/// Converts Fahrenheit to Celsius
+pub fn fahrenheit_to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// Converts Celsius to Kelvin
+pub fn celsius_to_kelvin(celsius: f64) -> f64 {
+    celsius + 273.15
+}
+
+/// Computes the NWS wind chill in °F.
+///
+/// Returns `None` outside the formula's valid range (above 50°F or below 3 mph wind).
+pub fn wind_chill_f(temperature_f: f64, wind_speed_mph: f64) -> Option<f64> {
+    if temperature_f > 50.0 || wind_speed_mph < 3.0 {
+        return None;
+    }
+
+    let v = wind_speed_mph.powf(0.16);
+    Some(35.74 + 0.6215 * temperature_f - 35.75 * v + 0.4275 * temperature_f * v)
+}
+
+/// Computes the NWS heat index in °F using the Rothfusz regression and its adjustments.
+///
+/// Returns `None` below 80°F, where the heat index is not meaningful.
+pub fn heat_index_f(temperature_f: f64, relative_humidity: f64) -> Option<f64> {
+    if temperature_f < 80.0 {
+        return None;
+    }
+
+    let t = temperature_f;
+    let rh = relative_humidity;
+
+    // NWS uses the simple formula unless its average with the temperature reaches 80°F
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return Some(simple);
+    }
+
+    let mut hi = -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh;
+
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        hi -= ((13.0 - rh) / 4.0) * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        hi += ((rh - 85.0) / 10.0) * ((87.0 - t) / 5.0);
+    }
+
+    Some(hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wind_chill_matches_nws_chart() {
+        assert_eq!(wind_chill_f(5.0, 15.0).map(f64::round), Some(-13.0));
+        assert_eq!(wind_chill_f(-10.0, 30.0).map(f64::round), Some(-39.0));
+        assert_eq!(wind_chill_f(30.0, 10.0).map(f64::round), Some(21.0));
+    }
+
+    #[test]
+    fn wind_chill_is_undefined_when_warm_or_calm() {
+        assert_eq!(wind_chill_f(51.0, 20.0), None);
+        assert_eq!(wind_chill_f(20.0, 2.0), None);
+    }
+
+    #[test]
+    fn heat_index_matches_nws_chart() {
+        assert_eq!(heat_index_f(90.0, 60.0).map(f64::round), Some(100.0));
+        assert_eq!(heat_index_f(100.0, 40.0).map(f64::round), Some(109.0));
+        assert_eq!(heat_index_f(80.0, 40.0).map(f64::round), Some(80.0));
+    }
+
+    #[test]
+    fn heat_index_is_undefined_below_80f() {
+        assert_eq!(heat_index_f(79.0, 90.0), None);
+    }
+
+    #[test]
+    fn beaufort_force_follows_the_scale_boundaries() {
+        assert_eq!(to_beaufort(0.0), (0, "Calm"));
+        assert_eq!(to_beaufort(5.9), (1, "Light air"));
+        assert_eq!(to_beaufort(6.0), (2, "Light breeze"));
+        assert_eq!(to_beaufort(45.0), (6, "Strong breeze"));
+        assert_eq!(to_beaufort(117.9), (11, "Violent storm"));
+        assert_eq!(to_beaufort(118.0), (12, "Hurricane force"));
+    }
+}